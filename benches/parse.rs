@@ -0,0 +1,101 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use cea708_types::{tables::*, *};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations<R>(f: impl FnOnce() -> R) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn packet_bytes() -> Vec<u8> {
+    let mut packet = DTVCCPacket::new(0);
+    for service_no in 1..=4 {
+        let mut service = Service::new(service_no);
+        service
+            .push_code(SetPenLocationArgs::new(service_no, 0))
+            .unwrap();
+        for c in "Hello World".chars() {
+            service.push_code(Code::from_char(c).unwrap()).unwrap();
+        }
+        service.push_code(Code::CR).unwrap();
+        packet.push_service(service).unwrap();
+    }
+    packet.to_bytes()
+}
+
+fn count_chars_owned(data: &[u8]) -> usize {
+    let packet = DTVCCPacket::parse(data).unwrap();
+    packet
+        .services()
+        .iter()
+        .map(|service| {
+            service
+                .codes()
+                .iter()
+                .filter(|code| code.char().is_some())
+                .count()
+        })
+        .sum()
+}
+
+fn count_chars_ref(data: &[u8]) -> usize {
+    let packet = DTVCCPacket::parse_ref(data).unwrap();
+    packet
+        .services()
+        .map(|service| {
+            service
+                .unwrap()
+                .codes()
+                .filter(|code| code.as_ref().unwrap().char().is_some())
+                .count()
+        })
+        .sum()
+}
+
+fn parse(c: &mut Criterion) {
+    let data = packet_bytes();
+    assert_eq!(count_chars_owned(&data), count_chars_ref(&data));
+    println!(
+        "allocations per packet: parse {}, parse_ref {}",
+        allocations(|| count_chars_owned(&data)),
+        allocations(|| count_chars_ref(&data)),
+    );
+
+    let mut group = c.benchmark_group("DTVCCPacket");
+    group.bench_function("parse", |b| b.iter(|| count_chars_owned(black_box(&data))));
+    group.bench_function("parse_ref", |b| {
+        b.iter(|| count_chars_ref(black_box(&data)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use cea708_types::{tables::*, *};
+
+fn service() -> Service {
+    let mut service = Service::new(1);
+    service.push_code(SetPenLocationArgs::new(0, 0)).unwrap();
+    for c in "The quick brown fox".chars() {
+        service.push_code(Code::from_char(c).unwrap()).unwrap();
+    }
+    service.push_code(Code::CR).unwrap();
+    service.push_code(Code::Ext1(Ext1::TradeMarkSign)).unwrap();
+    service.push_code(Code::ETX).unwrap();
+    service
+}
+
+fn text(c: &mut Criterion) {
+    let service = service();
+    let codes = service.codes();
+
+    let mut group = c.benchmark_group("text");
+    group.bench_function("text_from_codes", |b| {
+        b.iter(|| Code::text_from_codes(black_box(codes)))
+    });
+    group.bench_function("text_from_service", |b| {
+        b.iter(|| Code::text_from_service(black_box(&service)))
+    });
+    group.bench_function("naive_collect", |b| {
+        b.iter(|| {
+            black_box(codes)
+                .iter()
+                .filter_map(|code| code.char())
+                .collect::<String>()
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("char_count");
+    group.bench_function("text_char_count", |b| {
+        b.iter(|| Code::text_char_count(black_box(codes)))
+    });
+    group.bench_function("naive_count", |b| {
+        b.iter(|| {
+            black_box(codes)
+                .iter()
+                .filter(|code| code.char().is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, text);
+criterion_main!(benches);
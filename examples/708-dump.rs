@@ -30,44 +30,28 @@ fn main() -> std::process::ExitCode {
     }
 
     let file = std::fs::File::open(args[1].clone()).unwrap();
-    let mut buf_reader = std::io::BufReader::new(file);
+    let buf_reader = std::io::BufReader::new(file);
 
-    let mut parser = CCDataParser::new();
+    let reader = CCDataReader::new(buf_reader);
 
-    let mut i = 0;
-    'l: loop {
-        use std::io::{Read, Seek};
-        // XXX: this has a hardcoded packet size
-        let mut tmp = [0; 62];
-        tmp[0] = 0x40 | 0x14;
-        let buf_pos = buf_reader.stream_position().unwrap();
-        let mut size = 0;
-        while size < 60 {
-            let n_read = buf_reader.read(&mut tmp[2 + size..]).unwrap();
-            if n_read == 0 {
-                break 'l;
+    for (i, packet) in reader.enumerate() {
+        let packet = match packet {
+            Ok(packet) => packet,
+            Err(e) => {
+                eprintln!("{i} error parsing {e:?}");
+                continue;
             }
-            size += n_read;
-        }
-        debug!("{i} read {size} bytes at {buf_pos} from {}", args[1]);
-
-        trace!("{i} parsing {:?}", &tmp[..size]);
-        if let Err(e) = parser.push(&tmp[..size + 2]) {
-            eprintln!("{i} error parsing {e:?}");
-        }
-
-        while let Some(packet) = parser.pop_packet() {
-            println!("{i} start DTVCCPacket:{}", packet.sequence_no());
-            for service in packet.services().iter() {
-                println!("{i}  start Service:{}", service.number());
-                for code in service.codes() {
-                    println!("{i}   {code:?}");
-                }
-                println!("{i}  end Service:{}", service.number());
+        };
+        debug!("{i} parsed {packet:?} from {}", args[1]);
+        println!("{i} start DTVCCPacket:{}", packet.sequence_no());
+        for service in packet.services().iter() {
+            println!("{i}  start Service:{}", service.number());
+            for code in service.codes() {
+                println!("{i}   {code:?}");
             }
-            println!("{i} end DTVCCPacket:{}", packet.sequence_no());
+            println!("{i}  end Service:{}", service.number());
         }
-        i += 1;
+        println!("{i} end DTVCCPacket:{}", packet.sequence_no());
     }
 
     std::process::ExitCode::SUCCESS
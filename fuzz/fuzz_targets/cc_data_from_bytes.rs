@@ -15,21 +15,35 @@ pub fn debug_init() {
     });
 }
 
+const MAX_PENDING_BYTES: usize = 32;
+const MAX_QUEUED_PACKETS: usize = 2;
+
 fuzz_target!(|data: &[u8]| {
     debug_init();
     let mut parser = CCDataParser::new();
     parser.handle_cea608();
+
+    // feed the data as a stream into a parser with small limits without retrieving packets
+    let mut bounded = CCDataParser::new();
+    bounded.set_max_pending_bytes(Some(MAX_PENDING_BYTES));
+    bounded.set_max_queued_packets(Some(MAX_QUEUED_PACKETS));
+    for chunk in data.chunks(7) {
+        if let Ok(outcome) = bounded.push_stream(chunk) {
+            assert!(outcome.pending <= MAX_PENDING_BYTES);
+            assert!(outcome.packets_ready <= MAX_QUEUED_PACKETS);
+        }
+    }
+    assert!(bounded.drain_packets().count() <= MAX_QUEUED_PACKETS);
+
     if let Ok(_) = parser.push(data) {
         let mut writer = CCDataWriter::default();
         while let Some(packet) = parser.pop_packet() {
             info!("parsed {packet:?}");
             writer.push_packet(packet);
         }
-        if let Some(cea608) = parser.cea608() {
-            info!("parsed cea608 {cea608:?}");
-            for pair in cea608 {
-                writer.push_cea608(*pair);
-            }
+        while let Some(pair) = parser.pop_cea608() {
+            info!("parsed cea608 {pair:?}");
+            writer.push_cea608(pair);
         }
         let mut written = vec![];
         let framerate = Framerate::new(30, 1);
@@ -0,0 +1,234 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! [Arbitrary] implementations for generating random, valid, values with proptest
+
+use proptest::prelude::*;
+
+use crate::tables::{
+    Code, DefineWindowArgs, Ext1, SetPenAttributesArgs, SetPenColorArgs, SetPenLocationArgs,
+    SetWindowAttributesArgs, WindowBits, CODE_MAP_TABLE,
+};
+use crate::{DTVCCPacket, Service};
+
+impl Arbitrary for WindowBits {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        any::<u8>().prop_map(WindowBits::from).boxed()
+    }
+}
+
+impl Arbitrary for DefineWindowArgs {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        (0..8u8, any::<[u8; 6]>())
+            .prop_map(|(window_id, data)| DefineWindowArgs {
+                window_id,
+                ..data.into()
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for SetWindowAttributesArgs {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        any::<[u8; 4]>().prop_map(Self::from).boxed()
+    }
+}
+
+impl Arbitrary for SetPenAttributesArgs {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        any::<[u8; 2]>().prop_map(Self::from).boxed()
+    }
+}
+
+impl Arbitrary for SetPenColorArgs {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        any::<[u8; 3]>().prop_map(Self::from).boxed()
+    }
+}
+
+impl Arbitrary for SetPenLocationArgs {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        any::<[u8; 2]>().prop_map(Self::from).boxed()
+    }
+}
+
+fn in_code_table(bytes: &[u8]) -> bool {
+    CODE_MAP_TABLE
+        .iter()
+        .any(|code_map| code_map.cea708_bytes == bytes)
+}
+
+fn with_args<const N: usize>(first: std::ops::RangeInclusive<u8>) -> BoxedStrategy<Vec<u8>> {
+    (first, any::<[u8; N]>())
+        .prop_map(|(first, args)| {
+            let mut data = vec![first];
+            data.extend(args);
+            data
+        })
+        .boxed()
+}
+
+impl Arbitrary for Ext1 {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        let named = CODE_MAP_TABLE
+            .iter()
+            .filter_map(|code_map| match &code_map.code {
+                Code::Ext1(ext1) => Some(ext1.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        prop_oneof![
+            proptest::sample::select(named),
+            prop_oneof![
+                // C2
+                with_args::<0>(0x00..=0x07),
+                with_args::<1>(0x08..=0x0F),
+                with_args::<2>(0x10..=0x17),
+                with_args::<3>(0x18..=0x1F),
+                // G2 and G3 characters without a mapping
+                prop_oneof![0x20..=0x7Fu8, 0xA0..=0xFFu8]
+                    .prop_filter("named characters", |byte| !in_code_table(&[0x10, *byte]))
+                    .prop_map(|byte| vec![byte]),
+                // C3
+                with_args::<4>(0x80..=0x87),
+                with_args::<5>(0x88..=0x8F),
+                // C3 variable length, limited to fit in a service block
+                (
+                    0x90..=0x9Fu8,
+                    proptest::collection::vec(any::<u8>(), 0..=28)
+                )
+                    .prop_map(|(first, args)| {
+                        let mut data = vec![first, args.len() as u8];
+                        data.extend(args);
+                        data
+                    }),
+            ]
+            .prop_map(Ext1::Unknown),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Code {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        let named = CODE_MAP_TABLE
+            .iter()
+            .map(|code_map| code_map.code.clone())
+            .collect::<Vec<_>>();
+        prop_oneof![
+            proptest::sample::select(named),
+            any::<Ext1>().prop_map(Code::Ext1),
+            any::<u16>().prop_map(Code::P16),
+            any::<WindowBits>().prop_map(Code::ClearWindows),
+            any::<WindowBits>().prop_map(Code::DisplayWindows),
+            any::<WindowBits>().prop_map(Code::HideWindows),
+            any::<WindowBits>().prop_map(Code::ToggleWindows),
+            any::<WindowBits>().prop_map(Code::DeleteWindows),
+            any::<u8>().prop_map(Code::Delay),
+            any::<SetPenAttributesArgs>().prop_map(Code::SetPenAttributes),
+            any::<SetPenColorArgs>().prop_map(Code::SetPenColor),
+            any::<SetPenLocationArgs>().prop_map(Code::SetPenLocation),
+            any::<SetWindowAttributesArgs>().prop_map(Code::SetWindowAttributes),
+            any::<DefineWindowArgs>().prop_map(Code::DefineWindow),
+            // unassigned single byte codes
+            proptest::sample::select(vec![
+                0x01, 0x02, 0x04, 0x05, 0x06, 0x07, 0x09, 0x0A, 0x0B, 0x0F, 0x93, 0x94, 0x95, 0x96,
+            ])
+            .prop_map(|byte| Code::Unknown(vec![byte])),
+            // reserved 2 and 3 byte C0 commands
+            (0x11..=0x17u8, any::<u8>())
+                .prop_map(|(command, arg)| Code::Unknown(vec![command, arg])),
+            (0x19..=0x1Fu8, any::<u8>(), any::<u8>())
+                .prop_map(|(command, arg1, arg2)| Code::Unknown(vec![command, arg1, arg2])),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Service {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        (1..64u8, proptest::collection::vec(any::<Code>(), 1..32))
+            .prop_map(|(number, codes)| {
+                let mut service = Service::new(number);
+                service.push_all(&codes);
+                service
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for DTVCCPacket {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        (0..4u8, proptest::collection::vec(any::<Service>(), 1..6))
+            .prop_map(|(seq_no, services)| {
+                let mut packet = DTVCCPacket::new(seq_no);
+                for service in services {
+                    // services that do not fit are skipped
+                    let _ = packet.push_service(service);
+                }
+                packet
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn code_roundtrip(code in any::<Code>()) {
+            let mut data = vec![];
+            code.write(&mut data).unwrap();
+            prop_assert_eq!(data.len(), code.byte_len());
+            prop_assert_eq!(Code::parse_element(&data), Ok(code));
+        }
+
+        #[test]
+        fn service_roundtrip(service in any::<Service>()) {
+            let data = service.to_bytes();
+            prop_assert_eq!(data.len(), service.len());
+            prop_assert_eq!(Service::parse(&data), Ok(service));
+        }
+
+        #[test]
+        fn packet_roundtrip(packet in any::<DTVCCPacket>()) {
+            let data = packet.to_bytes();
+            prop_assert_eq!(DTVCCPacket::parse(&data), Ok(packet));
+        }
+    }
+}
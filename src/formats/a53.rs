@@ -0,0 +1,112 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! ATSC A/53 `user_data` as carried in MPEG-2 and AVC video streams
+//!
+//! The `cc_data` is wrapped with the `GA94` ATSC identifier and a `user_data_type_code` of
+//! `0x03`, optionally preceded by the MPEG-2 `user_data_start_code`.
+//!
+//! ```text
+//! [00 00 01 B2] 47 41 39 34 03 <cc_data header> <em_data> <cc triples...> FF [...]
+//! ```
+
+use log::trace;
+
+/// The ATSC identifier ("GA94")
+pub const ATSC_IDENTIFIER: [u8; 4] = *b"GA94";
+/// The `user_data_type_code` for `cc_data`
+pub const CC_DATA_TYPE_CODE: u8 = 0x03;
+
+const USER_DATA_START_CODE: [u8; 4] = [0x00, 0x00, 0x01, 0xB2];
+
+/// Extract the `cc_data` from ATSC A/53 `user_data`.
+///
+/// The returned slice includes the two byte `cc_data` header (the second byte being the
+/// `em_data`) and is suitable for [`CCDataParser::push`](crate::CCDataParser::push).  Any
+/// trailing marker bits or additional user data are not included.
+///
+/// Returns `None` if `data` does not have the `GA94` identifier, is not `cc_data` or is
+/// truncated.
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, formats::a53};
+/// let user_data = [0x47, 0x41, 0x39, 0x34, 0x03, 0xC1, 0xFF, 0xFC, 0x80, 0x80, 0xFF];
+/// let cc_data = a53::extract_cc_data(&user_data).unwrap();
+/// assert_eq!(cc_data, [0xC1, 0xFF, 0xFC, 0x80, 0x80]);
+/// let mut parser = CCDataParser::new();
+/// parser.push(cc_data).unwrap();
+/// ```
+pub fn extract_cc_data(data: &[u8]) -> Option<&[u8]> {
+    let data = data.strip_prefix(&USER_DATA_START_CODE).unwrap_or(data);
+    let data = data.strip_prefix(&ATSC_IDENTIFIER)?;
+    let (&type_code, data) = data.split_first()?;
+    if type_code != CC_DATA_TYPE_CODE {
+        trace!("ignoring user_data_type_code 0x{type_code:02x}");
+        return None;
+    }
+    let cc_count = (data.first()? & 0x1F) as usize;
+    let len = 2 + cc_count * 3;
+    if data.len() < len {
+        trace!(
+            "cc_data is truncated, expected {len} bytes, found {}",
+            data.len()
+        );
+        return None;
+    }
+    Some(&data[..len])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+    use crate::*;
+
+    #[test]
+    fn extract_ga94() {
+        test_init_log();
+        let cc_data = [0xC2, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00];
+        let user_data = [
+            &ATSC_IDENTIFIER[..],
+            &[CC_DATA_TYPE_CODE],
+            &cc_data,
+            &[0xFF],
+        ]
+        .concat();
+        assert_eq!(extract_cc_data(&user_data), Some(&cc_data[..]));
+
+        let mut parser = CCDataParser::new();
+        parser.push(extract_cc_data(&user_data).unwrap()).unwrap();
+        let packet = parser.pop_packet().unwrap();
+        assert_eq!(packet.services()[0].codes(), [tables::Code::LatinCapitalA]);
+
+        // with the MPEG-2 user_data_start_code
+        let with_start_code = [&USER_DATA_START_CODE[..], &user_data].concat();
+        assert_eq!(extract_cc_data(&with_start_code), Some(&cc_data[..]));
+
+        // additional trailing user data
+        let trailing = [&user_data[..], &[0x12, 0x34, 0x56]].concat();
+        assert_eq!(extract_cc_data(&trailing), Some(&cc_data[..]));
+    }
+
+    #[test]
+    fn extract_ga94_invalid() {
+        test_init_log();
+        let cc_data = [0xC1, 0xFF, 0xFC, 0x80, 0x80];
+        // different identifier
+        let user_data = [&b"DTG1"[..], &[CC_DATA_TYPE_CODE], &cc_data].concat();
+        assert_eq!(extract_cc_data(&user_data), None);
+        // bar data
+        let user_data = [&ATSC_IDENTIFIER[..], &[0x06], &cc_data].concat();
+        assert_eq!(extract_cc_data(&user_data), None);
+        // truncated
+        let user_data = [&ATSC_IDENTIFIER[..], &[CC_DATA_TYPE_CODE], &cc_data[..4]].concat();
+        assert_eq!(extract_cc_data(&user_data), None);
+        assert_eq!(extract_cc_data(&ATSC_IDENTIFIER), None);
+        assert_eq!(extract_cc_data(&[]), None);
+    }
+}
@@ -0,0 +1,694 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Caption Distribution Packets (CDP) as specified in SMPTE 334-2
+//!
+//! A CDP wraps `cc_data` with a header containing the framerate and sequence counter, an
+//! optional timecode and a footer containing a checksum.
+
+use std::io::Write;
+
+use log::{trace, warn};
+
+use super::SmpteTimecode;
+use crate::{CCDataParser, Framerate, ParserError};
+
+const CDP_IDENTIFIER: u16 = 0x9669;
+const TIME_CODE_SECTION_ID: u8 = 0x71;
+const CC_DATA_SECTION_ID: u8 = 0x72;
+const SVC_INFO_SECTION_ID: u8 = 0x73;
+const FOOTER_SECTION_ID: u8 = 0x74;
+
+const FLAG_TIME_CODE_PRESENT: u8 = 0x80;
+const FLAG_CC_DATA_PRESENT: u8 = 0x40;
+const FLAG_SVC_INFO_PRESENT: u8 = 0x20;
+const FLAG_CAPTION_SERVICE_ACTIVE: u8 = 0x02;
+const FLAG_RESERVED: u8 = 0x01;
+
+static FRAMERATES: [(u8, Framerate); 8] = [
+    (0x1, Framerate::new(24000, 1001)),
+    (0x2, Framerate::new(24, 1)),
+    (0x3, Framerate::new(25, 1)),
+    (0x4, Framerate::new(30000, 1001)),
+    (0x5, Framerate::new(30, 1)),
+    (0x6, Framerate::new(50, 1)),
+    (0x7, Framerate::new(60000, 1001)),
+    (0x8, Framerate::new(60, 1)),
+];
+
+fn framerate_from_code(code: u8) -> Option<Framerate> {
+    FRAMERATES
+        .iter()
+        .find_map(|&(c, framerate)| (c == code).then_some(framerate))
+}
+
+fn framerate_code(framerate: Framerate) -> Option<u8> {
+    FRAMERATES
+        .iter()
+        .find_map(|&(c, f)| (f == framerate).then_some(c))
+}
+
+/// Errors that can occur when parsing a CDP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum CdpError {
+    /// The CDP does not start with the `0x9669` identifier
+    #[error("Invalid CDP identifier 0x{0:04x}")]
+    BadMagic(u16),
+    /// The checksum in the footer does not match the data
+    #[error("CDP checksum mismatch, expected 0x{expected:02x}, found 0x{actual:02x}")]
+    ChecksumMismatch {
+        /// The checksum computed from the data
+        expected: u8,
+        /// The checksum in the footer
+        actual: u8,
+    },
+    /// The data is shorter than required
+    #[error("CDP data is truncated, expected {expected} bytes, found {actual} bytes")]
+    Truncated {
+        /// The expected size
+        expected: usize,
+        /// The actual size
+        actual: usize,
+    },
+    /// The frame rate code is not a valid value
+    #[error("Unknown CDP frame rate code 0x{0:x}")]
+    UnknownFrameRate(u8),
+    /// An unexpected section was encountered
+    #[error("Unexpected section 0x{id:02x} at byte {offset}")]
+    UnexpectedSection {
+        /// The identifier of the section
+        id: u8,
+        /// The position of the section in the CDP
+        offset: usize,
+    },
+    /// A section signalled as present in the header flags was not found
+    #[error("Missing section 0x{0:02x}")]
+    MissingSection(u8),
+    /// The sequence counter in the footer does not match the header
+    #[error(
+        "CDP footer sequence counter {footer} does not match the header sequence counter {header}"
+    )]
+    SequenceCounterMismatch {
+        /// The sequence counter in the header
+        header: u16,
+        /// The sequence counter in the footer
+        footer: u16,
+    },
+    /// The contained `cc_data` could not be parsed
+    #[error("Failed to parse cc_data: {0}")]
+    CcData(ParserError),
+}
+
+/// A parsed CDP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CdpFrame {
+    /// The sequence counter of this CDP
+    pub sequence_counter: u16,
+    /// The framerate this CDP was produced for
+    pub frame_rate: Framerate,
+    /// The timecode of this CDP, if present
+    pub timecode: Option<SmpteTimecode>,
+    /// Whether the caption service is active
+    pub caption_service_active: bool,
+    /// The contained `cc_data` including the two byte header, suitable for
+    /// [`CCDataParser::push`](crate::CCDataParser::push).  Empty if the CDP does not contain any
+    /// `cc_data`.
+    pub cc_data: Vec<u8>,
+}
+
+/// Compute the CDP checksum for `data`.  Adding the checksum to the sum of all the bytes of
+/// `data` results in 0 (modulo 256).
+fn checksum(data: &[u8]) -> u8 {
+    let sum = data.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+    sum.wrapping_neg()
+}
+
+fn check_len(data: &[u8], needed: usize) -> Result<(), CdpError> {
+    if data.len() < needed {
+        return Err(CdpError::Truncated {
+            expected: needed,
+            actual: data.len(),
+        });
+    }
+    Ok(())
+}
+
+fn parse_timecode(data: [u8; 4]) -> SmpteTimecode {
+    SmpteTimecode {
+        hours: ((data[0] & 0x30) >> 4) * 10 + (data[0] & 0x0F),
+        minutes: ((data[1] & 0x70) >> 4) * 10 + (data[1] & 0x0F),
+        seconds: ((data[2] & 0x70) >> 4) * 10 + (data[2] & 0x0F),
+        frames: ((data[3] & 0x30) >> 4) * 10 + (data[3] & 0x0F),
+        drop_frame: (data[3] & 0x80) > 0,
+    }
+}
+
+fn write_timecode(timecode: &SmpteTimecode) -> [u8; 4] {
+    [
+        0xC0 | ((timecode.hours / 10) & 0x3) << 4 | (timecode.hours % 10),
+        0x80 | ((timecode.minutes / 10) & 0x7) << 4 | (timecode.minutes % 10),
+        ((timecode.seconds / 10) & 0x7) << 4 | (timecode.seconds % 10),
+        u8::from(timecode.drop_frame) << 7
+            | ((timecode.frames / 10) & 0x3) << 4
+            | (timecode.frames % 10),
+    ]
+}
+
+/// Writes `cc_data` into CDPs
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, formats::cdp::*};
+/// let mut writer = CdpWriter::new(Framerate::new(30, 1));
+/// let mut written = vec![];
+/// writer.write_frame(&[0xC1, 0xFF, 0xFC, 0x41, 0x42], None, &mut written).unwrap();
+/// let frame = CdpParser::new().parse(&written).unwrap();
+/// assert_eq!(frame.sequence_counter, 0);
+/// assert_eq!(frame.frame_rate, Framerate::new(30, 1));
+/// assert_eq!(frame.cc_data, [0xC1, 0xFF, 0xFC, 0x41, 0x42]);
+/// ```
+#[derive(Debug)]
+pub struct CdpWriter {
+    framerate: Framerate,
+    sequence_counter: u16,
+}
+
+impl CdpWriter {
+    /// Create a new [CdpWriter] producing CDPs for `framerate`
+    pub fn new(framerate: Framerate) -> Self {
+        Self {
+            framerate,
+            sequence_counter: 0,
+        }
+    }
+
+    /// The sequence counter that will be used for the next CDP
+    pub fn sequence_counter(&self) -> u16 {
+        self.sequence_counter
+    }
+
+    /// Write a CDP containing `cc_data`.
+    ///
+    /// `cc_data` must include the two byte header as produced by
+    /// [`CCDataWriter::write`](crate::CCDataWriter::write).  Empty `cc_data` will produce a CDP
+    /// without a `cc_data` section.
+    ///
+    /// An error of kind [std::io::ErrorKind::InvalidInput] is returned if the framerate cannot be
+    /// represented in a CDP or if `cc_data` is malformed.
+    pub fn write_frame<W: Write>(
+        &mut self,
+        cc_data: &[u8],
+        timecode: Option<SmpteTimecode>,
+        w: &mut W,
+    ) -> std::io::Result<()> {
+        let invalid_input =
+            |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidInput, msg.to_string());
+        let frame_rate_code = framerate_code(self.framerate)
+            .ok_or_else(|| invalid_input("Framerate cannot be represented in a CDP"))?;
+
+        let triples = if cc_data.is_empty() {
+            &[]
+        } else {
+            if cc_data.len() < 2 {
+                return Err(invalid_input("cc_data is too short"));
+            }
+            let cc_count = (cc_data[0] & 0x1F) as usize;
+            if cc_data.len() != 2 + cc_count * 3 {
+                return Err(invalid_input("cc_data length does not match cc_count"));
+            }
+            &cc_data[2..]
+        };
+
+        let mut flags = FLAG_RESERVED;
+        if timecode.is_some() {
+            flags |= FLAG_TIME_CODE_PRESENT;
+        }
+        if !triples.is_empty() {
+            flags |= FLAG_CC_DATA_PRESENT | FLAG_CAPTION_SERVICE_ACTIVE;
+        }
+
+        let mut data = Vec::with_capacity(16 + triples.len());
+        data.extend(CDP_IDENTIFIER.to_be_bytes());
+        // length is filled in later
+        data.push(0x00);
+        data.push(frame_rate_code << 4 | 0x0F);
+        data.push(flags);
+        data.extend(self.sequence_counter.to_be_bytes());
+        if let Some(timecode) = timecode {
+            data.push(TIME_CODE_SECTION_ID);
+            data.extend(write_timecode(&timecode));
+        }
+        if !triples.is_empty() {
+            data.push(CC_DATA_SECTION_ID);
+            data.push(0xE0 | (triples.len() / 3) as u8);
+            data.extend_from_slice(triples);
+        }
+        data.push(FOOTER_SECTION_ID);
+        data.extend(self.sequence_counter.to_be_bytes());
+        data[2] = (data.len() + 1) as u8;
+        data.push(checksum(&data));
+        trace!("writing CDP {data:x?}");
+
+        self.sequence_counter = self.sequence_counter.wrapping_add(1);
+        w.write_all(&data)
+    }
+}
+
+/// Parses CDPs
+///
+/// [parse](CdpParser::parse) parses a single CDP.  [push](CdpParser::push) additionally checks
+/// the continuity of the sequence counter across CDPs and feeds the contained `cc_data` into a
+/// [CCDataParser].
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, formats::cdp::*};
+/// let cdp = [
+///     0x96, 0x69, 0x10, 0x5F, 0x43, 0x00, 0x01, 0x72, 0xE1, 0xFC, 0x41, 0x42, 0x74, 0x00, 0x01,
+///     0x07,
+/// ];
+/// let frame = CdpParser::new().parse(&cdp).unwrap();
+/// assert_eq!(frame.sequence_counter, 1);
+/// assert_eq!(frame.frame_rate, Framerate::new(30, 1));
+/// assert_eq!(frame.cc_data, [0xC1, 0xFF, 0xFC, 0x41, 0x42]);
+/// ```
+#[derive(Debug, Default)]
+pub struct CdpParser {
+    cc_data_parser: CCDataParser,
+    last_sequence_counter: Option<u16>,
+    sequence_discontinuities: usize,
+}
+
+impl CdpParser {
+    /// Create a new [CdpParser]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a single complete CDP and push the contained `cc_data` into the inner
+    /// [CCDataParser].
+    ///
+    /// A sequence counter that does not follow on from the previous CDP is counted in
+    /// [sequence_discontinuities](Self::sequence_discontinuities).
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, formats::cdp::*, tables::*};
+    /// let mut writer = CdpWriter::new(Framerate::new(30, 1));
+    /// let mut cdp = vec![];
+    /// writer.write_frame(&[0xC2, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00], None, &mut cdp).unwrap();
+    /// let mut parser = CdpParser::new();
+    /// let frame = parser.push(&cdp).unwrap();
+    /// assert!(frame.caption_service_active);
+    /// let packet = parser.cc_data_parser_mut().pop_packet().unwrap();
+    /// assert_eq!(packet.services()[0].codes(), [Code::LatinCapitalA]);
+    /// ```
+    pub fn push(&mut self, data: &[u8]) -> Result<CdpFrame, CdpError> {
+        let frame = self.parse(data)?;
+        if let Some(last) = self.last_sequence_counter {
+            if frame.sequence_counter != last.wrapping_add(1) {
+                warn!(
+                    "CDP sequence counter discontinuity from {last} to {}",
+                    frame.sequence_counter
+                );
+                self.sequence_discontinuities += 1;
+            }
+        }
+        self.last_sequence_counter = Some(frame.sequence_counter);
+        if !frame.cc_data.is_empty() {
+            self.cc_data_parser
+                .push(&frame.cc_data)
+                .map_err(CdpError::CcData)?;
+        }
+        Ok(frame)
+    }
+
+    /// The number of times the sequence counter of a CDP passed to [push](Self::push) did not
+    /// follow on from the previous CDP
+    pub fn sequence_discontinuities(&self) -> usize {
+        self.sequence_discontinuities
+    }
+
+    /// The [CCDataParser] the `cc_data` of CDPs passed to [push](Self::push) is pushed into
+    pub fn cc_data_parser(&self) -> &CCDataParser {
+        &self.cc_data_parser
+    }
+
+    /// The [CCDataParser] the `cc_data` of CDPs passed to [push](Self::push) is pushed into,
+    /// e.g. for retrieving [DTVCCPacket](crate::DTVCCPacket)s
+    pub fn cc_data_parser_mut(&mut self) -> &mut CCDataParser {
+        &mut self.cc_data_parser
+    }
+
+    /// Parse a single complete CDP
+    pub fn parse(&self, data: &[u8]) -> Result<CdpFrame, CdpError> {
+        check_len(data, 11)?;
+        let identifier = (data[0] as u16) << 8 | data[1] as u16;
+        if identifier != CDP_IDENTIFIER {
+            return Err(CdpError::BadMagic(identifier));
+        }
+        let cdp_length = data[2] as usize;
+        check_len(data, cdp_length)?;
+        let data = &data[..cdp_length];
+        check_len(data, 11)?;
+
+        let frame_rate_code = (data[3] & 0xF0) >> 4;
+        let frame_rate = framerate_from_code(frame_rate_code)
+            .ok_or(CdpError::UnknownFrameRate(frame_rate_code))?;
+        let flags = data[4];
+        let sequence_counter = (data[5] as u16) << 8 | data[6] as u16;
+        trace!("CDP length {cdp_length}, frame rate {frame_rate:?}, flags 0x{flags:02x}, sequence {sequence_counter}");
+
+        let mut timecode = None;
+        let mut cc_data = vec![];
+        let mut have_cc_data = false;
+        let mut offset = 7;
+        loop {
+            check_len(data, offset + 1)?;
+            let id = data[offset];
+            match id {
+                TIME_CODE_SECTION_ID
+                    if flags & FLAG_TIME_CODE_PRESENT > 0 && timecode.is_none() =>
+                {
+                    check_len(data, offset + 5)?;
+                    timecode = Some(parse_timecode(
+                        data[offset + 1..offset + 5].try_into().unwrap(),
+                    ));
+                    offset += 5;
+                }
+                CC_DATA_SECTION_ID if flags & FLAG_CC_DATA_PRESENT > 0 && !have_cc_data => {
+                    check_len(data, offset + 2)?;
+                    have_cc_data = true;
+                    let cc_count = data[offset + 1] & 0x1F;
+                    let len = cc_count as usize * 3;
+                    check_len(data, offset + 2 + len)?;
+                    cc_data.reserve(2 + len);
+                    cc_data.extend([0x80 | 0x40 | cc_count, 0xFF]);
+                    cc_data.extend_from_slice(&data[offset + 2..offset + 2 + len]);
+                    offset += 2 + len;
+                }
+                SVC_INFO_SECTION_ID if flags & FLAG_SVC_INFO_PRESENT > 0 => {
+                    check_len(data, offset + 2)?;
+                    let svc_count = data[offset + 1] & 0x0F;
+                    offset += 2 + svc_count as usize * 7;
+                }
+                FOOTER_SECTION_ID => {
+                    check_len(data, offset + 4)?;
+                    let footer = (data[offset + 1] as u16) << 8 | data[offset + 2] as u16;
+                    if footer != sequence_counter {
+                        return Err(CdpError::SequenceCounterMismatch {
+                            header: sequence_counter,
+                            footer,
+                        });
+                    }
+                    let expected = checksum(&data[..offset + 3]);
+                    let actual = data[offset + 3];
+                    if expected != actual {
+                        return Err(CdpError::ChecksumMismatch { expected, actual });
+                    }
+                    break;
+                }
+                0x75..=0xEF => {
+                    // future sections
+                    check_len(data, offset + 2)?;
+                    offset += 2 + data[offset + 1] as usize;
+                }
+                _ => return Err(CdpError::UnexpectedSection { id, offset }),
+            }
+        }
+
+        if flags & FLAG_TIME_CODE_PRESENT > 0 && timecode.is_none() {
+            return Err(CdpError::MissingSection(TIME_CODE_SECTION_ID));
+        }
+        if flags & FLAG_CC_DATA_PRESENT > 0 && !have_cc_data {
+            return Err(CdpError::MissingSection(CC_DATA_SECTION_ID));
+        }
+
+        Ok(CdpFrame {
+            sequence_counter,
+            frame_rate,
+            timecode,
+            caption_service_active: flags & FLAG_CAPTION_SERVICE_ACTIVE > 0,
+            cc_data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    fn with_checksum(mut data: Vec<u8>) -> Vec<u8> {
+        data[2] = data.len() as u8 + 1;
+        data.push(checksum(&data));
+        data
+    }
+
+    #[test]
+    fn parse_cdp_trailing_data() {
+        test_init_log();
+        let cdp = with_checksum(vec![
+            0x96, 0x69, 0x00, 0x4F, 0x43, 0x12, 0x34, 0x72, 0xE1, 0xFC, 0x94, 0x20, 0x74, 0x12,
+            0x34,
+        ]);
+        let frame = CdpParser::new().parse(&cdp).unwrap();
+
+        // data after the declared cdp_length is ignored
+        let mut trailing = cdp.clone();
+        trailing.extend([0x00, 0xFF, 0x12]);
+        assert_eq!(CdpParser::new().parse(&trailing).unwrap(), frame);
+
+        // the checksum is read from the footer and not the end of the CDP
+        let mut padded = cdp.clone();
+        padded.extend([0x00, 0x00]);
+        padded[2] = padded.len() as u8;
+        padded[cdp.len() - 1] = checksum(&padded[..cdp.len() - 1]);
+        assert_eq!(CdpParser::new().parse(&padded).unwrap(), frame);
+        padded[cdp.len() - 1] ^= 0x01;
+        assert!(matches!(
+            CdpParser::new().parse(&padded),
+            Err(CdpError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_cdp_with_timecode() {
+        test_init_log();
+        let cdp = with_checksum(vec![
+            0x96, 0x69, 0x00, 0x4F, 0xC3, 0x12, 0x34, 0x71, 0xC1, 0xA3, 0x45, 0x96, 0x72, 0xE2,
+            0xFC, 0x94, 0x20, 0xFE, 0x00, 0x00, 0x74, 0x12, 0x34,
+        ]);
+        let frame = CdpParser::new().parse(&cdp).unwrap();
+        assert_eq!(
+            frame,
+            CdpFrame {
+                sequence_counter: 0x1234,
+                frame_rate: Framerate::new(30000, 1001),
+                timecode: Some(SmpteTimecode::new(1, 23, 45, 16, true)),
+                caption_service_active: true,
+                cc_data: vec![0xC2, 0xFF, 0xFC, 0x94, 0x20, 0xFE, 0x00, 0x00],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_cdp_without_cc_data() {
+        test_init_log();
+        let cdp = with_checksum(vec![
+            0x96, 0x69, 0x00, 0x8F, 0x00, 0x00, 0x05, 0x74, 0x00, 0x05,
+        ]);
+        let frame = CdpParser::new().parse(&cdp).unwrap();
+        assert_eq!(frame.frame_rate, Framerate::new(60, 1));
+        assert_eq!(frame.timecode, None);
+        assert!(frame.cc_data.is_empty());
+    }
+
+    #[test]
+    fn cdp_roundtrip() {
+        test_init_log();
+        let framerate = Framerate::new(30000, 1001);
+        let mut cc_data_writer = crate::CCDataWriter::default();
+        let mut service = crate::Service::new(1);
+        for c in "Hello World".chars() {
+            service
+                .push_code(crate::tables::Code::from_char(c).unwrap())
+                .unwrap();
+        }
+        let mut packet = crate::DTVCCPacket::new(0);
+        packet.push_service(service).unwrap();
+        cc_data_writer.push_packet(packet);
+        cc_data_writer.push_cea608(crate::Cea608::Field1(0x94, 0x20));
+
+        let mut writer = CdpWriter::new(framerate);
+        let parser = CdpParser::new();
+        for i in 0..3u8 {
+            let mut cc_data = vec![];
+            cc_data_writer.write(framerate, &mut cc_data).unwrap();
+            let timecode = (i != 1).then(|| SmpteTimecode::new(1, 2, 3, i, true));
+            let mut cdp = vec![];
+            writer.write_frame(&cc_data, timecode, &mut cdp).unwrap();
+            let frame = parser.parse(&cdp).unwrap();
+            assert_eq!(
+                frame,
+                CdpFrame {
+                    sequence_counter: i as u16,
+                    frame_rate: framerate,
+                    timecode,
+                    caption_service_active: cc_data[0] & 0x1F > 0,
+                    cc_data: if cc_data[0] & 0x1F == 0 {
+                        vec![]
+                    } else {
+                        cc_data
+                    },
+                }
+            );
+        }
+        assert_eq!(writer.sequence_counter(), 3);
+    }
+
+    #[test]
+    fn write_cdp_errors() {
+        test_init_log();
+        let mut written = vec![];
+        let mut writer = CdpWriter::new(Framerate::new(15, 1));
+        assert_eq!(
+            writer
+                .write_frame(&[], None, &mut written)
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        let mut writer = CdpWriter::new(Framerate::new(25, 1));
+        assert_eq!(
+            writer
+                .write_frame(&[0xC2, 0xFF, 0xFC, 0x80, 0x80], None, &mut written)
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert!(written.is_empty());
+        assert_eq!(writer.sequence_counter(), 0);
+    }
+
+    #[test]
+    fn parse_cdp_errors() {
+        test_init_log();
+        let cdp = with_checksum(vec![
+            0x96, 0x69, 0x00, 0x5F, 0x43, 0x00, 0x01, 0x72, 0xE1, 0xFC, 0x41, 0x42, 0x74, 0x00,
+            0x01,
+        ]);
+        let parser = CdpParser::new();
+        parser.parse(&cdp).unwrap();
+
+        let mut bad = cdp.clone();
+        bad[0] = 0x97;
+        assert_eq!(parser.parse(&bad), Err(CdpError::BadMagic(0x9769)));
+
+        let mut bad = cdp.clone();
+        let last = bad.len() - 1;
+        bad[last] = bad[last].wrapping_add(1);
+        assert_eq!(
+            parser.parse(&bad),
+            Err(CdpError::ChecksumMismatch {
+                expected: cdp[last],
+                actual: cdp[last].wrapping_add(1)
+            })
+        );
+
+        assert_eq!(
+            parser.parse(&cdp[..cdp.len() - 2]),
+            Err(CdpError::Truncated {
+                expected: cdp.len(),
+                actual: cdp.len() - 2
+            })
+        );
+
+        let mut bad = cdp.clone();
+        bad[3] = 0x0F;
+        assert_eq!(parser.parse(&bad), Err(CdpError::UnknownFrameRate(0)));
+
+        // cc_count larger than the data available
+        let bad = with_checksum(vec![
+            0x96, 0x69, 0x00, 0x5F, 0x43, 0x00, 0x01, 0x72, 0xE3, 0xFC, 0x41, 0x42, 0x74, 0x00,
+            0x01,
+        ]);
+        assert!(matches!(
+            parser.parse(&bad),
+            Err(CdpError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_cdp_missing_sections() {
+        test_init_log();
+        let parser = CdpParser::new();
+        // flags signal cc_data but there is no cc_data section
+        let cdp = with_checksum(vec![
+            0x96, 0x69, 0x00, 0x5F, 0x43, 0x00, 0x01, 0x74, 0x00, 0x01,
+        ]);
+        assert_eq!(
+            parser.parse(&cdp),
+            Err(CdpError::MissingSection(CC_DATA_SECTION_ID))
+        );
+        // flags signal a timecode but there is no timecode section
+        let cdp = with_checksum(vec![
+            0x96, 0x69, 0x00, 0x5F, 0xC3, 0x00, 0x01, 0x72, 0xE1, 0xFC, 0x41, 0x42, 0x74, 0x00,
+            0x01,
+        ]);
+        assert_eq!(
+            parser.parse(&cdp),
+            Err(CdpError::MissingSection(TIME_CODE_SECTION_ID))
+        );
+        // footer sequence counter differs from the header
+        let cdp = with_checksum(vec![
+            0x96, 0x69, 0x00, 0x5F, 0x43, 0x00, 0x01, 0x72, 0xE1, 0xFC, 0x41, 0x42, 0x74, 0x00,
+            0x02,
+        ]);
+        assert_eq!(
+            parser.parse(&cdp),
+            Err(CdpError::SequenceCounterMismatch {
+                header: 1,
+                footer: 2
+            })
+        );
+    }
+
+    #[test]
+    fn push_cdp_sequence() {
+        test_init_log();
+        let framerate = Framerate::new(25, 1);
+        let mut writer = CdpWriter::new(framerate);
+        let mut parser = CdpParser::new();
+        parser.cc_data_parser_mut().handle_cea608();
+        let mut cdps = vec![];
+        for i in 0..4u8 {
+            let mut cdp = vec![];
+            writer
+                .write_frame(&[0xC1, 0xFF, 0xFC, 0x80, 0x80 + i], None, &mut cdp)
+                .unwrap();
+            cdps.push(cdp);
+        }
+        parser.push(&cdps[0]).unwrap();
+        parser.push(&cdps[1]).unwrap();
+        assert_eq!(parser.cc_data_parser().cea608_field1(), [(0x80, 0x81)]);
+        assert_eq!(parser.sequence_discontinuities(), 0);
+        // skip a CDP
+        parser.push(&cdps[3]).unwrap();
+        assert_eq!(parser.sequence_discontinuities(), 1);
+        assert_eq!(parser.cc_data_parser().cea608_field1(), [(0x80, 0x83)]);
+
+        // malformed cc_data is reported
+        let cdp = with_checksum(vec![
+            0x96, 0x69, 0x00, 0x3F, 0x43, 0x00, 0x04, 0x72, 0xE2, 0xFF, 0x02, 0x21, 0xFC, 0x41,
+            0x42, 0x74, 0x00, 0x04,
+        ]);
+        assert!(matches!(
+            parser.push(&cdp),
+            Err(CdpError::CcData(ParserError::Cea608AfterCea708 { .. }))
+        ));
+    }
+}
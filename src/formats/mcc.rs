@@ -0,0 +1,489 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Reading MacCaption (MCC) files
+//!
+//! An MCC file consists of a header containing metadata about the file followed by lines
+//! containing a [SmpteTimecode] and a SMPTE 334 ancillary data packet containing a CDP.  The
+//! ancillary data is hex encoded with common byte sequences replaced by a single letter.
+//!
+//! ```text
+//! File Format=MacCaption_MCC V1.0
+//!
+//! UUID=...
+//! Time Code Rate=30DF
+//!
+//! 00:00:00:00 T10S105F43ZZ72E1FC414274ZZ09
+//! ```
+
+use std::io::{BufRead, BufReader, Read};
+use std::time::Duration;
+
+use log::trace;
+
+use super::cdp::{CdpError, CdpParser};
+use super::SmpteTimecode;
+use crate::Framerate;
+
+/// Errors that can occur when reading an MCC file
+#[derive(Debug, thiserror::Error)]
+pub enum MccError {
+    /// Reading from the underlying reader failed
+    #[error("Failed to read MCC data: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file does not start with a MCC header
+    #[error("Invalid MCC header")]
+    InvalidHeader,
+    /// The header does not contain a valid `Time Code Rate`
+    #[error("Missing or invalid time code rate in the MCC header")]
+    InvalidFrameRate,
+    /// The timecode at the start of a line could not be parsed
+    #[error("Invalid timecode at line {line}")]
+    InvalidTimecode {
+        /// The line number (starting from 1)
+        line: usize,
+    },
+    /// The encoded data could not be decoded
+    #[error("Invalid encoded data at line {line}")]
+    InvalidData {
+        /// The line number (starting from 1)
+        line: usize,
+    },
+    /// The decoded data does not contain a valid CDP
+    #[error("Invalid CDP at line {line}: {source}")]
+    InvalidCdp {
+        /// The line number (starting from 1)
+        line: usize,
+        /// The error produced when parsing the CDP
+        source: CdpError,
+    },
+}
+
+fn framerate_from_time_code_rate(rate: &str) -> Option<(Framerate, bool)> {
+    Some(match rate {
+        "24" => (Framerate::new(24, 1), false),
+        "25" => (Framerate::new(25, 1), false),
+        "30" => (Framerate::new(30, 1), false),
+        "30DF" => (Framerate::new(30000, 1001), true),
+        "50" => (Framerate::new(50, 1), false),
+        "60" => (Framerate::new(60, 1), false),
+        "60DF" => (Framerate::new(60000, 1001), true),
+        _ => return None,
+    })
+}
+
+/// Decode the hex encoding of MCC data expanding any of the byte substitutions
+fn decode_payload(data: &str) -> Option<Vec<u8>> {
+    let mut ret = vec![];
+    let mut chars = data.bytes();
+    while let Some(c) = chars.next() {
+        match c {
+            b'G'..=b'O' => {
+                for _ in 0..(c - b'G' + 1) {
+                    ret.extend([0xFA, 0x00, 0x00]);
+                }
+            }
+            b'P' => ret.extend([0xFB, 0x80, 0x80]),
+            b'Q' => ret.extend([0xFC, 0x80, 0x80]),
+            b'R' => ret.extend([0xFD, 0x80, 0x80]),
+            b'S' => ret.extend([0x96, 0x69]),
+            b'T' => ret.extend([0x61, 0x01]),
+            b'U' => ret.extend([0xE1, 0x00, 0x00, 0x00]),
+            b'Z' => ret.push(0x00),
+            _ => {
+                let high = (c as char).to_digit(16)?;
+                let low = (chars.next()? as char).to_digit(16)?;
+                ret.push((high << 4 | low) as u8);
+            }
+        }
+    }
+    Some(ret)
+}
+
+/// Reads an MCC file line by line from any [BufRead] into `cc_data` with the time it should be
+/// presented
+///
+/// The produced `cc_data` includes the two byte header and is suitable for
+/// [`CCDataParser::push`](crate::CCDataParser::push).  Empty lines and comment lines starting
+/// with `//` are skipped.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use cea708_types::{*, formats::mcc::McceReader};
+/// let mcc = "File Format=MacCaption_MCC V1.0\n\
+///            \n\
+///            Time Code Rate=30\n\
+///            \n\
+///            00:00:01:15\tT10S105F43ZZ72E1FC414274ZZ09\n";
+/// let mut parser = McceReader::new(mcc.as_bytes()).unwrap();
+/// assert_eq!(parser.framerate(), Framerate::new(30, 1));
+/// let (pts, cc_data) = parser.next().unwrap().unwrap();
+/// assert_eq!(pts, Duration::from_millis(1500));
+/// assert_eq!(cc_data, [0xC1, 0xFF, 0xFC, 0x41, 0x42]);
+/// assert!(parser.next().is_none());
+/// ```
+#[derive(Debug)]
+pub struct McceReader<R: BufRead> {
+    reader: R,
+    framerate: Framerate,
+    drop_frame: bool,
+    uuid: Option<String>,
+    line_no: usize,
+    line: String,
+    pending_line: Option<(usize, String)>,
+    cdp_parser: CdpParser,
+    done: bool,
+}
+
+impl<R: BufRead> McceReader<R> {
+    /// Create a new [McceReader] reading from `r`.  The MCC header is read immediately.
+    pub fn new(r: R) -> Result<Self, MccError> {
+        let mut ret = Self {
+            reader: r,
+            framerate: Framerate::new(30000, 1001),
+            drop_frame: false,
+            uuid: None,
+            line_no: 0,
+            line: String::new(),
+            pending_line: None,
+            cdp_parser: CdpParser::new(),
+            done: false,
+        };
+        ret.read_header()?;
+        Ok(ret)
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>, MccError> {
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(None);
+        }
+        self.line_no += 1;
+        Ok(Some(self.line.trim().to_string()))
+    }
+
+    fn read_header(&mut self) -> Result<(), MccError> {
+        match self.read_line()? {
+            Some(line) if line.starts_with("File Format=MacCaption_MCC V") => (),
+            _ => return Err(MccError::InvalidHeader),
+        }
+        let mut framerate = None;
+        while let Some(line) = self.read_line()? {
+            if line.starts_with(|c: char| c.is_ascii_digit()) {
+                self.pending_line = Some((self.line_no, line));
+                break;
+            }
+            if let Some(rate) = line.strip_prefix("Time Code Rate=") {
+                framerate = Some(
+                    framerate_from_time_code_rate(rate.trim()).ok_or(MccError::InvalidFrameRate)?,
+                );
+            } else if let Some(uuid) = line.strip_prefix("UUID=") {
+                self.uuid = Some(uuid.trim().to_string());
+            } else {
+                trace!("skipping header line {}: {line}", self.line_no);
+            }
+        }
+        let (framerate, drop_frame) = framerate.ok_or(MccError::InvalidFrameRate)?;
+        self.framerate = framerate;
+        self.drop_frame = drop_frame;
+        Ok(())
+    }
+
+    /// The [Framerate] declared in the header of the MCC file
+    pub fn framerate(&self) -> Framerate {
+        self.framerate
+    }
+
+    /// The UUID declared in the header of the MCC file
+    pub fn uuid(&self) -> Option<&str> {
+        self.uuid.as_deref()
+    }
+
+    fn parse_line(&self, line: &str, line_no: usize) -> Result<(SmpteTimecode, Vec<u8>), MccError> {
+        let mut words = line.split_whitespace();
+        let timecode = words
+            .next()
+            .and_then(|tc| {
+                // MCC V2.0 may add a field suffix to the timecode
+                let tc = tc.get(..11)?;
+                tc.parse::<SmpteTimecode>().ok()
+            })
+            .ok_or(MccError::InvalidTimecode { line: line_no })?;
+        let timecode = SmpteTimecode {
+            drop_frame: self.drop_frame,
+            ..timecode
+        };
+
+        let data = words
+            .next()
+            .and_then(decode_payload)
+            .ok_or(MccError::InvalidData { line: line_no })?;
+        // SMPTE 334 ancillary data packet: DID, SDID, data count, CDP
+        if data.len() < 3 || data[0] != 0x61 || data[1] != 0x01 || data.len() < 3 + data[2] as usize
+        {
+            return Err(MccError::InvalidData { line: line_no });
+        }
+        let frame = self
+            .cdp_parser
+            .parse(&data[3..3 + data[2] as usize])
+            .map_err(|source| MccError::InvalidCdp {
+                line: line_no,
+                source,
+            })?;
+        Ok((timecode, frame.cc_data))
+    }
+
+    /// Read the next `cc_data` with the [SmpteTimecode] it was stored with in the MCC file
+    ///
+    /// This is the same as [Iterator::next] except that the timecode is not converted into a
+    /// [Duration].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::formats::{mcc::McceReader, SmpteTimecode};
+    /// let mcc = "File Format=MacCaption_MCC V1.0\n\
+    ///            Time Code Rate=30DF\n\
+    ///            \n\
+    ///            00:00:01;15\tT10S105F43ZZ72E1FC414274ZZ09\n";
+    /// let mut parser = McceReader::new(mcc.as_bytes()).unwrap();
+    /// let (timecode, cc_data) = parser.next_timecoded().unwrap().unwrap();
+    /// assert_eq!(timecode, SmpteTimecode::new(0, 0, 1, 15, true));
+    /// assert_eq!(cc_data, [0xC1, 0xFF, 0xFC, 0x41, 0x42]);
+    /// assert!(parser.next_timecoded().is_none());
+    /// ```
+    pub fn next_timecoded(&mut self) -> Option<Result<(SmpteTimecode, Vec<u8>), MccError>> {
+        if let Some((line_no, line)) = self.pending_line.take() {
+            return Some(self.parse_line(&line, line_no));
+        }
+        while !self.done {
+            match self.read_line() {
+                Ok(None) => self.done = true,
+                Ok(Some(line)) if line.is_empty() || line.starts_with("//") => {
+                    trace!("skipping line {}: {line}", self.line_no);
+                    continue;
+                }
+                Ok(Some(line)) => return Some(self.parse_line(&line, self.line_no)),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R: BufRead> Iterator for McceReader<R> {
+    type Item = Result<(Duration, Vec<u8>), MccError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let framerate = self.framerate;
+        self.next_timecoded()
+            .map(|res| res.map(|(timecode, cc_data)| (timecode.to_duration(framerate), cc_data)))
+    }
+}
+
+/// Parses an MCC file from any [Read] into `cc_data` with the time it should be presented
+///
+/// This is a [McceReader] over a [BufReader] of `R`.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use cea708_types::{*, formats::mcc::MccParser};
+/// let mcc = "File Format=MacCaption_MCC V1.0\n\
+///            \n\
+///            Time Code Rate=30\n\
+///            \n\
+///            00:00:01:15\tT10S105F43ZZ72E1FC414274ZZ09\n";
+/// let mut parser = MccParser::new(mcc.as_bytes()).unwrap();
+/// assert_eq!(parser.framerate(), Framerate::new(30, 1));
+/// let (pts, cc_data) = parser.next().unwrap().unwrap();
+/// assert_eq!(pts, Duration::from_millis(1500));
+/// assert_eq!(cc_data, [0xC1, 0xFF, 0xFC, 0x41, 0x42]);
+/// assert!(parser.next().is_none());
+/// ```
+#[derive(Debug)]
+pub struct MccParser<R: Read> {
+    reader: McceReader<BufReader<R>>,
+}
+
+impl<R: Read> MccParser<R> {
+    /// Create a new [MccParser] reading from `r`.  The MCC header is read immediately.
+    pub fn new(r: R) -> Result<Self, MccError> {
+        Ok(Self {
+            reader: McceReader::new(BufReader::new(r))?,
+        })
+    }
+
+    /// The [Framerate] declared in the header of the MCC file
+    pub fn framerate(&self) -> Framerate {
+        self.reader.framerate()
+    }
+
+    /// The UUID declared in the header of the MCC file
+    pub fn uuid(&self) -> Option<&str> {
+        self.reader.uuid()
+    }
+
+    /// Read the next `cc_data` with the [SmpteTimecode] it was stored with in the MCC file
+    ///
+    /// See [McceReader::next_timecoded].
+    pub fn next_timecoded(&mut self) -> Option<Result<(SmpteTimecode, Vec<u8>), MccError>> {
+        self.reader.next_timecoded()
+    }
+
+    /// The [McceReader] used by this [MccParser]
+    pub fn into_inner(self) -> McceReader<BufReader<R>> {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for MccParser<R> {
+    type Item = Result<(Duration, Vec<u8>), MccError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.next()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn decode_substitutions() {
+        test_init_log();
+        assert_eq!(
+            decode_payload("GHPQRSTUZ0a"),
+            Some(
+                [
+                    &[0xFA, 0x00, 0x00][..],
+                    &[0xFA, 0x00, 0x00],
+                    &[0xFA, 0x00, 0x00],
+                    &[0xFB, 0x80, 0x80],
+                    &[0xFC, 0x80, 0x80],
+                    &[0xFD, 0x80, 0x80],
+                    &[0x96, 0x69],
+                    &[0x61, 0x01],
+                    &[0xE1, 0x00, 0x00, 0x00],
+                    &[0x00],
+                    &[0x0A],
+                ]
+                .concat()
+            )
+        );
+        assert_eq!(decode_payload("O").unwrap().len(), 27);
+        assert_eq!(decode_payload("0"), None);
+        assert_eq!(decode_payload("0X"), None);
+    }
+
+    const MCC: &str = "File Format=MacCaption_MCC V1.0\n\
+                       ///////////////////////////////////\n\
+                       // Comment\n\
+                       ///////////////////////////////////\n\
+                       \n\
+                       UUID=CA5D42E1-5B44-4C43-A8E2-7FAC5A2F0A6B\n\
+                       Creation Program=test\n\
+                       Time Code Rate=30DF\n\
+                       \n\
+                       00:00:00:00\tT10S105F43ZZ72E1FC414274ZZ09\n\
+                       \n\
+                       // comment in the body\n\
+                       00:01:00;02\tT10S105F43Z0172E1FC434474Z0103\n";
+
+    #[test]
+    fn parse_mcc() {
+        test_init_log();
+        let mut parser = McceReader::new(MCC.as_bytes()).unwrap();
+        assert_eq!(parser.framerate(), Framerate::new(30000, 1001));
+        assert_eq!(parser.uuid(), Some("CA5D42E1-5B44-4C43-A8E2-7FAC5A2F0A6B"));
+        let (pts, cc_data) = parser.next().unwrap().unwrap();
+        assert_eq!(pts, Duration::ZERO);
+        assert_eq!(cc_data, [0xC1, 0xFF, 0xFC, 0x41, 0x42]);
+        let (pts, cc_data) = parser.next().unwrap().unwrap();
+        // 1800 frames after dropping 2
+        assert_eq!(pts, Duration::from_nanos(60_060_000_000));
+        assert_eq!(cc_data, [0xC1, 0xFF, 0xFC, 0x43, 0x44]);
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn parse_mcc_read() {
+        test_init_log();
+        let mut parser = MccParser::new(std::io::Cursor::new(MCC)).unwrap();
+        assert_eq!(parser.framerate(), Framerate::new(30000, 1001));
+        assert_eq!(parser.uuid(), Some("CA5D42E1-5B44-4C43-A8E2-7FAC5A2F0A6B"));
+        let (timecode, _) = parser.next_timecoded().unwrap().unwrap();
+        assert_eq!(timecode, SmpteTimecode::new(0, 0, 0, 0, true));
+        let (pts, cc_data) = parser.next().unwrap().unwrap();
+        assert_eq!(pts, Duration::from_nanos(60_060_000_000));
+        assert_eq!(cc_data, [0xC1, 0xFF, 0xFC, 0x43, 0x44]);
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn parse_mcc_timecoded() {
+        test_init_log();
+        let mut parser = McceReader::new(MCC.as_bytes()).unwrap();
+        let (timecode, _) = parser.next_timecoded().unwrap().unwrap();
+        assert_eq!(timecode, SmpteTimecode::new(0, 0, 0, 0, true));
+        let (timecode, cc_data) = parser.next_timecoded().unwrap().unwrap();
+        assert_eq!(timecode, SmpteTimecode::new(0, 1, 0, 2, true));
+        let mut cc_parser = crate::CCDataParser::new();
+        cc_parser.push(&cc_data).unwrap();
+        assert!(parser.next_timecoded().is_none());
+    }
+
+    #[test]
+    fn parse_mcc_buf_read() {
+        test_init_log();
+        let reader = std::io::BufReader::with_capacity(16, std::io::Cursor::new(MCC));
+        let mut parser = McceReader::new(reader).unwrap();
+        assert_eq!(parser.framerate(), Framerate::new(30000, 1001));
+        assert_eq!(parser.by_ref().filter(|res| res.is_ok()).count(), 2);
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn parse_mcc_errors() {
+        test_init_log();
+        assert!(matches!(
+            McceReader::new("Scenarist_SCC V1.0\n".as_bytes()),
+            Err(MccError::InvalidHeader)
+        ));
+        assert!(matches!(
+            McceReader::new("File Format=MacCaption_MCC V1.0\n\n00:00:00:00\tZZ\n".as_bytes()),
+            Err(MccError::InvalidFrameRate)
+        ));
+        let mcc = "File Format=MacCaption_MCC V1.0\nTime Code Rate=25\n\n\
+                   00:00:0:00\tZZ\n\
+                   00:00:00:01\tTX\n\
+                   00:00:00:02\tT10S105F43ZZ72E1FC414274ZZ09\n\
+                   00:00:00:03\tT10S105F43ZZ72E1FC414274ZZ0A\n";
+        let mut parser = McceReader::new(mcc.as_bytes()).unwrap();
+        assert!(matches!(
+            parser.next(),
+            Some(Err(MccError::InvalidTimecode { line: 4 }))
+        ));
+        assert!(matches!(
+            parser.next(),
+            Some(Err(MccError::InvalidData { line: 5 }))
+        ));
+        // framerate code 30 in the CDP does not matter
+        assert!(matches!(parser.next(), Some(Ok(_))));
+        assert!(matches!(
+            parser.next(),
+            Some(Err(MccError::InvalidCdp {
+                line: 7,
+                source: CdpError::ChecksumMismatch { .. }
+            }))
+        ));
+        assert!(parser.next().is_none());
+    }
+}
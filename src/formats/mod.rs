@@ -0,0 +1,314 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for reading and writing closed caption data stored in various file and stream formats
+
+use std::time::Duration;
+
+use muldiv::MulDiv;
+
+use crate::Framerate;
+
+pub mod a53;
+pub mod cdp;
+#[cfg(feature = "mcc")]
+pub mod mcc;
+pub mod scc;
+pub mod sei;
+
+/// A SMPTE timecode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SmpteTimecode {
+    /// The hours, 0-23
+    pub hours: u8,
+    /// The minutes, 0-59
+    pub minutes: u8,
+    /// The seconds, 0-59
+    pub seconds: u8,
+    /// The frame within the second, starting from 0
+    pub frames: u8,
+    /// Whether this is a drop frame timecode.  For the NTSC 30000/1001 and 60000/1001
+    /// framerates, drop frame timecodes skip the first 2 (or 4 at 60000/1001) frame numbers of
+    /// every minute except every tenth minute so that the timecode stays in sync with the
+    /// elapsed time.  Drop frame timecodes are written with a `;` before the frames.
+    pub drop_frame: bool,
+}
+
+/// An error when parsing a [SmpteTimecode] from a string
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid SMPTE timecode")]
+pub struct InvalidTimecode;
+
+impl SmpteTimecode {
+    /// Create a new [SmpteTimecode]
+    pub const fn new(hours: u8, minutes: u8, seconds: u8, frames: u8, drop_frame: bool) -> Self {
+        Self {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            drop_frame,
+        }
+    }
+
+    fn check_framerate(framerate: Framerate) {
+        if framerate.numer() == 0 || framerate.denom() == 0 {
+            panic!(
+                "SMPTE timecodes require a non-zero framerate, not {}/{}",
+                framerate.numer(),
+                framerate.denom()
+            );
+        }
+    }
+
+    // the number of frames in a timecode second and the number of frames dropped every minute
+    fn frame_counts(framerate: Framerate) -> (u64, u64) {
+        Self::check_framerate(framerate);
+        let nominal =
+            (framerate.numer() as u64 + framerate.denom() as u64 - 1) / framerate.denom() as u64;
+        let dropped = if framerate.denom() == 1001 && nominal % 30 == 0 {
+            2 * nominal / 30
+        } else {
+            0
+        };
+        (nominal, dropped)
+    }
+
+    /// Create a [SmpteTimecode] from a frame count at a given [Framerate].
+    ///
+    /// Drop frame timecodes are produced for the NTSC 30000/1001 and 60000/1001 framerates.  Hours
+    /// wrap around after 24 hours.
+    ///
+    /// # Panics
+    ///
+    /// * If the numerator or denominator of `framerate` is 0
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, formats::SmpteTimecode};
+    /// let tc = SmpteTimecode::from_frame_count(1800, Framerate::new(30000, 1001));
+    /// assert_eq!(tc, SmpteTimecode::new(0, 1, 0, 2, true));
+    /// ```
+    pub fn from_frame_count(frame_count: u64, framerate: Framerate) -> Self {
+        let (nominal, dropped) = Self::frame_counts(framerate);
+        let mut frame_count = frame_count;
+        if dropped > 0 {
+            let frames_per_10min = nominal * 600 - 9 * dropped;
+            let frames_per_min = nominal * 60 - dropped;
+            let tens = frame_count / frames_per_10min;
+            let rem = frame_count % frames_per_10min;
+            frame_count += 9 * dropped * tens;
+            if rem > dropped {
+                frame_count += dropped * ((rem - dropped) / frames_per_min);
+            }
+        }
+        Self {
+            hours: ((frame_count / (nominal * 3600)) % 24) as u8,
+            minutes: ((frame_count / (nominal * 60)) % 60) as u8,
+            seconds: ((frame_count / nominal) % 60) as u8,
+            frames: (frame_count % nominal) as u8,
+            drop_frame: dropped > 0,
+        }
+    }
+
+    /// The number of frames this [SmpteTimecode] represents at a given [Framerate]
+    ///
+    /// # Panics
+    ///
+    /// * If the numerator or denominator of `framerate` is 0
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, formats::SmpteTimecode};
+    /// let tc = SmpteTimecode::new(0, 0, 2, 5, false);
+    /// assert_eq!(tc.frame_count(Framerate::new(25, 1)), 55);
+    /// ```
+    pub fn frame_count(&self, framerate: Framerate) -> u64 {
+        let (nominal, dropped) = Self::frame_counts(framerate);
+        let total_minutes = 60 * self.hours as u64 + self.minutes as u64;
+        let frames =
+            nominal * 60 * total_minutes + nominal * self.seconds as u64 + self.frames as u64;
+        if self.drop_frame {
+            frames - dropped * (total_minutes - total_minutes / 10)
+        } else {
+            frames
+        }
+    }
+
+    /// Create a [SmpteTimecode] from a [Duration] at a given [Framerate]
+    ///
+    /// # Panics
+    ///
+    /// * If the numerator or denominator of `framerate` is 0
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::time::Duration;
+    /// # use cea708_types::{*, formats::SmpteTimecode};
+    /// let tc = SmpteTimecode::from_duration(Duration::from_millis(61_500), Framerate::new(30, 1));
+    /// assert_eq!(tc, SmpteTimecode::new(0, 1, 1, 15, false));
+    /// ```
+    pub fn from_duration(duration: Duration, framerate: Framerate) -> Self {
+        Self::check_framerate(framerate);
+        let frame_count = (duration.as_nanos() as u64)
+            .mul_div_floor(
+                framerate.numer() as u64,
+                framerate.denom() as u64 * 1_000_000_000,
+            )
+            .unwrap();
+        Self::from_frame_count(frame_count, framerate)
+    }
+
+    /// The [Duration] since the start of the stream this [SmpteTimecode] represents at a given
+    /// [Framerate]
+    ///
+    /// # Panics
+    ///
+    /// * If the numerator or denominator of `framerate` is 0
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::time::Duration;
+    /// # use cea708_types::{*, formats::SmpteTimecode};
+    /// let tc = SmpteTimecode::new(0, 1, 1, 15, false);
+    /// assert_eq!(tc.to_duration(Framerate::new(30, 1)), Duration::from_millis(61_500));
+    /// ```
+    pub fn to_duration(&self, framerate: Framerate) -> Duration {
+        Duration::from_nanos(
+            self.frame_count(framerate)
+                .mul_div_floor(
+                    framerate.denom() as u64 * 1_000_000_000,
+                    framerate.numer() as u64,
+                )
+                .unwrap(),
+        )
+    }
+}
+
+impl std::fmt::Display for SmpteTimecode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let sep = if self.drop_frame { ';' } else { ':' };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{sep}{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
+impl std::str::FromStr for SmpteTimecode {
+    type Err = InvalidTimecode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+        if bytes.len() != 11
+            || bytes[2] != b':'
+            || bytes[5] != b':'
+            || !matches!(bytes[8], b':' | b';' | b'.' | b',')
+        {
+            return Err(InvalidTimecode);
+        }
+        let field = |idx: usize| -> Result<u8, InvalidTimecode> {
+            s[idx..idx + 2].parse::<u8>().map_err(|_| InvalidTimecode)
+        };
+        Ok(Self {
+            hours: field(0)?,
+            minutes: field(3)?,
+            seconds: field(6)?,
+            frames: field(9)?,
+            drop_frame: bytes[8] != b':',
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn timecode_drop_frame_roundtrip() {
+        test_init_log();
+        for framerate in [
+            Framerate::new(30000, 1001),
+            Framerate::new(60000, 1001),
+            Framerate::new(25, 1),
+            Framerate::new(24000, 1001),
+        ] {
+            for frame_count in (0..200_000).step_by(7) {
+                let tc = SmpteTimecode::from_frame_count(frame_count, framerate);
+                assert_eq!(tc.frame_count(framerate), frame_count, "{tc}");
+            }
+        }
+    }
+
+    #[test]
+    fn timecode_drop_frame_skips() {
+        test_init_log();
+        let framerate = Framerate::new(30000, 1001);
+        assert_eq!(
+            SmpteTimecode::from_frame_count(1799, framerate),
+            SmpteTimecode::new(0, 0, 59, 29, true)
+        );
+        assert_eq!(
+            SmpteTimecode::from_frame_count(1800, framerate),
+            SmpteTimecode::new(0, 1, 0, 2, true)
+        );
+        assert_eq!(
+            SmpteTimecode::from_frame_count(17982, framerate),
+            SmpteTimecode::new(0, 10, 0, 0, true)
+        );
+    }
+
+    #[test]
+    fn timecode_hours_wrap() {
+        test_init_log();
+        let framerate = Framerate::new(25, 1);
+        assert_eq!(
+            SmpteTimecode::from_frame_count(25 * 3600 * 23, framerate),
+            SmpteTimecode::new(23, 0, 0, 0, false)
+        );
+        assert_eq!(
+            SmpteTimecode::from_frame_count(25 * 3600 * 25 + 1, framerate),
+            SmpteTimecode::new(1, 0, 0, 1, false)
+        );
+        assert_eq!(
+            SmpteTimecode::from_frame_count(u64::MAX, framerate).hours,
+            ((u64::MAX / (25 * 3600)) % 24) as u8
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn timecode_zero_framerate_numer() {
+        SmpteTimecode::from_frame_count(100, Framerate::new(0, 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn timecode_zero_framerate_denom() {
+        SmpteTimecode::new(0, 0, 1, 0, false).to_duration(Framerate::new(30, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn timecode_zero_framerate_duration() {
+        SmpteTimecode::from_duration(Duration::from_secs(1), Framerate::new(0, 1));
+    }
+
+    #[test]
+    fn timecode_to_from_str() {
+        test_init_log();
+        let tc: SmpteTimecode = "01:02:03;04".parse().unwrap();
+        assert_eq!(tc, SmpteTimecode::new(1, 2, 3, 4, true));
+        assert_eq!(tc.to_string(), "01:02:03;04");
+        let tc: SmpteTimecode = "01:02:03:04".parse().unwrap();
+        assert_eq!(tc, SmpteTimecode::new(1, 2, 3, 4, false));
+        assert_eq!(tc.to_string(), "01:02:03:04");
+        assert!("01:02:03".parse::<SmpteTimecode>().is_err());
+        assert!("01:0a:03:04".parse::<SmpteTimecode>().is_err());
+    }
+}
@@ -0,0 +1,314 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Reading and writing Scenarist Closed Caption (SCC) files
+//!
+//! An SCC file consists of a `Scenarist_SCC V1.0` header line followed by lines containing a
+//! [SmpteTimecode] and a list of CEA-608 byte pairs encoded as hex words.
+//!
+//! ```text
+//! Scenarist_SCC V1.0
+//!
+//! 00:00:00:00 9420 9420 94ae 94ae
+//! ```
+
+use std::io::{BufRead, BufReader, Read, Write};
+
+use log::trace;
+
+use super::SmpteTimecode;
+use crate::Cea608;
+
+/// The header line at the start of every SCC file
+pub const SCC_HEADER: &str = "Scenarist_SCC V1.0";
+
+/// Errors that can occur when reading an SCC file
+#[derive(Debug, thiserror::Error)]
+pub enum SccError {
+    /// Reading from the underlying reader failed
+    #[error("Failed to read SCC data: {0}")]
+    Io(#[from] std::io::Error),
+    /// The timecode at the start of a line could not be parsed
+    #[error("Invalid timecode at line {line}")]
+    InvalidTimecode {
+        /// The line number (starting from 1)
+        line: usize,
+    },
+    /// A CEA-608 byte pair could not be parsed
+    #[error("Invalid hex data at line {line}")]
+    InvalidData {
+        /// The line number (starting from 1)
+        line: usize,
+    },
+}
+
+/// Parses an SCC file into lists of [Cea608] byte pairs with their [SmpteTimecode]
+///
+/// All byte pairs in an SCC file are CEA-608 field 1 data.
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, formats::{SmpteTimecode, scc::SccParser}};
+/// let scc = "Scenarist_SCC V1.0\n\n00:00:01:02\t9420 c1c2\n";
+/// let mut parser = SccParser::new(scc.as_bytes());
+/// let (timecode, pairs) = parser.next().unwrap().unwrap();
+/// assert_eq!(timecode, SmpteTimecode::new(0, 0, 1, 2, false));
+/// assert_eq!(pairs, [Cea608::Field1(0x94, 0x20), Cea608::Field1(0xc1, 0xc2)]);
+/// assert!(parser.next().is_none());
+/// ```
+#[derive(Debug)]
+pub struct SccParser<R: Read> {
+    reader: BufReader<R>,
+    line_no: usize,
+    line: String,
+    done: bool,
+}
+
+impl<R: Read> SccParser<R> {
+    /// Create a new [SccParser] reading from `r`
+    pub fn new(r: R) -> Self {
+        Self {
+            reader: BufReader::new(r),
+            line_no: 0,
+            line: String::new(),
+            done: false,
+        }
+    }
+
+    fn parse_line(line: &str, line_no: usize) -> Result<(SmpteTimecode, Vec<Cea608>), SccError> {
+        let mut words = line.split_whitespace();
+        let timecode = words
+            .next()
+            .and_then(|tc| tc.parse::<SmpteTimecode>().ok())
+            .ok_or(SccError::InvalidTimecode { line: line_no })?;
+        let pairs = words
+            .map(|word| {
+                if word.len() != 4 {
+                    return Err(SccError::InvalidData { line: line_no });
+                }
+                let word = u16::from_str_radix(word, 16)
+                    .map_err(|_| SccError::InvalidData { line: line_no })?;
+                Ok(Cea608::Field1((word >> 8) as u8, (word & 0xFF) as u8))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((timecode, pairs))
+    }
+}
+
+impl<R: Read> Iterator for SccParser<R> {
+    type Item = Result<(SmpteTimecode, Vec<Cea608>), SccError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    self.line_no += 1;
+                    let line = self.line.trim();
+                    if line.is_empty() || (self.line_no == 1 && line.starts_with("Scenarist_SCC")) {
+                        trace!("skipping line {}: {line}", self.line_no);
+                        continue;
+                    }
+                    return Some(Self::parse_line(line, self.line_no));
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e.into()));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Writes lists of [Cea608] byte pairs into an SCC file
+///
+/// The SCC header is written before the first line.
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, formats::{SmpteTimecode, scc::SccWriter}};
+/// let mut written = vec![];
+/// let mut writer = SccWriter::new(&mut written);
+/// writer
+///     .write_line(
+///         SmpteTimecode::new(0, 0, 1, 2, false),
+///         &[Cea608::Field1(0x94, 0x20), Cea608::Field1(0xc1, 0xc2)],
+///     )
+///     .unwrap();
+/// writer.finish().unwrap();
+/// assert_eq!(
+///     std::str::from_utf8(&written).unwrap(),
+///     "Scenarist_SCC V1.0\n\n00:00:01:02\t9420 c1c2\n\n"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct SccWriter<W: Write> {
+    writer: W,
+    wrote_header: bool,
+}
+
+impl<W: Write> SccWriter<W> {
+    /// Create a new [SccWriter] writing to `w`
+    pub fn new(w: W) -> Self {
+        Self {
+            writer: w,
+            wrote_header: false,
+        }
+    }
+
+    fn write_header(&mut self) -> std::io::Result<()> {
+        if !self.wrote_header {
+            self.writer.write_all(SCC_HEADER.as_bytes())?;
+            self.writer.write_all(b"\n\n")?;
+            self.wrote_header = true;
+        }
+        Ok(())
+    }
+
+    /// Write a single line of [Cea608] byte pairs starting at `timecode`
+    ///
+    /// SCC files can only contain CEA-608 field 1 data.  An error of kind
+    /// [std::io::ErrorKind::InvalidInput] is returned for any [Cea608::Field2] byte pairs.
+    pub fn write_line(&mut self, timecode: SmpteTimecode, pairs: &[Cea608]) -> std::io::Result<()> {
+        let mut line = timecode.to_string();
+        for (i, pair) in pairs.iter().enumerate() {
+            let Cea608::Field1(byte0, byte1) = pair else {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "SCC files cannot contain CEA-608 field 2 data",
+                ));
+            };
+            let sep = if i == 0 { '\t' } else { ' ' };
+            line.push_str(&format!("{sep}{byte0:02x}{byte1:02x}"));
+        }
+        line.push_str("\n\n");
+        trace!("writing line {line:?}");
+        self.write_header()?;
+        self.writer.write_all(line.as_bytes())
+    }
+
+    /// Finish writing the SCC file and flush the underlying writer
+    pub fn finish(&mut self) -> std::io::Result<()> {
+        self.write_header()?;
+        self.writer.flush()
+    }
+
+    /// Consume this [SccWriter] and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn parse_scc() {
+        test_init_log();
+        let scc = "Scenarist_SCC V1.0\r\n\r\n\
+                   00:00:00;00\t9420 9420 94ae 94ae\r\n\
+                   \r\n\
+                   00:00:02;15\t942f 942f\r\n";
+        let parsed = SccParser::new(scc.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            parsed,
+            [
+                (
+                    SmpteTimecode::new(0, 0, 0, 0, true),
+                    vec![
+                        Cea608::Field1(0x94, 0x20),
+                        Cea608::Field1(0x94, 0x20),
+                        Cea608::Field1(0x94, 0xae),
+                        Cea608::Field1(0x94, 0xae),
+                    ]
+                ),
+                (
+                    SmpteTimecode::new(0, 0, 2, 15, true),
+                    vec![Cea608::Field1(0x94, 0x2f), Cea608::Field1(0x94, 0x2f)]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn scc_roundtrip() {
+        test_init_log();
+        let scc = "Scenarist_SCC V1.0\n\n\
+                   00:00:00;00\t9420 9420 94ae 94ae 9452 9452 97a1 97a1 c8e5 ecec ef80\n\n\
+                   00:00:02;15\t942c 942c 942f 942f\n\n";
+        let parsed = SccParser::new(scc.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let mut writer = SccWriter::new(vec![]);
+        for (timecode, pairs) in parsed.iter() {
+            writer.write_line(*timecode, pairs).unwrap();
+        }
+        writer.finish().unwrap();
+        let written = writer.into_inner();
+        assert_eq!(std::str::from_utf8(&written).unwrap(), scc);
+        let reparsed = SccParser::new(written.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(parsed, reparsed);
+    }
+
+    #[test]
+    fn write_scc_field2() {
+        test_init_log();
+        let mut writer = SccWriter::new(vec![]);
+        let err = writer
+            .write_line(
+                SmpteTimecode::default(),
+                &[Cea608::Field1(0x94, 0x20), Cea608::Field2(0x94, 0x20)],
+            )
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        writer.finish().unwrap();
+        assert_eq!(writer.into_inner(), b"Scenarist_SCC V1.0\n\n");
+    }
+
+    #[test]
+    fn parse_scc_without_header() {
+        test_init_log();
+        let scc = "00:00:00:00 8080\n";
+        let parsed = SccParser::new(scc.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            parsed,
+            [(
+                SmpteTimecode::new(0, 0, 0, 0, false),
+                vec![Cea608::Field1(0x80, 0x80)]
+            )]
+        );
+    }
+
+    #[test]
+    fn parse_scc_errors() {
+        test_init_log();
+        let mut parser = SccParser::new("Scenarist_SCC V1.0\n\n00:00:0:00\t9420\n".as_bytes());
+        assert!(matches!(
+            parser.next(),
+            Some(Err(SccError::InvalidTimecode { line: 3 }))
+        ));
+        let mut parser = SccParser::new("Scenarist_SCC V1.0\n00:00:00:00\t942\n".as_bytes());
+        assert!(matches!(
+            parser.next(),
+            Some(Err(SccError::InvalidData { line: 2 }))
+        ));
+        let mut parser = SccParser::new("00:00:00:00\t94zz\n".as_bytes());
+        assert!(matches!(
+            parser.next(),
+            Some(Err(SccError::InvalidData { line: 1 }))
+        ));
+    }
+}
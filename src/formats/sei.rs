@@ -0,0 +1,169 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `user_data_registered_itu_t_t35` SEI payloads as used by H.264 and H.265
+//!
+//! ATSC `cc_data` is carried with the United States country code, the ATSC provider code and
+//! the `GA94` [ATSC user_data](super::a53).  Only the SEI payload is handled, not the
+//! surrounding NAL unit.
+//!
+//! ```text
+//! B5 00 31 47 41 39 34 03 <cc_data header> <em_data> <cc triples...> FF
+//! ```
+
+use log::trace;
+
+use super::a53;
+
+/// The ITU-T T.35 country code of the United States
+pub const COUNTRY_CODE_USA: u8 = 0xB5;
+/// The ITU-T T.35 provider code of ATSC
+pub const PROVIDER_CODE_ATSC: u16 = 0x0031;
+
+/// Errors that can occur when parsing an ITU-T T.35 SEI payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum SeiError {
+    /// The country code is not [COUNTRY_CODE_USA]
+    #[error("Unsupported ITU-T T.35 country code 0x{0:02x}")]
+    UnsupportedCountryCode(u8),
+    /// The provider code is not [PROVIDER_CODE_ATSC]
+    #[error("Unsupported ITU-T T.35 provider code 0x{0:04x}")]
+    UnsupportedProviderCode(u16),
+    /// The payload is shorter than required
+    #[error("ITU-T T.35 payload is truncated")]
+    Truncated,
+}
+
+/// Parse the `cc_data` from an ITU-T T.35 SEI payload.
+///
+/// The returned slice includes the two byte `cc_data` header and is suitable for
+/// [`CCDataParser::push`](crate::CCDataParser::push).  `Ok(None)` is returned for ATSC
+/// payloads that do not contain `cc_data`, e.g. AFD or bar data.
+///
+/// # Errors
+///
+/// * [SeiError::UnsupportedCountryCode] if the payload is not for the United States
+/// * [SeiError::UnsupportedProviderCode] if the payload is not from ATSC
+/// * [SeiError::Truncated] if the payload is shorter than the contained `cc_data`
+///
+/// # Examples
+/// ```
+/// # use cea708_types::formats::sei;
+/// let payload = sei::wrap_cc_data(&[0xC1, 0xFF, 0xFC, 0x80, 0x80]);
+/// assert_eq!(
+///     sei::parse_t35_cc_data(&payload),
+///     Ok(Some(&[0xC1, 0xFF, 0xFC, 0x80, 0x80][..]))
+/// );
+/// ```
+pub fn parse_t35_cc_data(payload: &[u8]) -> Result<Option<&[u8]>, SeiError> {
+    let (&country_code, data) = payload.split_first().ok_or(SeiError::Truncated)?;
+    if country_code != COUNTRY_CODE_USA {
+        return Err(SeiError::UnsupportedCountryCode(country_code));
+    }
+    if data.len() < 2 {
+        return Err(SeiError::Truncated);
+    }
+    let provider_code = (data[0] as u16) << 8 | data[1] as u16;
+    if provider_code != PROVIDER_CODE_ATSC {
+        return Err(SeiError::UnsupportedProviderCode(provider_code));
+    }
+    let data = &data[2..];
+    if data.len() < a53::ATSC_IDENTIFIER.len() + 1 {
+        return Err(SeiError::Truncated);
+    }
+    if data[..4] != a53::ATSC_IDENTIFIER || data[4] != a53::CC_DATA_TYPE_CODE {
+        trace!("ignoring ATSC user data {:x?}", &data[..5]);
+        return Ok(None);
+    }
+    a53::extract_cc_data(data)
+        .map(Some)
+        .ok_or(SeiError::Truncated)
+}
+
+/// Wrap `cc_data` (including the two byte header) into an ITU-T T.35 SEI payload.
+///
+/// # Examples
+/// ```
+/// # use cea708_types::formats::sei;
+/// assert_eq!(
+///     sei::wrap_cc_data(&[0xC1, 0xFF, 0xFC, 0x80, 0x80]),
+///     [0xB5, 0x00, 0x31, b'G', b'A', b'9', b'4', 0x03, 0xC1, 0xFF, 0xFC, 0x80, 0x80, 0xFF]
+/// );
+/// ```
+pub fn wrap_cc_data(cc_data: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(9 + cc_data.len());
+    payload.push(COUNTRY_CODE_USA);
+    payload.extend(PROVIDER_CODE_ATSC.to_be_bytes());
+    payload.extend(a53::ATSC_IDENTIFIER);
+    payload.push(a53::CC_DATA_TYPE_CODE);
+    payload.extend_from_slice(cc_data);
+    // marker_bits
+    payload.push(0xFF);
+    payload
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+    use crate::*;
+
+    #[test]
+    fn t35_roundtrip() {
+        test_init_log();
+        let mut writer = CCDataWriter::default();
+        let mut service = Service::new(1);
+        service.push_code(tables::Code::LatinCapitalA).unwrap();
+        let mut packet = DTVCCPacket::new(0);
+        packet.push_service(service).unwrap();
+        writer.push_packet(packet.clone());
+        let mut cc_data = vec![];
+        writer.write(Framerate::new(30, 1), &mut cc_data).unwrap();
+
+        let payload = wrap_cc_data(&cc_data);
+        let parsed = parse_t35_cc_data(&payload).unwrap().unwrap();
+        assert_eq!(parsed, cc_data);
+
+        let mut parser = CCDataParser::new();
+        parser.push(parsed).unwrap();
+        assert_eq!(parser.pop_packet(), Some(packet));
+    }
+
+    #[test]
+    fn t35_invalid() {
+        test_init_log();
+        let payload = wrap_cc_data(&[0xC1, 0xFF, 0xFC, 0x80, 0x80]);
+
+        let mut bad = payload.clone();
+        bad[0] = 0x26;
+        assert_eq!(
+            parse_t35_cc_data(&bad),
+            Err(SeiError::UnsupportedCountryCode(0x26))
+        );
+
+        let mut bad = payload.clone();
+        bad[2] = 0x2F;
+        assert_eq!(
+            parse_t35_cc_data(&bad),
+            Err(SeiError::UnsupportedProviderCode(0x002F))
+        );
+
+        // AFD data
+        let afd = [0xB5, 0x00, 0x31, b'D', b'T', b'G', b'1', 0x41, 0xF8];
+        assert_eq!(parse_t35_cc_data(&afd), Ok(None));
+        // bar data
+        let mut bar = payload.clone();
+        bar[7] = 0x06;
+        assert_eq!(parse_t35_cc_data(&bar), Ok(None));
+
+        assert_eq!(
+            parse_t35_cc_data(&payload[..payload.len() - 3]),
+            Err(SeiError::Truncated)
+        );
+        assert_eq!(parse_t35_cc_data(&payload[..5]), Err(SeiError::Truncated));
+        assert_eq!(parse_t35_cc_data(&[]), Err(SeiError::Truncated));
+    }
+}
@@ -18,7 +18,17 @@ use muldiv::MulDiv;
 
 use log::{debug, trace, warn};
 
+#[cfg(feature = "proptest")]
+mod arbitrary;
+pub mod formats;
+pub mod output;
+pub mod renderer;
+#[cfg(feature = "serde")]
+mod serde_base64;
+#[cfg(feature = "serde")]
+mod serde_repr;
 pub mod tables;
+pub mod timing;
 
 /// Various possible errors when parsing data
 #[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
@@ -37,17 +47,117 @@ pub enum ParserError {
         /// Position of the offending bytes
         byte_pos: usize,
     },
+    /// An extended service block header contains a service number that is not in the range
+    /// 7-63
+    #[error("Invalid service number {number}")]
+    InvalidServiceNumber {
+        /// The service number
+        number: u8,
+    },
+    /// A reserved byte was found in a [DTVCCPacket]
+    #[error("Reserved byte 0x{byte:02x} found at position {position}")]
+    ReservedByte {
+        /// The reserved byte
+        byte: u8,
+        /// The position of the byte in the [DTVCCPacket]
+        position: usize,
+    },
 }
 
 /// An error enum returned when writing data fails
+///
+/// New variants may be added without a major version change.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
 pub enum WriterError {
     /// Writing would overflow by how many bytes
-    #[error("Writing the data would overflow by {0} bytes")]
-    WouldOverflow(usize),
+    #[error("Writing the data would overflow by {overflow_bytes} bytes in {context}")]
+    WouldOverflow {
+        /// The number of bytes that would overflow
+        overflow_bytes: usize,
+        /// Where the overflow would occur
+        context: OverflowContext,
+        /// The number of bytes that would overflow
+        #[deprecated(note = "Use overflow_bytes instead")]
+        by: usize,
+        /// The number of the [Service] that caused the overflow, if any
+        #[deprecated(note = "Use context instead")]
+        service: Option<u8>,
+    },
     /// It is not possible to write to this resource
     #[error("The resource is not writable")]
     ReadOnly,
+    /// A service number is not in the range 0-63
+    #[error("Invalid service number {number}")]
+    InvalidServiceNumber {
+        /// The service number
+        number: u8,
+    },
+    /// The maximum number of queued [DTVCCPacket]s has been reached
+    #[error("The packet queue is full")]
+    QueueFull,
+}
+
+impl WriterError {
+    /// Construct a [WriterError::WouldOverflow] including the values of the deprecated fields
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let err = WriterError::would_overflow(3, OverflowContext::Service { service_no: 1 });
+    /// assert_eq!(err.to_string(), "Writing the data would overflow by 3 bytes in service 1");
+    /// ```
+    pub fn would_overflow(overflow_bytes: usize, context: OverflowContext) -> Self {
+        let service = match context {
+            OverflowContext::Service { service_no } => Some(service_no),
+            OverflowContext::Packet | OverflowContext::Writer => None,
+        };
+        #[allow(deprecated)]
+        Self::WouldOverflow {
+            overflow_bytes,
+            context,
+            by: overflow_bytes,
+            service,
+        }
+    }
+}
+
+/// Where a [WriterError::WouldOverflow] would occur
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverflowContext {
+    /// The [Service] with the provided number
+    Service {
+        /// The number of the [Service]
+        service_no: u8,
+    },
+    /// The [DTVCCPacket]
+    Packet,
+    /// The [CCDataWriter]
+    Writer,
+}
+
+impl std::fmt::Display for OverflowContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Service { service_no } => write!(f, "service {service_no}"),
+            Self::Packet => write!(f, "the packet"),
+            Self::Writer => write!(f, "the writer"),
+        }
+    }
+}
+
+/// An error covering all the errors produced by this crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum Cea708Error {
+    /// An error when parsing data
+    #[error(transparent)]
+    Parser(#[from] ParserError),
+    /// An error when writing data
+    #[error(transparent)]
+    Writer(#[from] WriterError),
+    /// An error when parsing a [tables::Code]
+    #[error(transparent)]
+    Code(#[from] tables::CodeError),
 }
 
 impl From<tables::CodeError> for ParserError {
@@ -56,28 +166,205 @@ impl From<tables::CodeError> for ParserError {
             tables::CodeError::LengthMismatch { expected, actual } => {
                 ParserError::LengthMismatch { expected, actual }
             }
+            tables::CodeError::UnexpectedEnd {
+                offset,
+                expected,
+                actual,
+            } => ParserError::LengthMismatch {
+                expected: offset + expected,
+                actual: offset + actual,
+            },
         }
     }
 }
 
 /// A CEA-608 compatibility byte pair
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cea608 {
     Field1(u8, u8),
     Field2(u8, u8),
 }
 
+/// The type of a `cc_data` triple
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CcType {
+    /// CEA-608 compatibility bytes for field 1
+    Cea608Field1,
+    /// CEA-608 compatibility bytes for field 2
+    Cea608Field2,
+    /// The continuation of a [DTVCCPacket]
+    DtvccData,
+    /// The start of a [DTVCCPacket]
+    DtvccStart,
+}
+
+impl CcType {
+    fn from_byte(byte: u8) -> Self {
+        match byte & 0x3 {
+            0b00 => CcType::Cea608Field1,
+            0b01 => CcType::Cea608Field2,
+            0b10 => CcType::DtvccData,
+            _ => CcType::DtvccStart,
+        }
+    }
+}
+
+/// A single raw triple of `cc_data`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CcTriple {
+    /// Whether the data in this triple is valid
+    pub cc_valid: bool,
+    /// The type of this triple
+    pub cc_type: CcType,
+    /// The first data byte
+    pub cc_data_1: u8,
+    /// The second data byte
+    pub cc_data_2: u8,
+}
+
+/// Iterate over the raw triples in a complete `cc_data` packet without reassembling any
+/// [DTVCCPacket]s.
+///
+/// Will fail with [ParserError::LengthMismatch] if the length of the data does not match the
+/// number of cc triples specified in the `cc_data` header.  If the `process_cc_data_flag` is not
+/// set, no triples are produced.
+///
+/// # Examples
+/// ```
+/// # use cea708_types::*;
+/// let mut triples = iter_cc_triples(&[0x41, 0xFF, 0xFC, 0x80, 0x81]).unwrap();
+/// assert_eq!(
+///     triples.next(),
+///     Some(CcTriple {
+///         cc_valid: true,
+///         cc_type: CcType::Cea608Field1,
+///         cc_data_1: 0x80,
+///         cc_data_2: 0x81,
+///     })
+/// );
+/// assert_eq!(triples.next(), None);
+/// ```
+pub fn iter_cc_triples(data: &[u8]) -> Result<impl Iterator<Item = CcTriple> + '_, ParserError> {
+    if data.len() < 2 {
+        return Err(ParserError::LengthMismatch {
+            expected: 2,
+            actual: data.len(),
+        });
+    }
+    let process_cc_data_flag = data[0] & 0x40 > 0;
+    let cc_count = (data[0] & 0x1F) as usize;
+    if cc_count * 3 + 2 != data.len() {
+        return Err(ParserError::LengthMismatch {
+            expected: cc_count * 3 + 2,
+            actual: data.len(),
+        });
+    }
+    let triples = if process_cc_data_flag {
+        &data[2..]
+    } else {
+        &[]
+    };
+    Ok(triples.chunks_exact(3).map(|triple| CcTriple {
+        cc_valid: (triple[0] & 0x04) == 0x04,
+        cc_type: CcType::from_byte(triple[0]),
+        cc_data_1: triple[1],
+        cc_data_2: triple[2],
+    }))
+}
+
+/// The result of pushing `cc_data` into a [CCDataParser]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PushOutcome {
+    /// The number of bytes of the pushed data that were interpreted.  0 if the data was ignored,
+    /// e.g. because the `process_cc_data_flag` was not set.
+    pub consumed: usize,
+    /// The number of bytes of an incomplete [DTVCCPacket] that are waiting for more data
+    pub pending: usize,
+    /// The number of complete [DTVCCPacket]s that can be retrieved with
+    /// [CCDataParser::pop_packet]
+    pub packets_ready: usize,
+}
+
+/// How a [CCDataParser] handles valid CEA-608 triples that follow CEA-708 triples in the same
+/// `cc_data`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Cea608AfterCea708Policy {
+    /// Return [ParserError::Cea608AfterCea708] and ignore the rest of the `cc_data`
+    #[default]
+    Error,
+    /// Skip the CEA-608 triples and continue parsing
+    Ignore,
+    /// Provide the CEA-608 triples as [Cea608] data and continue parsing
+    Accept,
+}
+
+// The limits on the data buffered by a CCDataParser
+#[derive(Debug, Clone, Copy)]
+struct ParserLimits {
+    max_pending_bytes: Option<usize>,
+    max_queued_packets: Option<usize>,
+    max_queued_cea608: Option<usize>,
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        Self {
+            max_pending_bytes: Some(CCDataParser::DEFAULT_MAX_PENDING_BYTES),
+            max_queued_packets: Some(CCDataParser::DEFAULT_MAX_QUEUED_PACKETS),
+            max_queued_cea608: Some(CCDataParser::DEFAULT_MAX_QUEUED_CEA608),
+        }
+    }
+}
+
 /// Parses a byte stream of `cc_data` bytes into indivdual [`DTVCCPacket`]s.
 #[derive(Debug, Default)]
 pub struct CCDataParser {
+    stream_data: Vec<u8>,
+    frame_triples: Vec<u8>,
+    frame_in_dtvcc: bool,
+    frame_failed: bool,
+    strict: bool,
+    lenient_cc_count: bool,
+    cc_count_mismatches: usize,
+    cea608_after_cea708_policy: Cea608AfterCea708Policy,
+    cea608_after_cea708: usize,
+    dedupe_repeated: bool,
+    last_cc_data: Option<Vec<u8>>,
+    // process_cc_data_flag and cc_count of the last cc_data header
+    last_header: Option<(bool, u8)>,
+    unprocessed_frames: usize,
+    empty_frames: usize,
+    repeated_frames: usize,
     pending_data: Vec<u8>,
     packets: VecDeque<DTVCCPacket>,
     cea608: Option<Vec<Cea608>>,
+    cea608_queue: VecDeque<Cea608>,
+    accumulate_cea608: bool,
+    cea608_field1: Vec<(u8, u8)>,
+    cea608_field2: Vec<(u8, u8)>,
     have_initial_ccp_header: bool,
     ccp_bytes_needed: usize,
+    limits: ParserLimits,
+    dropped_pending_bytes: usize,
+    dropped_packets: usize,
+    dropped_cea608: usize,
+    last_sequence_no: Option<u8>,
+    sequence_discontinuities: usize,
 }
 
 impl CCDataParser {
+    /// The default for [set_max_pending_bytes](Self::set_max_pending_bytes).  The size of the
+    /// largest possible [DTVCCPacket].
+    pub const DEFAULT_MAX_PENDING_BYTES: usize = 128;
+
+    /// The default for [set_max_queued_packets](Self::set_max_queued_packets)
+    pub const DEFAULT_MAX_QUEUED_PACKETS: usize = 64;
+
+    /// The default for [set_max_queued_cea608](Self::set_max_queued_cea608).  About one second of
+    /// CEA-608 byte pairs for both fields.
+    pub const DEFAULT_MAX_QUEUED_CEA608: usize = 120;
+
     /// Create a new [CCDataParser]
     pub fn new() -> Self {
         Self::default()
@@ -90,38 +377,273 @@ impl CCDataParser {
     /// Push a complete `cc_data` packet into the parser for processing.
     ///
     /// Will fail with [ParserError::LengthMismatch] if the length of the data does not match the
-    /// number of cc triples specified in the `cc_data` header.
+    /// number of cc triples specified in the `cc_data` header unless
+    /// [set_lenient_cc_count](Self::set_lenient_cc_count) is enabled.
     ///
     /// Any CEA-608 data provided after valid CEA-708 data will return
-    /// [ParserError::Cea608AfterCea708].
-    pub fn push(&mut self, data: &[u8]) -> Result<(), ParserError> {
-        trace!("parsing {data:?}");
-        if let Some(ref mut cea608) = self.cea608 {
-            cea608.clear();
+    /// [ParserError::Cea608AfterCea708] unless a different
+    /// [set_cea608_after_cea708_policy](Self::set_cea608_after_cea708_policy) is set.
+    ///
+    /// On success, a [PushOutcome] describes how the data was handled.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// // the start of a DTVCCPacket that requires 4 bytes
+    /// let outcome = parser.push(&[0x41, 0xFF, 0xFF, 0x02, 0x21]).unwrap();
+    /// assert_eq!(outcome, PushOutcome { consumed: 5, pending: 2, packets_ready: 0 });
+    /// // the rest of the DTVCCPacket
+    /// let outcome = parser.push(&[0x41, 0xFF, 0xFE, 0x41, 0x00]).unwrap();
+    /// assert_eq!(outcome, PushOutcome { consumed: 5, pending: 0, packets_ready: 1 });
+    /// ```
+    pub fn push(&mut self, data: &[u8]) -> Result<PushOutcome, ParserError> {
+        let consumed = self.push_internal(data)?;
+        let outcome = PushOutcome {
+            consumed,
+            pending: self.pending_data.len(),
+            packets_ready: self.packets.len(),
+        };
+        trace!("{outcome:?}");
+        Ok(outcome)
+    }
+
+    /// Push an arbitrary chunk of a byte stream of complete `cc_data` packets into the parser for
+    /// processing.
+    ///
+    /// Unlike [push](Self::push), `data` does not need to start or end on a `cc_data` boundary.
+    /// Bytes of an incomplete `cc_data` are kept until the next call and each complete `cc_data`
+    /// is processed as if passed to [push](Self::push).  Only the [`Cea608`] bytes of the last
+    /// complete `cc_data` are available unless [set_accumulate_cea608](Self::set_accumulate_cea608)
+    /// is enabled.  Any incomplete `cc_data` at the end of the stream is discarded by
+    /// [flush](Self::flush).
+    ///
+    /// On success, [PushOutcome::consumed] is the number of bytes of complete `cc_data` that were
+    /// processed.
+    ///
+    /// # Errors
+    ///
+    /// Any error from [push](Self::push) for a complete `cc_data`.  The offending `cc_data` is
+    /// discarded.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// let outcome = parser.push_stream(&[0x42, 0xFF, 0xFF, 0x02, 0x21]).unwrap();
+    /// assert_eq!(outcome, PushOutcome { consumed: 0, pending: 0, packets_ready: 0 });
+    /// let outcome = parser.push_stream(&[0xFE, 0x41, 0x00]).unwrap();
+    /// assert_eq!(outcome, PushOutcome { consumed: 8, pending: 0, packets_ready: 1 });
+    /// ```
+    pub fn push_stream(&mut self, data: &[u8]) -> Result<PushOutcome, ParserError> {
+        self.stream_data.extend_from_slice(data);
+        let mut consumed = 0;
+        while let Some(&byte) = self.stream_data.first() {
+            let cc_count = (byte & 0x1F) as usize;
+            let len = 2 + cc_count * 3;
+            if self.stream_data.len() < len {
+                break;
+            }
+            let cc_data = self.stream_data.drain(..len).collect::<Vec<_>>();
+            trace!("stream has complete cc_data of {len} bytes");
+            self.push_internal(&cc_data)?;
+            consumed += len;
         }
+        let outcome = PushOutcome {
+            consumed,
+            pending: self.pending_data.len(),
+            packets_ready: self.packets.len(),
+        };
+        trace!("{outcome:?}");
+        Ok(outcome)
+    }
 
-        if data.len() < 5 {
-            // enough for 2 byte header plus 1 byte triple
+    /// Push `cc_data` triples without the two byte `cc_data` header into the parser for
+    /// processing, e.g. as carried in a CDP.
+    ///
+    /// Behaves like [push](Self::push) with a `cc_data` header that has the
+    /// `process_cc_data_flag` set and a `cc_count` of the number of triples in `data`.
+    ///
+    /// # Errors
+    ///
+    /// * [ParserError::LengthMismatch] if `data` is not a whole number of triples
+    /// * [ParserError::Cea608AfterCea708] if CEA-608 data is provided after valid CEA-708 data
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// let outcome = parser.push_triples(&[0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00]).unwrap();
+    /// assert_eq!(outcome, PushOutcome { consumed: 6, pending: 0, packets_ready: 1 });
+    /// ```
+    pub fn push_triples(&mut self, data: &[u8]) -> Result<PushOutcome, ParserError> {
+        trace!("parsing triples {data:?}");
+        if data.len() % 3 != 0 {
+            return Err(ParserError::LengthMismatch {
+                expected: (data.len() / 3 + 1) * 3,
+                actual: data.len(),
+            });
+        }
+        self.start_frame();
+        self.process_triples(data)?;
+        let outcome = PushOutcome {
+            consumed: data.len(),
+            pending: self.pending_data.len(),
+            packets_ready: self.packets.len(),
+        };
+        trace!("{outcome:?}");
+        Ok(outcome)
+    }
+
+    /// Push a single `cc_data` triple into the parser.  The triples of a frame are processed as
+    /// if passed to [push](Self::push) once [end_of_frame](Self::end_of_frame) is called.
+    ///
+    /// Only the lower two bits of `cc_type` are used.
+    ///
+    /// # Errors
+    ///
+    /// * [ParserError::Cea608AfterCea708] if a valid CEA-608 triple follows CEA-708 data in the
+    ///   current frame.  The rest of the frame is ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// parser.push_triple(true, 0b11, [0x02, 0x21]).unwrap();
+    /// parser.push_triple(true, 0b10, [0x41, 0x00]).unwrap();
+    /// let outcome = parser.end_of_frame().unwrap();
+    /// assert_eq!(outcome, PushOutcome { consumed: 6, pending: 0, packets_ready: 1 });
+    /// ```
+    pub fn push_triple(
+        &mut self,
+        cc_valid: bool,
+        cc_type: u8,
+        data: [u8; 2],
+    ) -> Result<(), ParserError> {
+        if self.frame_failed {
+            trace!("ignoring triple in failed frame");
             return Ok(());
         }
+        let cc_type = cc_type & 0x3;
+        if (cc_type & 0b10) > 0 {
+            self.frame_in_dtvcc = true;
+        }
+        self.frame_triples
+            .extend([0xF8 | ((cc_valid as u8) << 2) | cc_type, data[0], data[1]]);
+        if self.frame_in_dtvcc
+            && cc_valid
+            && (cc_type & 0b10) == 0
+            && self.cea608_after_cea708_policy == Cea608AfterCea708Policy::Error
+        {
+            // process now to produce the same error (and side effects) as push()
+            self.frame_failed = true;
+            let triples = std::mem::take(&mut self.frame_triples);
+            self.start_frame();
+            self.process_triples(&triples)?;
+        }
+        Ok(())
+    }
+
+    /// Mark the end of the frame of triples provided by [push_triple](Self::push_triple) and
+    /// process them.
+    ///
+    /// # Errors
+    ///
+    /// Any error from [push](Self::push) for the equivalent `cc_data`
+    pub fn end_of_frame(&mut self) -> Result<PushOutcome, ParserError> {
+        let triples = std::mem::take(&mut self.frame_triples);
+        self.frame_in_dtvcc = false;
+        let mut consumed = 0;
+        if !std::mem::take(&mut self.frame_failed) {
+            self.start_frame();
+            self.process_triples(&triples)?;
+            consumed = triples.len();
+        }
+        let outcome = PushOutcome {
+            consumed,
+            pending: self.pending_data.len(),
+            packets_ready: self.packets.len(),
+        };
+        trace!("{outcome:?}");
+        Ok(outcome)
+    }
+
+    fn push_internal(&mut self, data: &[u8]) -> Result<usize, ParserError> {
+        trace!("parsing {data:?}");
+        self.start_frame();
+        if data.len() >= 2 {
+            self.last_header = Some((data[0] & 0x40 > 0, data[0] & 0x1F));
+        }
+
+        if self.dedupe_repeated {
+            if self.last_cc_data.as_deref() == Some(data) {
+                debug!("skipping repeated cc_data");
+                self.repeated_frames += 1;
+                return Ok(0);
+            }
+            let last = self.last_cc_data.get_or_insert_with(Vec::new);
+            last.clear();
+            last.extend_from_slice(data);
+        }
+
+        if data.len() < 2 {
+            return Ok(0);
+        }
         let process_cc_data_flag = data[0] & 0x40 > 0;
         if !process_cc_data_flag {
-            return Ok(());
+            trace!("process_cc_data_flag not set, skipping cc_data");
+            self.unprocessed_frames += 1;
+            return Ok(0);
         }
 
         let cc_count = data[0] & 0x1F;
         if cc_count == 0 {
-            return Ok(());
+            trace!("cc_data without any triples");
+            self.empty_frames += 1;
+            return Ok(0);
+        }
+        if data.len() < 5 {
+            // enough for 2 byte header plus 1 byte triple
+            return Ok(0);
         }
         trace!("cc_count: {cc_count}, len = {}", data.len());
-        if (cc_count * 3 + 2) as usize != data.len() {
-            return Err(ParserError::LengthMismatch {
-                expected: (cc_count * 3 + 1) as usize,
-                actual: data.len(),
-            });
+        let expected = (cc_count * 3 + 2) as usize;
+        let mut len = data.len();
+        if expected != data.len() {
+            if !self.lenient_cc_count {
+                return Err(ParserError::LengthMismatch {
+                    expected: (cc_count * 3 + 1) as usize,
+                    actual: data.len(),
+                });
+            }
+            warn!(
+                "cc_count {cc_count} requires {expected} bytes, have {} bytes",
+                data.len()
+            );
+            self.cc_count_mismatches += 1;
+            // only complete triples are used
+            len = expected.min(2 + (data.len() - 2) / 3 * 3);
+        }
+
+        self.process_triples(&data[2..len])?;
+
+        Ok(len)
+    }
+
+    fn start_frame(&mut self) {
+        self.last_header = None;
+        if !self.accumulate_cea608 {
+            if let Some(ref mut cea608) = self.cea608 {
+                cea608.clear();
+            }
         }
+        self.cea608_field1.clear();
+        self.cea608_field2.clear();
+    }
 
+    fn process_triples(&mut self, data: &[u8]) -> Result<(), ParserError> {
         let mut ccp_data = vec![];
+        let mut error = None;
         let mut in_dtvcc = false;
 
         // re-add first byte to pending_data
@@ -142,7 +664,7 @@ impl CCDataParser {
         let ccp_offset;
         {
             let mut ret = None;
-            for (i, triple) in data[2..].chunks_exact(3).enumerate() {
+            for (i, triple) in data.chunks_exact(3).enumerate() {
                 let cc_valid = (triple[0] & 0x04) == 0x04;
                 let cc_type = triple[0] & 0x3;
                 trace!(
@@ -158,7 +680,19 @@ impl CCDataParser {
                 if !cc_valid {
                     continue;
                 }
-                if !in_dtvcc && (cc_type == 0b00 || cc_type == 0b01) {
+                if in_dtvcc && (cc_type == 0b00 || cc_type == 0b01) {
+                    // invalid packet construction;
+                    warn!("cea608 bytes after cea708 data at byte:{}", i * 3);
+                    self.cea608_after_cea708 += 1;
+                    match self.cea608_after_cea708_policy {
+                        Cea608AfterCea708Policy::Error => {
+                            return Err(ParserError::Cea608AfterCea708 { byte_pos: i * 3 })
+                        }
+                        Cea608AfterCea708Policy::Ignore => continue,
+                        Cea608AfterCea708Policy::Accept => (),
+                    }
+                }
+                if cc_type == 0b00 || cc_type == 0b01 {
                     trace!(
                         "have cea608 bytes type {cc_type} 0x{:02x} 0x{:02x}",
                         triple[1],
@@ -166,28 +700,35 @@ impl CCDataParser {
                     );
                     if let Some(ref mut cea608) = self.cea608 {
                         let pair = match cc_type {
-                            0b00 => Cea608::Field1(triple[1], triple[2]),
-                            0b01 => Cea608::Field2(triple[1], triple[2]),
+                            0b00 => {
+                                self.cea608_field1.push((triple[1], triple[2]));
+                                Cea608::Field1(triple[1], triple[2])
+                            }
+                            0b01 => {
+                                self.cea608_field2.push((triple[1], triple[2]));
+                                Cea608::Field2(triple[1], triple[2])
+                            }
                             _ => unreachable!(),
                         };
                         cea608.push(pair);
+                        self.cea608_queue.push_front(pair);
+                        let dropped =
+                            trim_queue(&mut self.cea608_queue, self.limits.max_queued_cea608);
+                        if dropped > 0 {
+                            debug!("too many queued CEA-608 byte pairs, dropped {dropped}");
+                            self.dropped_cea608 += dropped;
+                        }
                     }
                     continue;
                 }
 
-                if in_dtvcc && (cc_type == 0b00 || cc_type == 0b01) {
-                    // invalid packet construction;
-                    warn!("cea608 bytes after cea708 data at byte:{}", i * 3);
-                    return Err(ParserError::Cea608AfterCea708 { byte_pos: i * 3 });
-                }
-
                 if ret.is_none() {
                     ret = Some(i * 3);
                 }
             }
 
             if let Some(ret) = ret {
-                ccp_offset = 2 + ret
+                ccp_offset = ret
             } else {
                 // no data to process
                 return Ok(());
@@ -197,7 +738,6 @@ impl CCDataParser {
 
         let mut data_iter = pending_data.iter().chain(data[ccp_offset..].iter());
         let mut i = 0;
-        in_dtvcc = false;
         loop {
             let byte0 = data_iter.next();
             let byte1 = data_iter.next();
@@ -208,30 +748,21 @@ impl CCDataParser {
             };
             let cc_valid = (byte0 & 0x04) == 0x04;
             let cc_type = byte0 & 0x3;
-            if (cc_type & 0b10) > 0 {
-                in_dtvcc = true;
-            }
             if !cc_valid {
                 continue;
             }
-            if !in_dtvcc && (cc_type == 0b00 || cc_type == 0b01) {
-                // 608-in-708 data should not be hit as we skip over it
-                unreachable!();
+            if cc_type == 0b00 || cc_type == 0b01 {
+                // cea608 data has already been handled
+                continue;
             }
 
             if (cc_type & 0b11) == 0b11 {
                 trace!("found ccp header at index {}", i - 3);
                 self.have_initial_ccp_header = true;
                 // a header byte truncates the size of any previous packet
-                match DTVCCPacket::parse(&ccp_data) {
-                    Ok(packet) => self.packets.push_front(packet),
-                    Err(ParserError::LengthMismatch { .. }) => (),
-                    Err(e) => {
-                        eprintln!("{e:?}");
-                        unreachable!()
-                    }
+                if let Err(e) = self.packet_complete(&ccp_data) {
+                    error.get_or_insert(e);
                 }
-                in_dtvcc = false;
                 ccp_data = vec![];
                 let (_seq_no, packet_len) = DTVCCPacket::parse_hdr_byte(*byte1);
                 trace!("waiting for {} dtvcc bytes", packet_len + 1);
@@ -252,1000 +783,3698 @@ impl CCDataParser {
         }
 
         if self.ccp_bytes_needed == 0 {
-            match DTVCCPacket::parse(&ccp_data) {
-                Ok(packet) => self.packets.push_front(packet),
-                Err(ParserError::LengthMismatch { .. }) => (),
-                _ => unreachable!(),
+            if let Err(e) = self.packet_complete(&ccp_data) {
+                error.get_or_insert(e);
             }
             ccp_data = vec![];
         }
 
+        if let Some(max) = self.limits.max_pending_bytes {
+            if ccp_data.len() > max {
+                warn!(
+                    "dropping {} pending bytes larger than the maximum of {max}",
+                    ccp_data.len()
+                );
+                self.dropped_pending_bytes += ccp_data.len();
+                ccp_data = vec![];
+                self.have_initial_ccp_header = false;
+                self.ccp_bytes_needed = 0;
+            }
+        }
         self.pending_data = ccp_data;
 
+        match error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn packet_complete(&mut self, ccp_data: &[u8]) -> Result<(), ParserError> {
+        let packet = match DTVCCPacket::parse(ccp_data) {
+            Ok(packet) => packet,
+            Err(ParserError::LengthMismatch { .. }) => return Ok(()),
+            Err(e) => {
+                warn!("dropping invalid packet: {e}");
+                return if self.strict { Err(e) } else { Ok(()) };
+            }
+        };
+        if self.strict {
+            if let Some((byte, position)) = packet.find_reserved_byte() {
+                warn!("dropping packet with reserved byte 0x{byte:02x} at {position}");
+                return Err(ParserError::ReservedByte { byte, position });
+            }
+        }
+        self.queue_packet(packet);
         Ok(())
     }
 
-    /// Clear any internal buffers
-    pub fn flush(&mut self) {
-        *self = Self::default();
+    /// Return errors for [DTVCCPacket]s that contain reserved bytes or invalid service numbers
+    /// instead of producing [tables::Code::Unknown] or dropping the [DTVCCPacket].  The
+    /// offending [DTVCCPacket] is dropped and the error is returned after the rest of the
+    /// `cc_data` has been processed.  The default is `false`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// parser.set_strict(true);
+    /// // a DTVCCPacket containing the reserved byte 0x93 in service 1
+    /// assert_eq!(
+    ///     parser.push(&[0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x93, 0x00]),
+    ///     Err(ParserError::ReservedByte { byte: 0x93, position: 2 })
+    /// );
+    /// assert!(parser.pop_packet().is_none());
+    /// ```
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
     }
 
-    /// Pop a valid [DTVCCPacket] or None if no packet could be parsed
-    pub fn pop_packet(&mut self) -> Option<DTVCCPacket> {
-        let ret = self.packets.pop_back();
-        trace!("popped {ret:?}");
-        ret
+    /// Whether strict parsing is enabled
+    pub fn strict(&self) -> bool {
+        self.strict
     }
 
-    /// Any [`Cea608`] bytes in the last parsed `cc_data`
-    pub fn cea608(&mut self) -> Option<&[Cea608]> {
-        self.cea608.as_deref()
+    /// Accept `cc_data` passed to [push](Self::push) whose length does not match the
+    /// `cc_count` in the header instead of returning [ParserError::LengthMismatch].  Any bytes
+    /// after `cc_count` triples are ignored and if the data is too short, all the complete
+    /// triples are processed.  The default is `false`.
+    ///
+    /// The number of `cc_data` with a mismatched length is available from
+    /// [cc_count_mismatches](Self::cc_count_mismatches).
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut parser = CCDataParser::new();
+    /// parser.set_lenient_cc_count(true);
+    /// // cc_count of 2 followed by zero padding
+    /// let outcome = parser.push(&[0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00, 0x00, 0x00, 0x00]).unwrap();
+    /// assert_eq!(outcome.consumed, 8);
+    /// assert_eq!(parser.cc_count_mismatches(), 1);
+    /// let packet = parser.pop_packet().unwrap();
+    /// assert_eq!(packet.services()[0].codes(), [Code::LatinCapitalA]);
+    /// ```
+    pub fn set_lenient_cc_count(&mut self, lenient: bool) {
+        self.lenient_cc_count = lenient;
     }
-}
 
-/// A framerate.  Framerates larger than 60fps are not well supported.
-#[derive(Debug, Copy, Clone)]
-pub struct Framerate {
-    numer: u32,
-    denom: u32,
-}
+    /// Whether `cc_data` with a length that does not match the `cc_count` is accepted
+    pub fn lenient_cc_count(&self) -> bool {
+        self.lenient_cc_count
+    }
 
-impl Framerate {
-    /// Create a new [`Framerate`]
-    pub const fn new(numer: u32, denom: u32) -> Self {
-        Self { numer, denom }
+    /// The total number of `cc_data` whose length did not match the `cc_count` that were
+    /// accepted because of [set_lenient_cc_count](Self::set_lenient_cc_count)
+    pub fn cc_count_mismatches(&self) -> usize {
+        self.cc_count_mismatches
     }
 
-    /// The numerator of this [`Framerate`] fraction
-    pub fn numer(&self) -> u32 {
-        self.numer
+    /// Set how valid CEA-608 triples that follow CEA-708 triples in the same `cc_data` are
+    /// handled.  The default is [Cea608AfterCea708Policy::Error].
+    ///
+    /// The number of such CEA-608 triples is available from
+    /// [cea608_after_cea708_count](Self::cea608_after_cea708_count).
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// parser.handle_cea608();
+    /// parser.set_cea608_after_cea708_policy(Cea608AfterCea708Policy::Accept);
+    /// parser.push(&[0x43, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00, 0xFC, 0x80, 0x81]).unwrap();
+    /// assert_eq!(parser.cea608(), Some(&[Cea608::Field1(0x80, 0x81)][..]));
+    /// assert!(parser.pop_packet().is_some());
+    /// assert_eq!(parser.cea608_after_cea708_count(), 1);
+    /// ```
+    pub fn set_cea608_after_cea708_policy(&mut self, policy: Cea608AfterCea708Policy) {
+        self.cea608_after_cea708_policy = policy;
     }
 
-    /// The denominator of this [`Framerate`] fraction
-    pub fn denom(&self) -> u32 {
-        self.denom
+    /// How valid CEA-608 triples that follow CEA-708 triples are handled
+    pub fn cea608_after_cea708_policy(&self) -> Cea608AfterCea708Policy {
+        self.cea608_after_cea708_policy
     }
 
-    fn cea608_pairs_per_frame(&self) -> usize {
-        // CEA-608 has a max bitrate of 960 bits/s for a single field
-        // TODO: handle alternating counts for 24fps
-        60.mul_div_round(self.denom, self.numer).unwrap() as usize
+    /// The total number of valid CEA-608 triples that followed CEA-708 triples in the same
+    /// `cc_data`
+    pub fn cea608_after_cea708_count(&self) -> usize {
+        self.cea608_after_cea708
     }
 
-    fn max_cc_count(&self) -> usize {
-        // CEA-708 has a max bitrate of 9_600 bits/s
-        600.mul_div_round(self.denom, self.numer).unwrap() as usize
+    /// Skip `cc_data` that is identical to the previously pushed `cc_data`, e.g. from repeated
+    /// frames.  Neither the CEA-708 nor the CEA-608 data of the repeated `cc_data` is
+    /// processed and [PushOutcome::consumed] is 0.  The default is `false`.
+    ///
+    /// The number of skipped `cc_data` is available from
+    /// [repeated_frames](Self::repeated_frames).
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// parser.set_dedupe_repeated(true);
+    /// let cc_data = [0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00];
+    /// parser.push(&cc_data).unwrap();
+    /// assert_eq!(parser.push(&cc_data).unwrap().consumed, 0);
+    /// assert_eq!(parser.repeated_frames(), 1);
+    /// assert!(parser.pop_packet().is_some());
+    /// assert!(parser.pop_packet().is_none());
+    /// ```
+    pub fn set_dedupe_repeated(&mut self, dedupe: bool) {
+        self.dedupe_repeated = dedupe;
+        if !dedupe {
+            self.last_cc_data = None;
+        }
     }
-}
 
-/// A struct for writing cc_data packets
-#[derive(Debug, Default)]
-pub struct CCDataWriter {
-    // settings
-    output_cea608_padding: bool,
-    output_padding: bool,
-    // state
-    packets: VecDeque<DTVCCPacket>,
-    // part of a packet we could not fit into the previous packet
-    pending_packet_data: Vec<u8>,
-    cea608_1: VecDeque<(u8, u8)>,
-    cea608_2: VecDeque<(u8, u8)>,
-    last_cea608_was_field1: bool,
-}
+    /// Whether repeated `cc_data` is skipped
+    pub fn dedupe_repeated(&self) -> bool {
+        self.dedupe_repeated
+    }
 
-impl CCDataWriter {
-    /// Whether to output padding CEA-608 bytes when not enough enough data has been provided
-    pub fn set_output_cea608_padding(&mut self, output_cea608_padding: bool) {
-        self.output_cea608_padding = output_cea608_padding;
+    /// The total number of repeated `cc_data` that were skipped because of
+    /// [set_dedupe_repeated](Self::set_dedupe_repeated)
+    pub fn repeated_frames(&self) -> usize {
+        self.repeated_frames
     }
 
-    /// Whether padding CEA-608 bytes will be used
-    pub fn output_cea608_padding(&self) -> bool {
-        self.output_cea608_padding
+    fn queue_packet(&mut self, packet: DTVCCPacket) {
+        self.packets.push_front(packet);
+        let dropped = trim_queue(&mut self.packets, self.limits.max_queued_packets);
+        if dropped > 0 {
+            warn!("too many queued packets, dropped {dropped} oldest packets");
+            self.dropped_packets += dropped;
+        }
     }
 
-    /// Whether to output padding data in the CCP bitstream when not enough data has been provided
-    pub fn set_output_padding(&mut self, output_padding: bool) {
-        self.output_padding = output_padding;
+    /// Limit the number of bytes of an incomplete [DTVCCPacket] that are kept between calls to
+    /// [push](Self::push).  If the limit is exceeded, the incomplete [DTVCCPacket] is dropped and
+    /// parsing continues from the next [DTVCCPacket] header.  `None` disables the limit.  The
+    /// default is [DEFAULT_MAX_PENDING_BYTES](Self::DEFAULT_MAX_PENDING_BYTES).
+    ///
+    /// Every [DTVCCPacket] header replaces any previous incomplete [DTVCCPacket] so the default
+    /// only bounds the data to the largest possible [DTVCCPacket].  A smaller limit avoids waiting
+    /// on a [DTVCCPacket] that advertises more data than will ever be provided.
+    ///
+    /// The number of dropped bytes is available from
+    /// [dropped_pending_bytes](Self::dropped_pending_bytes).
+    pub fn set_max_pending_bytes(&mut self, max: Option<usize>) {
+        self.limits.max_pending_bytes = max;
     }
 
-    /// Whether padding data will be produced in the CCP
-    pub fn output_padding(&self) -> bool {
-        self.output_padding
+    /// The maximum number of bytes of an incomplete [DTVCCPacket] that are kept
+    pub fn max_pending_bytes(&self) -> Option<usize> {
+        self.limits.max_pending_bytes
     }
 
-    /// Push a [`DTVCCPacket`] for writing
-    pub fn push_packet(&mut self, packet: DTVCCPacket) {
-        self.packets.push_front(packet)
+    /// Limit the number of parsed [DTVCCPacket]s waiting to be retrieved with
+    /// [pop_packet](Self::pop_packet).  If the limit is exceeded, the oldest [DTVCCPacket]s are
+    /// dropped.  `None` disables the limit and every parsed [DTVCCPacket] is kept until it is
+    /// retrieved.  The default is [DEFAULT_MAX_QUEUED_PACKETS](Self::DEFAULT_MAX_QUEUED_PACKETS).
+    ///
+    /// The number of dropped [DTVCCPacket]s is available from
+    /// [dropped_packets](Self::dropped_packets).
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// parser.set_max_queued_packets(Some(1));
+    /// // two complete DTVCCPackets
+    /// parser.push(&[0x44, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00, 0xFF, 0x42, 0x21, 0xFE, 0x42, 0x00]).unwrap();
+    /// assert_eq!(parser.dropped_packets(), 1);
+    /// assert_eq!(parser.pop_packet().unwrap().sequence_no(), 1);
+    /// assert!(parser.pop_packet().is_none());
+    /// ```
+    pub fn set_max_queued_packets(&mut self, max: Option<usize>) {
+        self.limits.max_queued_packets = max;
     }
 
-    /// Push a [`Cea608`] byte pair for writing
-    pub fn push_cea608(&mut self, cea608: Cea608) {
-        match cea608 {
-            Cea608::Field1(byte0, byte1) => {
-                if byte0 != 0x80 || byte1 != 0x80 {
-                    self.cea608_1.push_front((byte0, byte1))
-                }
-            }
-            Cea608::Field2(byte0, byte1) => {
-                if byte0 != 0x80 || byte1 != 0x80 {
-                    self.cea608_2.push_front((byte0, byte1))
-                }
-            }
-        }
+    /// The maximum number of parsed [DTVCCPacket]s that are kept
+    pub fn max_queued_packets(&self) -> Option<usize> {
+        self.limits.max_queued_packets
     }
 
-    /// Clear all stored data
-    pub fn flush(&mut self) {
-        self.packets.clear();
-        self.pending_packet_data.clear();
-        self.cea608_1.clear();
-        self.cea608_2.clear();
+    /// Limit the number of [`Cea608`] byte pairs waiting to be retrieved with
+    /// [pop_cea608](Self::pop_cea608).  If the limit is exceeded, the oldest [`Cea608`] byte
+    /// pairs are dropped.  `None` disables the limit.  The default is
+    /// [DEFAULT_MAX_QUEUED_CEA608](Self::DEFAULT_MAX_QUEUED_CEA608).
+    ///
+    /// Applications that only use [cea608](Self::cea608) or [take_cea608](Self::take_cea608)
+    /// never retrieve the queued [`Cea608`] byte pairs and rely on this limit to bound the
+    /// memory used by the queue.
+    ///
+    /// The number of dropped [`Cea608`] byte pairs is available from
+    /// [dropped_cea608](Self::dropped_cea608).
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// parser.handle_cea608();
+    /// parser.set_max_queued_cea608(Some(1));
+    /// parser.push(&[0x42, 0xFF, 0xFC, 0x80, 0x81, 0xFD, 0x82, 0x83]).unwrap();
+    /// assert_eq!(parser.dropped_cea608(), 1);
+    /// assert_eq!(parser.pop_cea608(), Some(Cea608::Field2(0x82, 0x83)));
+    /// assert_eq!(parser.pop_cea608(), None);
+    /// ```
+    pub fn set_max_queued_cea608(&mut self, max: Option<usize>) {
+        self.limits.max_queued_cea608 = max;
     }
 
-    /// The amount of time that is currently stored for CEA-608 field 1 data
-    pub fn buffered_cea608_field1_duration(&self) -> Duration {
-        // CEA-608 has a max bitrate of 60000 * 2 / 1001 bytes/s
-        Duration::from_micros(
-            (self.cea608_1.len() as u64)
-                .mul_div_ceil(1001 * 1_000_000, 60000)
-                .unwrap(),
-        )
+    /// The maximum number of [`Cea608`] byte pairs that are kept for
+    /// [pop_cea608](Self::pop_cea608)
+    pub fn max_queued_cea608(&self) -> Option<usize> {
+        self.limits.max_queued_cea608
     }
 
-    /// The amount of time that is currently stored for CEA-608 field 2 data
-    pub fn buffered_cea608_field2_duration(&self) -> Duration {
-        // CEA-608 has a max bitrate of 60000 * 2 / 1001 bytes/s
-        Duration::from_micros(
-            (self.cea608_2.len() as u64)
-                .mul_div_ceil(1001 * 1_000_000, 60000)
-                .unwrap(),
-        )
+    /// The total number of bytes of incomplete [DTVCCPacket]s that have been dropped because of
+    /// [set_max_pending_bytes](Self::set_max_pending_bytes)
+    pub fn dropped_pending_bytes(&self) -> usize {
+        self.dropped_pending_bytes
     }
 
-    fn buffered_packet_bytes(&self) -> usize {
-        self.pending_packet_data.len()
-            + self
-                .packets
-                .iter()
-                .map(|packet| packet.len())
-                .sum::<usize>()
+    /// The total number of [DTVCCPacket]s that have been dropped because of
+    /// [set_max_queued_packets](Self::set_max_queued_packets)
+    pub fn dropped_packets(&self) -> usize {
+        self.dropped_packets
     }
 
-    /// The amount of time that is currently stored for CCP data
-    pub fn buffered_packet_duration(&self) -> Duration {
-        // CEA-708 has a max bitrate of 9600000 / 1001 bits/s
-        Duration::from_micros(
-            ((self.buffered_packet_bytes() + 1) as u64 / 2)
-                .mul_div_ceil(2 * 1001 * 1_000_000, 9_600_000 / 8)
-                .unwrap(),
-        )
+    /// The total number of [`Cea608`] byte pairs that have been dropped because of
+    /// [set_max_queued_cea608](Self::set_max_queued_cea608)
+    pub fn dropped_cea608(&self) -> usize {
+        self.dropped_cea608
     }
 
-    /// Write the next cc_data packet taking the next relevant CEA-608 byte pairs and
-    /// [`DTVCCPacket`]s.  The framerate provided determines how many bytes are written.
-    pub fn write<W: std::io::Write>(
-        &mut self,
-        framerate: Framerate,
-        w: &mut W,
-    ) -> Result<(), std::io::Error> {
-        let mut cea608_pair_rem = if self.output_cea608_padding {
-            framerate.cea608_pairs_per_frame()
-        } else {
-            framerate
-                .cea608_pairs_per_frame()
-                .min(self.cea608_1.len().max(self.cea608_2.len() * 2))
-        };
-
-        let mut cc_count_rem = if self.output_padding {
-            framerate.max_cc_count()
-        } else {
-            framerate.max_cc_count().min(
-                cea608_pair_rem
-                    + self.pending_packet_data.len() / 3
-                    + self.packets.iter().map(|p| p.cc_count()).sum::<usize>(),
-            )
-        };
-        trace!("writing with cc_count: {cc_count_rem} and {cea608_pair_rem} cea608 pairs");
-
-        let reserved = 0x80;
-        let process_cc_flag = 0x40;
-        w.write_all(&[
-            reserved | process_cc_flag | (cc_count_rem & 0x1f) as u8,
-            0xFF,
-        ])?;
-        while cc_count_rem > 0 {
-            if cea608_pair_rem > 0 {
-                if !self.last_cea608_was_field1 {
-                    trace!("attempting to write a cea608 byte pair from field 1");
-                    if let Some((byte0, byte1)) = self.cea608_1.pop_back() {
-                        w.write_all(&[0xFC, byte0, byte1])?;
-                        cc_count_rem -= 1;
-                    } else if !self.cea608_2.is_empty() {
-                        // need to write valid field 0 if we are going to write field 1
-                        w.write_all(&[0xFC, 0x80, 0x80])?;
-                        cc_count_rem -= 1;
-                    } else if self.output_cea608_padding {
-                        w.write_all(&[0xF8, 0x80, 0x80])?;
-                        cc_count_rem -= 1;
-                    }
-                    self.last_cea608_was_field1 = true;
-                } else {
-                    trace!("attempting to write a cea608 byte pair from field 2");
-                    if let Some((byte0, byte1)) = self.cea608_2.pop_back() {
-                        w.write_all(&[0xFD, byte0, byte1])?;
-                        cc_count_rem -= 1;
-                    } else if self.output_cea608_padding {
-                        w.write_all(&[0xF9, 0x80, 0x80])?;
-                        cc_count_rem -= 1;
-                    }
-                    self.last_cea608_was_field1 = false;
-                }
-                cea608_pair_rem -= 1;
-            } else {
-                let mut current_packet_data = &mut self.pending_packet_data;
-                let mut packet_offset = 0;
-                while packet_offset >= current_packet_data.len() {
-                    if let Some(packet) = self.packets.pop_back() {
-                        trace!("starting packet {packet:?}");
-                        packet.write_as_cc_data(&mut current_packet_data)?;
-                    } else {
-                        trace!("no packet to write");
-                        break;
-                    }
-                }
-
-                trace!("cea708 pending data length {}", current_packet_data.len(),);
-
-                while packet_offset < current_packet_data.len() && cc_count_rem > 0 {
-                    assert!(current_packet_data.len() >= packet_offset + 3);
-                    w.write_all(&current_packet_data[packet_offset..packet_offset + 3])?;
-                    packet_offset += 3;
-                    cc_count_rem -= 1;
-                }
-
-                self.pending_packet_data = current_packet_data[packet_offset..].to_vec();
-
-                if self.packets.is_empty() && self.pending_packet_data.is_empty() {
-                    // no more data to write
-                    if self.output_padding {
-                        trace!("writing {cc_count_rem} padding bytes");
-                        while cc_count_rem > 0 {
-                            w.write_all(&[0xFA, 0x00, 0x00])?;
-                            cc_count_rem -= 1;
-                        }
-                    }
-                    break;
-                }
-            }
+    /// Clear any internal buffers, including any incomplete `cc_data` from
+    /// [push_stream](Self::push_stream), any incomplete [DTVCCPacket], any parsed
+    /// [DTVCCPacket]s and any [`Cea608`] bytes.  Settings and statistics are not changed.
+    pub fn flush(&mut self) {
+        self.stream_data.clear();
+        self.frame_triples.clear();
+        self.frame_in_dtvcc = false;
+        self.frame_failed = false;
+        self.last_cc_data = None;
+        self.pending_data.clear();
+        self.have_initial_ccp_header = false;
+        self.ccp_bytes_needed = 0;
+        self.packets.clear();
+        if let Some(ref mut cea608) = self.cea608 {
+            cea608.clear();
         }
-        Ok(())
+        self.cea608_queue.clear();
+        self.cea608_field1.clear();
+        self.cea608_field2.clear();
     }
-}
-
-/// A packet in the `cc_data` bitstream
-#[derive(Debug)]
-pub struct DTVCCPacket {
-    seq_no: u8,
-    services: Vec<Service>,
-}
 
-impl DTVCCPacket {
-    /// Create a new [DTVCCPacket] with the specified sequence number.
+    /// Parse any incomplete [DTVCCPacket] and then [flush](Self::flush) this parser, e.g. at the
+    /// end of a stream.  Settings are kept so the parser can be reused for another stream.
     ///
-    /// # Panics
+    /// The returned [DTVCCPacket] is likely to be truncated (see
+    /// [DTVCCPacket::was_truncated]) and only contains the [tables::Code]s that were completely
+    /// received.  `None` is returned if there is no incomplete [DTVCCPacket] or it does not
+    /// contain any complete [tables::Code]s.  Any [DTVCCPacket]s that have not been retrieved
+    /// with [pop_packet](Self::pop_packet) are discarded.
     ///
-    /// * If seq_no >= 4
-    pub fn new(seq_no: u8) -> Self {
-        if seq_no > 3 {
-            panic!("DTVCCPacket sequence numbers must be between 0 and 3 inclusive, not {seq_no}");
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut parser = CCDataParser::new();
+    /// // the start of a DTVCCPacket advertising 5 bytes
+    /// parser.push(&[0x42, 0xFF, 0xFF, 0x03, 0x22, 0xFE, 0x41, 0x42]).unwrap();
+    /// assert!(parser.pop_packet().is_none());
+    /// let packet = parser.finish().unwrap();
+    /// assert!(packet.was_truncated());
+    /// assert_eq!(packet.services()[0].codes(), [Code::LatinCapitalA, Code::LatinCapitalB]);
+    /// assert!(parser.finish().is_none());
+    /// ```
+    pub fn finish(&mut self) -> Option<DTVCCPacket> {
+        let pending = std::mem::take(&mut self.pending_data);
+        self.flush();
+        if pending.is_empty() {
+            return None;
         }
-        Self {
-            seq_no,
-            services: vec![],
+        match DTVCCPacket::parse(&pending) {
+            Ok(packet) if !packet.services().is_empty() => Some(packet),
+            Ok(_) => None,
+            Err(e) => {
+                debug!("failed to parse pending data: {e}");
+                None
+            }
         }
     }
 
-    /// The sequence number of the DTVCCPacket
+    /// The number of times the sequence number of a [DTVCCPacket] returned by
+    /// [pop_packet](Self::pop_packet) did not follow on from the previously returned
+    /// [DTVCCPacket]
     ///
     /// # Examples
     /// ```
     /// # use cea708_types::*;
-    /// let packet = DTVCCPacket::new(2);
-    /// assert_eq!(2, packet.sequence_no());
+    /// let mut parser = CCDataParser::new();
+    /// // sequence numbers 0 and 2
+    /// parser.push(&[0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00]).unwrap();
+    /// parser.push(&[0x42, 0xFF, 0xFF, 0x82, 0x21, 0xFE, 0x41, 0x00]).unwrap();
+    /// assert_eq!(parser.pop_packet().unwrap().sequence_no(), 0);
+    /// assert_eq!(parser.sequence_discontinuities(), 0);
+    /// assert_eq!(parser.pop_packet().unwrap().sequence_no(), 2);
+    /// assert_eq!(parser.sequence_discontinuities(), 1);
     /// ```
-    pub fn sequence_no(&self) -> u8 {
-        self.seq_no
-    }
-
-    /// The amount of free space (in bytes) that can by placed inside this [DTVCCPacket]
-    pub fn free_space(&self) -> usize {
-        // 128 is the max size of a DTVCCPacket, minus 1 for the header
-        128 - self.len()
+    pub fn sequence_discontinuities(&self) -> usize {
+        self.sequence_discontinuities
     }
 
-    /// The number of bytes this [DTVCCPacket] will use when written to a byte stream.
+    /// Forget the sequence number of the last [DTVCCPacket] returned by
+    /// [pop_packet](Self::pop_packet) so that the next [DTVCCPacket] is not counted in
+    /// [sequence_discontinuities](Self::sequence_discontinuities), e.g. when splicing streams.
+    /// Any buffered data is kept.
     ///
     /// # Examples
     /// ```
-    /// # use cea708_types::{*, tables::*};
-    /// let mut packet = DTVCCPacket::new(2);
-    /// assert_eq!(0, packet.len());
-    /// let mut service = Service::new(1);
-    /// service.push_code(&Code::LatinCapitalA).unwrap();
-    /// packet.push_service(service);
-    /// assert_eq!(3, packet.len());
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// parser.push(&[0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00]).unwrap();
+    /// parser.pop_packet().unwrap();
+    /// parser.reset_sequence_tracking();
+    /// parser.push(&[0x42, 0xFF, 0xFF, 0x82, 0x21, 0xFE, 0x41, 0x00]).unwrap();
+    /// assert_eq!(parser.pop_packet().unwrap().sequence_no(), 2);
+    /// assert_eq!(parser.sequence_discontinuities(), 0);
     /// ```
-    pub fn len(&self) -> usize {
-        let services_len = self.services.iter().map(|s| s.len()).sum::<usize>();
-        if services_len > 0 {
-            1 + services_len
-        } else {
-            0
+    pub fn reset_sequence_tracking(&mut self) {
+        self.last_sequence_no = None;
+    }
+
+    /// Pop a valid [DTVCCPacket] or None if no packet could be parsed
+    pub fn pop_packet(&mut self) -> Option<DTVCCPacket> {
+        let ret = self.packets.pop_back();
+        trace!("popped {ret:?}");
+        if let Some(packet) = ret.as_ref() {
+            let seq_no = packet.sequence_no();
+            if let Some(last) = self.last_sequence_no {
+                if seq_no != (last + 1) & 0x3 {
+                    warn!("DTVCCPacket sequence number discontinuity from {last} to {seq_no}");
+                    self.sequence_discontinuities += 1;
+                }
+            }
+            self.last_sequence_no = Some(seq_no);
         }
+        ret
     }
 
-    /// Push a completed service block into this [DTVCCPacket]
+    /// The `cc_count` of the `cc_data` header from the last call to [push](Self::push), or
+    /// `None` if no `cc_data` header was provided
     ///
     /// # Examples
     /// ```
-    /// # use cea708_types::{*, tables::*};
-    /// let mut packet = DTVCCPacket::new(2);
-    /// assert_eq!(0, packet.len());
-    /// let mut service = Service::new(1);
-    /// service.push_code(&Code::LatinCapitalA).unwrap();
-    /// packet.push_service(service);
-    /// assert_eq!(3, packet.len());
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// assert_eq!(parser.last_cc_count(), None);
+    /// parser.push(&[0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00]).unwrap();
+    /// assert_eq!(parser.last_cc_count(), Some(2));
+    /// assert_eq!(parser.last_process_flag(), Some(true));
     /// ```
-    pub fn push_service(&mut self, service: Service) -> Result<(), WriterError> {
-        // TODO: fail if we would overrun max size
-        if service.len() > self.free_space() {
-            return Err(WriterError::WouldOverflow(
-                service.len() - self.free_space(),
-            ));
-        }
-        self.services.push(service);
-        Ok(())
+    pub fn last_cc_count(&self) -> Option<u8> {
+        self.last_header.map(|(_, cc_count)| cc_count)
     }
 
-    fn parse_hdr_byte(byte: u8) -> (u8, usize) {
-        let seq_no = (byte & 0xC0) >> 6;
-        let len = byte & 0x3F;
-        let len = if len == 0 {
-            127usize
-        } else {
-            ((len as usize) * 2) - 1
-        };
-        (seq_no, len)
+    /// The `process_cc_data_flag` of the `cc_data` header from the last call to
+    /// [push](Self::push), or `None` if no `cc_data` header was provided
+    pub fn last_process_flag(&self) -> Option<bool> {
+        self.last_header.map(|(process, _)| process)
     }
 
-    /// Parse bytes into a [DTVCCPacket]
+    /// The total number of `cc_data` passed to [push](Self::push) that were skipped because the
+    /// `process_cc_data_flag` was not set
     ///
-    /// Will return [ParserError::LengthMismatch] if the data is shorter than the length advertised in
-    /// the [DTVCCPacket] header.
-    ///
-    /// Will return errors from [Service::parse] if parsing the contained [Service]s fails.
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// // process_cc_data_flag not set
+    /// let outcome = parser.push(&[0x82, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00]).unwrap();
+    /// assert_eq!(outcome.consumed, 0);
+    /// assert_eq!(parser.unprocessed_frames(), 1);
+    /// // no triples
+    /// parser.push(&[0xC0, 0xFF]).unwrap();
+    /// assert_eq!(parser.empty_frames(), 1);
+    /// ```
+    pub fn unprocessed_frames(&self) -> usize {
+        self.unprocessed_frames
+    }
+
+    /// The total number of `cc_data` passed to [push](Self::push) that have the
+    /// `process_cc_data_flag` set and a `cc_count` of 0
+    pub fn empty_frames(&self) -> usize {
+        self.empty_frames
+    }
+
+    /// Remove all the parsed [DTVCCPacket]s in the same order as repeated calls to
+    /// [pop_packet](Self::pop_packet).  Any [DTVCCPacket]s that are not consumed from the
+    /// returned [Iterator] are kept.
     ///
     /// # Examples
     /// ```
-    /// # use cea708_types::{*, tables::*};
-    /// let data = [0x02, 0x21, 0x41, 0x00];
-    /// let packet = DTVCCPacket::parse(&data).unwrap();
-    /// assert_eq!(3, packet.len());
-    /// assert_eq!(0, packet.sequence_no());
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// parser.push(&[0x44, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00, 0xFF, 0x42, 0x21, 0xFE, 0x42, 0x00]).unwrap();
+    /// let seq_nos = parser.drain_packets().map(|packet| packet.sequence_no()).collect::<Vec<_>>();
+    /// assert_eq!(seq_nos, [0, 1]);
+    /// assert!(parser.pop_packet().is_none());
     /// ```
-    pub fn parse(data: &[u8]) -> Result<Self, ParserError> {
-        if data.is_empty() {
-            return Err(ParserError::LengthMismatch {
-                expected: 1,
-                actual: 0,
-            });
-        }
-        let (seq_no, len) = Self::parse_hdr_byte(data[0]);
-        trace!(
-            "dtvcc seq:{seq_no} len {len} data {data_len}",
-            data_len = data.len()
-        );
-        if (len + 1) < data.len() {
-            return Err(ParserError::LengthMismatch {
-                expected: len + 1,
-                actual: data.len(),
-            });
-        }
+    pub fn drain_packets(&mut self) -> impl Iterator<Item = DTVCCPacket> + '_ {
+        std::iter::from_fn(move || self.pop_packet())
+    }
 
-        let mut offset = 1;
-        let mut services = vec![];
-        while offset < data.len() {
-            let service = Service::parse(&data[offset..])?;
-            trace!("parsed service {service:?}, len:{}", service.len());
-            if service.len() == 0 {
-                offset += 1;
-                continue;
-            }
-            offset += service.len();
-            services.push(service);
-        }
-        Ok(Self { seq_no, services })
+    /// Any [`Cea608`] bytes in the last parsed `cc_data`, or all the [`Cea608`] bytes since the
+    /// last call to [take_cea608](Self::take_cea608) when accumulating.
+    pub fn cea608(&mut self) -> Option<&[Cea608]> {
+        self.cea608.as_deref()
     }
 
-    /// The [Service]s for this [DTVCCPacket]
-    pub fn services(&self) -> &[Service] {
-        &self.services
+    /// Pop the oldest [`Cea608`] byte pair parsed by any previous call to [push](Self::push).
+    ///
+    /// Unlike [cea608](Self::cea608), [`Cea608`] byte pairs are kept until they are popped and
+    /// are not lost when multiple `cc_data` are pushed before retrieving them.  Always `None`
+    /// unless [handle_cea608](Self::handle_cea608) has been called.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// parser.handle_cea608();
+    /// parser.push(&[0x41, 0xFF, 0xFC, 0x80, 0x81]).unwrap();
+    /// parser.push(&[0x41, 0xFF, 0xFD, 0x82, 0x83]).unwrap();
+    /// assert_eq!(parser.pop_cea608(), Some(Cea608::Field1(0x80, 0x81)));
+    /// assert_eq!(parser.pop_cea608(), Some(Cea608::Field2(0x82, 0x83)));
+    /// assert_eq!(parser.pop_cea608(), None);
+    /// ```
+    pub fn pop_cea608(&mut self) -> Option<Cea608> {
+        self.cea608_queue.pop_back()
     }
 
-    fn cc_count(&self) -> usize {
-        (self.len() + 1) / 2
+    /// The CEA-608 field 1 byte pairs in the last parsed `cc_data`
+    ///
+    /// Always empty unless [handle_cea608](Self::handle_cea608) has been called.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// parser.handle_cea608();
+    /// parser.push(&[0x43, 0xFF, 0xFC, 0x80, 0x81, 0xFD, 0x82, 0x83, 0xFC, 0x84, 0x85]).unwrap();
+    /// assert_eq!(parser.cea608_field1(), [(0x80, 0x81), (0x84, 0x85)]);
+    /// assert_eq!(parser.cea608_field2(), [(0x82, 0x83)]);
+    /// ```
+    pub fn cea608_field1(&self) -> &[(u8, u8)] {
+        &self.cea608_field1
     }
 
-    fn hdr_byte(&self) -> u8 {
-        let packet_size_code = if self.len() == 127 {
-            0
-        } else {
-            (self.len() + 1) / 2
-        };
-        (self.seq_no & 0x3) << 6 | packet_size_code as u8
+    /// The CEA-608 field 2 byte pairs in the last parsed `cc_data`
+    ///
+    /// Always empty unless [handle_cea608](Self::handle_cea608) has been called.
+    pub fn cea608_field2(&self) -> &[(u8, u8)] {
+        &self.cea608_field2
     }
 
-    /// Write the [DTVCCPacket] to a byte stream
+    /// Keep [`Cea608`] bytes across multiple calls to [push](Self::push) instead of only
+    /// providing the [`Cea608`] bytes of the last parsed `cc_data`.  The default is `false`.
+    ///
+    /// Has no effect unless [handle_cea608](Self::handle_cea608) has been called.
     ///
     /// # Examples
     /// ```
-    /// # use cea708_types::{*, tables::*};
-    /// let mut packet = DTVCCPacket::new(2);
-    /// let mut service = Service::new(1);
-    /// service.push_code(&Code::LatinCapitalA).unwrap();
-    /// packet.push_service(service);
-    /// let mut written = vec![];
-    /// packet.write(&mut written);
-    /// let expected = [0x82, 0x21, 0x41, 0x00];
-    /// assert_eq!(written, expected);
+    /// # use cea708_types::*;
+    /// let mut parser = CCDataParser::new();
+    /// parser.handle_cea608();
+    /// parser.set_accumulate_cea608(true);
+    /// parser.push(&[0x41, 0xFF, 0xFC, 0x80, 0x81]).unwrap();
+    /// parser.push(&[0x41, 0xFF, 0xFC, 0x82, 0x83]).unwrap();
+    /// assert_eq!(
+    ///     parser.take_cea608(),
+    ///     [Cea608::Field1(0x80, 0x81), Cea608::Field1(0x82, 0x83)]
+    /// );
+    /// assert!(parser.take_cea608().is_empty());
     /// ```
-    pub fn write<W: std::io::Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
-        // TODO: fail if we would overrun max size
-        w.write_all(&[self.hdr_byte()])?;
-        for service in self.services.iter() {
-            service.write(w)?;
-        }
-        if self.len() % 2 == 1 {
-            w.write_all(&[0x00])?;
+    pub fn set_accumulate_cea608(&mut self, accumulate: bool) {
+        self.accumulate_cea608 = accumulate;
+    }
+
+    /// Remove and return all the currently stored [`Cea608`] bytes
+    pub fn take_cea608(&mut self) -> Vec<Cea608> {
+        self.cea608.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+}
+
+// Remove the oldest (back) entries of `queue` until it contains at most `max` entries.  Returns
+// the number of removed entries.
+fn trim_queue<T>(queue: &mut VecDeque<T>, max: Option<usize>) -> usize {
+    let Some(max) = max else {
+        return 0;
+    };
+    let dropped = queue.len().saturating_sub(max);
+    queue.truncate(max);
+    dropped
+}
+
+/// Errors that can occur when reading `cc_data` with a [CCDataReader]
+#[derive(Debug, thiserror::Error)]
+pub enum CCDataReaderError {
+    /// Reading from the underlying reader failed
+    #[error("Failed to read cc_data: {0}")]
+    Io(#[from] std::io::Error),
+    /// Parsing the `cc_data` failed
+    #[error("Failed to parse cc_data: {0}")]
+    Parser(#[from] ParserError),
+}
+
+/// Reads [DTVCCPacket]s from a stream of `cc_data` triples without the two byte `cc_data` header.
+///
+/// The stream is read in chunks of `cc_count` triples which are pushed into a [CCDataParser] with
+/// [CCDataParser::push_triples].
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, tables::*};
+/// let triples = [0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00];
+/// let mut reader = CCDataReader::new(std::io::Cursor::new(triples));
+/// let packet = reader.next().unwrap().unwrap();
+/// assert_eq!(packet.services()[0].codes(), [Code::LatinCapitalA]);
+/// assert!(reader.next().is_none());
+/// ```
+#[derive(Debug)]
+pub struct CCDataReader<R: std::io::Read> {
+    reader: R,
+    parser: CCDataParser,
+    cc_count: usize,
+    done: bool,
+}
+
+impl<R: std::io::Read> CCDataReader<R> {
+    /// Create a new [CCDataReader] reading from `reader` using the `cc_count` of a 30000/1001
+    /// framerate
+    pub fn new(reader: R) -> Self {
+        Self::with_framerate(reader, Framerate::new(30000, 1001))
+    }
+
+    /// Create a new [CCDataReader] reading from `reader` using the maximum `cc_count` for
+    /// `framerate`
+    pub fn with_framerate(reader: R, framerate: Framerate) -> Self {
+        Self {
+            reader,
+            parser: CCDataParser::new(),
+            cc_count: framerate.max_cc_count().clamp(1, 31),
+            done: false,
         }
-        Ok(())
     }
 
-    fn write_as_cc_data<W: std::io::Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
-        // TODO: fail if we would overrun max size
-        // TODO: handle framerate?
-        if self.services.is_empty() {
-            return Ok(());
+    /// Set the number of triples that are read for each `cc_data`.  Values are clamped to the
+    /// range 1 to 31.
+    pub fn set_cc_count(&mut self, cc_count: usize) {
+        self.cc_count = cc_count.clamp(1, 31);
+    }
+
+    /// The number of triples that are read for each `cc_data`
+    pub fn cc_count(&self) -> usize {
+        self.cc_count
+    }
+
+    /// The [CCDataParser] used for parsing
+    pub fn parser(&self) -> &CCDataParser {
+        &self.parser
+    }
+
+    /// The [CCDataParser] used for parsing, e.g. for enabling CEA-608 handling
+    pub fn parser_mut(&mut self) -> &mut CCDataParser {
+        &mut self.parser
+    }
+
+    /// Consume the [CCDataReader] and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_chunk(&mut self) -> Result<(), CCDataReaderError> {
+        let mut buf = vec![0; self.cc_count * 3];
+        let mut size = 0;
+        while size < buf.len() {
+            match self.reader.read(&mut buf[size..]) {
+                Ok(0) => {
+                    self.done = true;
+                    break;
+                }
+                Ok(n) => size += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.done = true;
+                    return Err(e.into());
+                }
+            }
         }
-        let mut written = vec![];
-        for service in self.services.iter() {
-            service.write(&mut written)?;
-            trace!("wrote service {service:?}");
+        if size % 3 != 0 {
+            warn!("ignoring {} trailing bytes", size % 3);
         }
-        w.write_all(&[0xFF, self.hdr_byte(), written[0]])?;
-        for pair in written[1..].chunks(2) {
-            let cc_valid = 0x04;
-            let cc_type = 0b10;
-            let reserved = 0xF8;
-            w.write_all(&[reserved | cc_valid | cc_type])?;
-            w.write_all(pair)?;
-            if pair.len() == 1 {
-                w.write_all(&[0x00])?;
-            }
+        let cc_count = size / 3;
+        if cc_count == 0 {
+            return Ok(());
         }
+        trace!("read {cc_count} triples");
+        self.parser.push_triples(&buf[..cc_count * 3])?;
         Ok(())
     }
 }
 
-/// A [Service] in a [DTVCCPacket]
+impl<R: std::io::Read> Iterator for CCDataReader<R> {
+    type Item = Result<DTVCCPacket, CCDataReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(packet) = self.parser.pop_packet() {
+                return Some(Ok(packet));
+            }
+            if self.done {
+                return None;
+            }
+            if let Err(e) = self.read_chunk() {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// Groups the [tables::Code]s of [DTVCCPacket]s by service number
 ///
-/// As specified in CEA-708, there can be a maximum of 63 services.  Service 1 is the primary
-/// caption service and Service 2 is the secondary caption service.  All other services are
-/// undefined.
+/// [tables::Code]s for each service are accumulated across packet boundaries in the order they
+/// were received until retrieved with [pop](Self::pop).  Both standard (1-6) and extended (7-63)
+/// service numbers are supported.
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, tables::*};
+/// let mut parser = CCDataParser::new();
+/// let mut demux = ServiceDemux::new();
+/// parser.push(&[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00]).unwrap();
+/// parser.push(&[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x42, 0x21, 0xFE, 0x42, 0x00]).unwrap();
+/// demux.push_from_parser(&mut parser);
+/// assert_eq!(demux.services_with_data().collect::<Vec<_>>(), [1]);
+/// assert_eq!(demux.pop(1), Some(vec![Code::LatinCapitalA, Code::LatinCapitalB]));
+/// assert_eq!(demux.pop(1), None);
+/// ```
 #[derive(Debug, Clone)]
-pub struct Service {
-    number: u8,
-    codes: Vec<tables::Code>,
+pub struct ServiceDemux {
+    services: Vec<Vec<tables::Code>>,
 }
 
-impl Service {
-    /// Create a new [Service]
-    ///
-    /// # Panics
-    ///
-    /// * if number >= 64
-    pub fn new(service_no: u8) -> Self {
-        if service_no >= 64 {
-            panic!("Service numbers must be between 0 and 63 inclusive, not {service_no}");
-        }
+impl Default for ServiceDemux {
+    fn default() -> Self {
         Self {
-            number: service_no,
-            codes: vec![],
+            services: vec![vec![]; 64],
         }
     }
+}
 
-    /// Returns the number of this [Service]
-    ///
-    /// # Examples
-    /// ```
-    /// # use cea708_types::{*, tables::*};
-    /// let mut service = Service::new(1);
-    /// assert_eq!(service.number(), 1);
-    /// ```
-    pub fn number(&self) -> u8 {
-        self.number
+impl ServiceDemux {
+    /// Create a new [ServiceDemux]
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn codes_len(&self) -> usize {
-        self.codes.iter().map(|c| c.byte_len()).sum()
+    /// Add the [tables::Code]s of each [Service] in `packet` to the end of the relevant service
+    pub fn push_packet(&mut self, packet: DTVCCPacket) {
+        for service in packet {
+            let service_no = service.number() as usize;
+            self.services[service_no].extend(service);
+        }
     }
 
-    /// The amount of free space (in bytes) that can by placed inside this [Service] block
-    ///
-    /// # Examples
-    /// ```
-    /// # use cea708_types::{*, tables::*};
-    /// let service = Service::new(1);
-    /// assert_eq!(service.free_space(), 31);
-    /// ```
-    pub fn free_space(&self) -> usize {
-        // 31 is the maximum size of a service block
-        31 - self.codes_len()
+    /// Push all of the [DTVCCPacket]s currently available from `parser`
+    pub fn push_from_parser(&mut self, parser: &mut CCDataParser) {
+        while let Some(packet) = parser.pop_packet() {
+            self.push_packet(packet);
+        }
     }
 
-    /// The length in bytes of this [Service] block
-    ///
-    /// # Examples
-    /// ```
-    /// # use cea708_types::{*, tables::*};
-    /// let mut service = Service::new(1);
-    /// assert_eq!(service.len(), 0);
-    /// service.push_code(&Code::LatinCapitalA).unwrap();
-    /// assert_eq!(service.len(), 2);
-    /// service.push_code(&Code::LatinCapitalB).unwrap();
-    /// assert_eq!(service.len(), 3);
-    /// ```
-    pub fn len(&self) -> usize {
-        if self.number == 0 {
-            return 0;
+    /// Retrieve all of the [tables::Code]s accumulated for `service_no`.  Returns `None` if no
+    /// [tables::Code]s are available.
+    pub fn pop(&mut self, service_no: u8) -> Option<Vec<tables::Code>> {
+        let codes = self.services.get_mut(service_no as usize)?;
+        if codes.is_empty() {
+            return None;
         }
-        if self.codes.is_empty() {
-            return 0;
-        }
-        let hdr_size = if self.number >= 7 { 2 } else { 1 };
-        hdr_size + self.codes_len()
+        Some(std::mem::take(codes))
     }
 
-    /// Push a [tables::Code] to the end of this [Service]
-    ///
-    /// # Errors
-    ///
-    /// * [WriterError::ReadOnly] if [Service] is number 0 (called the NULL Service)
-    /// * [WriterError::WouldOverflow] if adding the [tables::Code] would cause to [Service] to overflow
-    ///
-    /// # Examples
-    /// ```
-    /// # use cea708_types::{*, tables::*};
-    /// let mut service = Service::new(1);
-    /// service.push_code(&Code::LatinCapitalA).unwrap();
-    /// ```
-    pub fn push_code(&mut self, code: &tables::Code) -> Result<(), WriterError> {
-        // TODO: errors?
-        if self.number == 0 {
-            return Err(WriterError::ReadOnly);
-        }
+    /// The service numbers that currently have [tables::Code]s available in increasing order
+    pub fn services_with_data(&self) -> impl Iterator<Item = u8> + '_ {
+        self.services
+            .iter()
+            .enumerate()
+            .filter(|(_, codes)| !codes.is_empty())
+            .map(|(service_no, _)| service_no as u8)
+    }
 
-        if code.byte_len() > self.free_space() {
-            let overflow_bytes = code.byte_len() - self.free_space();
-            debug!("pushing would overflow by {overflow_bytes} bytes");
-            return Err(WriterError::WouldOverflow(overflow_bytes));
+    /// Remove all accumulated [tables::Code]s
+    pub fn flush(&mut self) {
+        for codes in self.services.iter_mut() {
+            codes.clear();
         }
-        trace!("pushing {code:?}");
-        self.codes.push(code.clone());
-        Ok(())
     }
+}
 
-    /// Parse a [Service] from a set of bytes
-    ///
-    /// # Errors
-    ///
-    /// * [ParserError::LengthMismatch] if the length of the data is less than the size advertised in the
-    /// header
-    ///
-    /// # Examples
-    /// ```
-    /// # use cea708_types::{*, tables::*};
-    /// let bytes = [0x21, 0x41];
-    /// let service = Service::parse(&bytes).unwrap();
-    /// assert_eq!(service.number(), 1);
-    /// assert_eq!(service.codes()[0], Code::LatinCapitalA);
-    /// ```
-    pub fn parse(data: &[u8]) -> Result<Self, ParserError> {
-        if data.is_empty() {
-            return Err(ParserError::LengthMismatch {
-                expected: 1,
-                actual: 0,
-            });
-        }
-        let byte = data[0];
-        let mut service_no = (byte & 0xE0) >> 5;
-        let block_size = (byte & 0x1F) as usize;
-        let mut idx = 1;
-        trace!("block_size: {block_size}");
-        if service_no == 7 && block_size != 0 {
-            if data.len() == 1 {
-                return Err(ParserError::LengthMismatch {
-                    expected: 2,
-                    actual: data.len(),
-                });
-            }
-            let byte2 = data[1];
-            service_no = byte2 & 0x3F;
-            idx += 1;
-        }
+/// A framerate.  Framerates larger than 60fps are not well supported.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Framerate {
+    numer: u32,
+    denom: u32,
+}
 
-        if data.len() < idx + block_size {
-            return Err(ParserError::LengthMismatch {
-                expected: idx + block_size,
-                actual: data.len(),
-            });
-        }
+impl Framerate {
+    /// Create a new [`Framerate`]
+    pub const fn new(numer: u32, denom: u32) -> Self {
+        Self { numer, denom }
+    }
 
-        if service_no != 0 {
-            Ok(Self {
-                number: service_no,
-                codes: tables::Code::from_data(&data[idx..idx + block_size])?,
-            })
-        } else {
-            Ok(Self {
-                number: 0,
-                codes: vec![],
-            })
-        }
+    /// The numerator of this [`Framerate`] fraction
+    pub fn numer(&self) -> u32 {
+        self.numer
     }
 
-    /// The ordered list of [tables::Code]s present in this [Service] block
+    /// The denominator of this [`Framerate`] fraction
+    pub fn denom(&self) -> u32 {
+        self.denom
+    }
+
+    fn cea608_pairs_per_frame(&self) -> usize {
+        // CEA-608 has a max bitrate of 960 bits/s for a single field
+        // TODO: handle alternating counts for 24fps
+        60.mul_div_round(self.denom, self.numer).unwrap() as usize
+    }
+
+    fn max_cc_count(&self) -> usize {
+        // CEA-708 has a max bitrate of 9_600 bits/s
+        600.mul_div_round(self.denom, self.numer).unwrap() as usize
+    }
+}
+
+/// A struct for writing cc_data packets
+#[derive(Debug, Default)]
+pub struct CCDataWriter {
+    // settings
+    output_cea608_padding: bool,
+    output_padding: bool,
+    preserve_cea608_padding: bool,
+    max_queued_packets: Option<usize>,
+    strict_capacity: bool,
+    // state
+    packets: VecDeque<DTVCCPacket>,
+    next_sequence_no: u8,
+    // part of a packet we could not fit into the previous packet
+    pending_packet_data: Vec<u8>,
+    cea608_1: VecDeque<(u8, u8)>,
+    cea608_2: VecDeque<(u8, u8)>,
+    last_cea608_was_field1: bool,
+    // the amount of data that was left over after the previous write
+    last_backlog: usize,
+}
+
+impl CCDataWriter {
+    /// Whether to output padding CEA-608 bytes when not enough enough data has been provided
+    pub fn set_output_cea608_padding(&mut self, output_cea608_padding: bool) {
+        self.output_cea608_padding = output_cea608_padding;
+    }
+
+    /// Whether padding CEA-608 bytes will be used
+    pub fn output_cea608_padding(&self) -> bool {
+        self.output_cea608_padding
+    }
+
+    /// Whether to output padding data in the CCP bitstream when not enough data has been provided
+    pub fn set_output_padding(&mut self, output_padding: bool) {
+        self.output_padding = output_padding;
+    }
+
+    /// Whether padding data will be produced in the CCP
+    pub fn output_padding(&self) -> bool {
+        self.output_padding
+    }
+
+    /// Whether to keep CEA-608 padding (`0x80 0x80`) byte pairs passed to
+    /// [push_cea608](Self::push_cea608).  By default, padding byte pairs are discarded.
+    pub fn set_preserve_cea608_padding(&mut self, preserve_cea608_padding: bool) {
+        self.preserve_cea608_padding = preserve_cea608_padding;
+    }
+
+    /// Whether CEA-608 padding byte pairs will be kept
+    pub fn preserve_cea608_padding(&self) -> bool {
+        self.preserve_cea608_padding
+    }
+
+    /// Set the maximum number of [DTVCCPacket]s that can be queued with
+    /// [try_push_packet](Self::try_push_packet).  `None` (the default) does not limit the number
+    /// of queued [DTVCCPacket]s.
+    pub fn set_max_queued_packets(&mut self, max: Option<usize>) {
+        self.max_queued_packets = max;
+    }
+
+    /// The maximum number of [DTVCCPacket]s that can be queued
+    pub fn max_queued_packets(&self) -> Option<usize> {
+        self.max_queued_packets
+    }
+
+    /// Whether to return an error from [write](Self::write) when the queued data can never be
+    /// written or the amount of queued data keeps growing.  By default, any data that does not
+    /// fit is carried over into following cc_data packets without limit.
+    ///
+    /// The queued data is considered to be growing when more data is left over after a write
+    /// than was left over after the previous write.  A single large [DTVCCPacket] that is
+    /// written over multiple cc_data packets is not an error.
+    ///
+    /// The error is a [WriterError::WouldOverflow] wrapped in a [std::io::Error] of kind
+    /// [std::io::ErrorKind::Other] and nothing is written or removed from the queue.  The
+    /// overflow is the number of CEA-708 packet and CEA-608 bytes that the queued data would have
+    /// grown by.
     ///
     /// # Examples
     /// ```
     /// # use cea708_types::{*, tables::*};
-    /// let mut service = Service::new(1);
-    /// service.push_code(&Code::LatinCapitalA).unwrap();
-    /// let codes = service.codes();
-    /// assert_eq!(codes, [Code::LatinCapitalA]);
+    /// let framerate = Framerate::new(60, 1);
+    /// let mut writer = CCDataWriter::default();
+    /// writer.set_strict_capacity(true);
+    /// let mut written = vec![];
+    /// // 9 codes in a single service require 6 of the 10 triples available at 60fps
+    /// for i in 0..2 {
+    ///     let mut service = Service::new(1);
+    ///     for _ in 0..9 {
+    ///         service.push_code(&Code::LatinCapitalA).unwrap();
+    ///     }
+    ///     writer.push_packet_auto_seq(vec![service]).unwrap();
+    ///     let mut service = Service::new(2);
+    ///     for _ in 0..9 {
+    ///         service.push_code(&Code::LatinCapitalB).unwrap();
+    ///     }
+    ///     writer.push_packet_auto_seq(vec![service]).unwrap();
+    ///     let res = writer.write(framerate, &mut written);
+    ///     if i == 0 {
+    ///         // the first write leaves 2 triples for the next write
+    ///         res.unwrap();
+    ///     } else {
+    ///         // the next write would leave 4 triples
+    ///         let err = res.unwrap_err();
+    ///         assert_eq!(
+    ///             err.get_ref().and_then(|e| e.downcast_ref::<WriterError>()),
+    ///             Some(&WriterError::would_overflow(4, OverflowContext::Writer))
+    ///         );
+    ///     }
+    /// }
     /// ```
-    pub fn codes(&self) -> &[tables::Code] {
-        &self.codes
+    pub fn set_strict_capacity(&mut self, strict_capacity: bool) {
+        self.strict_capacity = strict_capacity;
     }
 
-    /// Write the [Service] block to a byte stream
+    /// Whether queued data that can never be written or that keeps growing is an error
+    pub fn strict_capacity(&self) -> bool {
+        self.strict_capacity
+    }
+
+    /// Push a [`DTVCCPacket`] for writing
+    pub fn push_packet(&mut self, packet: DTVCCPacket) {
+        self.next_sequence_no = (packet.sequence_no() + 1) & 0x3;
+        self.packets.push_front(packet)
+    }
+
+    /// Push a [`DTVCCPacket`] containing `services` for writing.  The sequence number of the
+    /// [`DTVCCPacket`] follows on from the previously pushed [`DTVCCPacket`], including any
+    /// pushed with [push_packet](Self::push_packet), and starts at 0.
+    ///
+    /// # Errors
+    ///
+    /// Any error from [DTVCCPacket::push_service].  Nothing is pushed in that case.
     ///
     /// # Examples
     /// ```
     /// # use cea708_types::{*, tables::*};
+    /// let mut writer = CCDataWriter::default();
+    /// writer.push_packet(DTVCCPacket::new(2));
     /// let mut service = Service::new(1);
     /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// writer.push_packet_auto_seq(vec![service]).unwrap();
     /// let mut written = vec![];
-    /// service.write(&mut written);
-    /// let expected = [0x21, 0x41];
-    /// assert_eq!(written, expected);
+    /// writer.write(Framerate::new(60, 1), &mut written).unwrap();
+    /// // the DTVCCPacket header contains sequence number 3
+    /// assert_eq!(written[3], 0xC2);
     /// ```
-    pub fn write<W: std::io::Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
-        // TODO: fail if we would overrun max size
-        let len = (self.codes_len() & 0x3F) as u8;
-        if self.number > 7 {
-            let mut buf = [0; 2];
-            buf[0] = 0xC0 | len;
-            buf[1] = self.number;
-            w.write_all(&buf)?;
-        } else {
-            let byte = (self.number & 0x7) << 5 | len;
-            w.write_all(&[byte])?;
-        }
-        for code in self.codes.iter() {
-            code.write(w)?;
+    pub fn push_packet_auto_seq(&mut self, services: Vec<Service>) -> Result<(), WriterError> {
+        let mut packet = DTVCCPacket::new(self.next_sequence_no);
+        for service in services {
+            packet.push_service(service)?;
         }
+        self.push_packet(packet);
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::tests::*;
+    /// Push a [`DTVCCPacket`] for writing unless the maximum number of queued [DTVCCPacket]s
+    /// has been reached
+    ///
+    /// # Errors
+    ///
+    /// * [WriterError::QueueFull] if [max_queued_packets](Self::max_queued_packets)
+    ///   [DTVCCPacket]s are already queued
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let mut writer = CCDataWriter::default();
+    /// writer.set_max_queued_packets(Some(1));
+    /// writer.try_push_packet(DTVCCPacket::new(0)).unwrap();
+    /// assert_eq!(
+    ///     writer.try_push_packet(DTVCCPacket::new(1)),
+    ///     Err(WriterError::QueueFull)
+    /// );
+    /// ```
+    pub fn try_push_packet(&mut self, packet: DTVCCPacket) -> Result<(), WriterError> {
+        if let Some(max) = self.max_queued_packets {
+            if self.packets.len() >= max {
+                return Err(WriterError::QueueFull);
+            }
+        }
+        self.push_packet(packet);
+        Ok(())
+    }
 
-    #[test]
-    fn simple_parse_dtvcc() {
-        test_init_log();
-        let data = [0x02, 0x01 << 5 | 0x01, 0x2A];
-        let dtvcc = DTVCCPacket::parse(&data).unwrap();
-        let services = dtvcc.services();
-        assert_eq!(services.len(), 1);
-        for service in services.iter() {
-            assert_eq!(service.number, 1);
-            let codes = service.codes();
-            for code in codes.iter() {
-                trace!("parsed {code:?}");
+    /// Push a [`Cea608`] byte pair for writing
+    pub fn push_cea608(&mut self, cea608: Cea608) {
+        match cea608 {
+            Cea608::Field1(byte0, byte1) => {
+                if self.preserve_cea608_padding || byte0 != 0x80 || byte1 != 0x80 {
+                    self.cea608_1.push_front((byte0, byte1))
+                }
+            }
+            Cea608::Field2(byte0, byte1) => {
+                if self.preserve_cea608_padding || byte0 != 0x80 || byte1 != 0x80 {
+                    self.cea608_2.push_front((byte0, byte1))
+                }
             }
         }
     }
 
-    #[test]
-    fn simple_write_dtvcc() {
-        test_init_log();
-        let mut service = Service::new(1);
-        let code = tables::Code::Asterisk;
-        service.push_code(&code).unwrap();
-        let mut dtvcc = DTVCCPacket::new(0);
-        dtvcc.push_service(service).unwrap();
-        let mut written = vec![];
-        dtvcc.write(&mut written).unwrap();
-        let data = [0x02, 0x01 << 5 | 0x01, 0x2A, 0x00];
-        assert_eq!(written, data);
+    /// Clear all stored data
+    pub fn flush(&mut self) {
+        self.packets.clear();
+        self.pending_packet_data.clear();
+        self.cea608_1.clear();
+        self.cea608_2.clear();
+        self.last_backlog = 0;
     }
 
-    #[derive(Debug)]
-    struct ServiceData<'a> {
-        service_no: u8,
-        codes: &'a [tables::Code],
+    /// The amount of time that is currently stored for CEA-608 field 1 data
+    pub fn buffered_cea608_field1_duration(&self) -> Duration {
+        // CEA-608 has a max bitrate of 60000 * 2 / 1001 bytes/s
+        Duration::from_micros(
+            (self.cea608_1.len() as u64)
+                .mul_div_ceil(1001 * 1_000_000, 60000)
+                .unwrap(),
+        )
     }
 
-    #[derive(Debug)]
-    struct PacketData<'a> {
-        sequence_no: u8,
-        services: &'a [ServiceData<'a>],
+    /// The amount of time that is currently stored for CEA-608 field 2 data
+    pub fn buffered_cea608_field2_duration(&self) -> Duration {
+        // CEA-608 has a max bitrate of 60000 * 2 / 1001 bytes/s
+        Duration::from_micros(
+            (self.cea608_2.len() as u64)
+                .mul_div_ceil(1001 * 1_000_000, 60000)
+                .unwrap(),
+        )
     }
 
-    #[derive(Debug)]
-    struct TestCCData<'a> {
-        framerate: Framerate,
-        cc_data: &'a [&'a [u8]],
-        packets: &'a [PacketData<'a>],
-        cea608: &'a [&'a [Cea608]],
+    fn buffered_packet_bytes(&self) -> usize {
+        self.pending_packet_data.len()
+            + self
+                .packets
+                .iter()
+                .map(|packet| packet.len())
+                .sum::<usize>()
     }
 
-    static TEST_CC_DATA: [TestCCData; 8] = [
-        // simple packet with a single service and single code
-        TestCCData {
-            framerate: Framerate::new(25, 1),
-            cc_data: &[&[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00]],
-            packets: &[PacketData {
-                sequence_no: 0,
-                services: &[ServiceData {
-                    service_no: 1,
-                    codes: &[tables::Code::LatinCapitalA],
-                }],
-            }],
-            cea608: &[],
-        },
-        // simple packet with a single service and two codes
-        TestCCData {
-            framerate: Framerate::new(25, 1),
-            cc_data: &[&[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x02, 0x22, 0xFE, 0x41, 0x42]],
-            packets: &[PacketData {
-                sequence_no: 0,
-                services: &[ServiceData {
-                    service_no: 1,
-                    codes: &[tables::Code::LatinCapitalA, tables::Code::LatinCapitalB],
-                }],
-            }],
-            cea608: &[],
-        },
-        // two packets each with a single service and single code
-        TestCCData {
-            framerate: Framerate::new(25, 1),
-            cc_data: &[
-                &[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00],
-                &[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x42, 0x21, 0xFE, 0x42, 0x00],
-            ],
-            packets: &[
-                PacketData {
-                    sequence_no: 0,
-                    services: &[ServiceData {
-                        service_no: 1,
-                        codes: &[tables::Code::LatinCapitalA],
-                    }],
-                },
-                PacketData {
-                    sequence_no: 1,
-                    services: &[ServiceData {
-                        service_no: 1,
-                        codes: &[tables::Code::LatinCapitalB],
-                    }],
-                },
-            ],
-            cea608: &[],
-        },
-        // two packets with a single service and one code split across both packets
-        TestCCData {
-            framerate: Framerate::new(25, 1),
-            cc_data: &[
-                &[0x80 | 0x40 | 0x01, 0xFF, 0xFF, 0x02, 0x21],
-                &[0x80 | 0x40 | 0x01, 0xFF, 0xFE, 0x41, 0x00],
-            ],
-            packets: &[PacketData {
-                sequence_no: 0,
-                services: &[ServiceData {
-                    service_no: 1,
-                    codes: &[tables::Code::LatinCapitalA],
-                }],
-            }],
-            cea608: &[],
-        },
-        // simple packet with a single null service
-        TestCCData {
-            framerate: Framerate::new(25, 1),
-            cc_data: &[&[0x80 | 0x40 | 0x01, 0xFF, 0xFF, 0x01, 0x00]],
-            packets: &[PacketData {
-                sequence_no: 0,
-                services: &[],
-            }],
-            cea608: &[],
-        },
-        // DTVCCPacket with two services
-        TestCCData {
-            framerate: Framerate::new(25, 1),
-            cc_data: &[&[
-                0x80 | 0x40 | 0x03,
-                0xFF,
-                0xFF,
-                0x03,
-                0x21,
-                0xFE,
-                0x41,
-                0x41,
-                0xFE,
-                0x42,
-                0x00,
-            ]],
-            packets: &[PacketData {
-                sequence_no: 0,
-                services: &[
-                    ServiceData {
-                        service_no: 1,
-                        codes: &[tables::Code::LatinCapitalA],
-                    },
-                    ServiceData {
-                        service_no: 2,
-                        codes: &[tables::Code::LatinCapitalB],
-                    },
-                ],
-            }],
-            cea608: &[],
-        },
-        // cc_data with two DTVCCPacket
-        TestCCData {
-            framerate: Framerate::new(25, 1),
-            cc_data: &[&[
-                0x80 | 0x40 | 0x04,
-                0xFF,
-                0xFF,
-                0x02,
-                0x21,
-                0xFE,
-                0x41,
-                0x00,
-                0xFF,
-                0x42,
-                0x41,
-                0xFE,
-                0x42,
-                0x00,
-            ]],
-            packets: &[
-                PacketData {
-                    sequence_no: 0,
-                    services: &[ServiceData {
-                        service_no: 1,
-                        codes: &[tables::Code::LatinCapitalA],
-                    }],
-                },
-                PacketData {
-                    sequence_no: 1,
-                    services: &[ServiceData {
-                        service_no: 2,
-                        codes: &[tables::Code::LatinCapitalB],
-                    }],
-                },
-            ],
-            cea608: &[],
-        },
-        // two packets with a single service and one code split across both packets with 608
-        // padding data
-        TestCCData {
-            framerate: Framerate::new(25, 1),
-            cc_data: &[
-                &[
-                    0x80 | 0x40 | 0x03,
-                    0xFF,
-                    0xFC,
-                    0x61,
-                    0x62,
-                    0xFD,
-                    0x63,
-                    0x64,
-                    0xFF,
-                    0x02,
-                    0x21,
-                ],
-                &[
-                    0x80 | 0x40 | 0x03,
-                    0xFF,
-                    0xFC,
-                    0x41,
-                    0x42,
-                    0xFD,
-                    0x43,
-                    0x44,
-                    0xFE,
-                    0x41,
-                    0x00,
-                ],
-            ],
-            packets: &[PacketData {
-                sequence_no: 0,
-                services: &[ServiceData {
-                    service_no: 1,
-                    codes: &[tables::Code::LatinCapitalA],
-                }],
-            }],
-            cea608: &[
-                &[Cea608::Field1(0x61, 0x62), Cea608::Field2(0x63, 0x64)],
-                &[Cea608::Field1(0x41, 0x42), Cea608::Field2(0x43, 0x44)],
-            ],
-        },
-    ];
+    /// The amount of time that is currently stored for CCP data
+    pub fn buffered_packet_duration(&self) -> Duration {
+        // CEA-708 has a max bitrate of 9600000 / 1001 bits/s
+        Duration::from_micros(
+            ((self.buffered_packet_bytes() + 1) as u64 / 2)
+                .mul_div_ceil(2 * 1001 * 1_000_000, 9_600_000 / 8)
+                .unwrap(),
+        )
+    }
 
-    #[test]
-    fn cc_data_parse() {
-        test_init_log();
-        for (i, test_data) in TEST_CC_DATA.iter().enumerate() {
-            log::info!("parsing {i}: {test_data:?}");
-            let mut parser = CCDataParser::new();
-            if !test_data.cea608.is_empty() {
-                parser.handle_cea608();
-            }
-            let mut expected_iter = test_data.packets.iter();
-            let mut cea608_iter = test_data.cea608.iter();
-            for data in test_data.cc_data.iter() {
-                debug!("pushing {data:?}");
-                parser.push(data).unwrap();
-                while let Some(packet) = parser.pop_packet() {
-                    let expected = expected_iter.next().unwrap();
-                    assert_eq!(expected.sequence_no, packet.sequence_no());
-                    let services = packet.services();
-                    let mut expected_service_iter = expected.services.iter();
-                    for parsed_service in services.iter() {
-                        let expected_service = expected_service_iter.next().unwrap();
-                        assert_eq!(parsed_service.number(), expected_service.service_no);
-                        assert_eq!(expected_service.codes, parsed_service.codes());
+    /// The number of cc_data packets that [write](Self::write) must produce at `framerate` to
+    /// output all of the currently queued [`DTVCCPacket`]s and CEA-608 byte pairs.
+    ///
+    /// Returns `usize::MAX` if `framerate` is too high to carry any of the queued data.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// for _ in 0..30 {
+    ///     service.push_code(&Code::LatinCapitalA).unwrap();
+    /// }
+    /// let mut packet = DTVCCPacket::new(0);
+    /// packet.push_service(service).unwrap();
+    /// let mut writer = CCDataWriter::default();
+    /// writer.push_packet(packet);
+    /// // 16 triples at 10 triples per frame
+    /// assert_eq!(writer.frames_remaining(Framerate::new(60, 1)), 2);
+    /// assert_eq!(writer.frames_remaining(Framerate::new(25, 1)), 1);
+    /// ```
+    pub fn frames_remaining(&self, framerate: Framerate) -> usize {
+        let max_cc_count = framerate.max_cc_count().min(31);
+        let pairs_per_frame = framerate.cea608_pairs_per_frame();
+        let mut backlog = self.backlog();
+
+        if backlog.is_empty() {
+            return 0;
+        }
+        if max_cc_count == 0 || (pairs_per_frame == 0 && backlog.cea608_len() > 0) {
+            return usize::MAX;
+        }
+
+        let mut frames = 0;
+        while !backlog.is_empty() {
+            frames += 1;
+            self.drain_frame(&mut backlog, pairs_per_frame, max_cc_count);
+        }
+        frames
+    }
+
+    fn backlog(&self) -> WriterBacklog {
+        WriterBacklog {
+            field1: self.cea608_1.len(),
+            field2: self.cea608_2.len(),
+            triples: self.pending_packet_data.len() / 3
+                + self.packets.iter().map(|p| p.cc_count()).sum::<usize>(),
+            last_cea608_was_field1: self.last_cea608_was_field1,
+        }
+    }
+
+    // mirrors the accounting performed by write_with_cc_count()
+    fn drain_frame(
+        &self,
+        backlog: &mut WriterBacklog,
+        pairs_per_frame: usize,
+        max_cc_count: usize,
+    ) {
+        let mut cea608_pair_rem = if self.output_cea608_padding {
+            pairs_per_frame
+        } else {
+            pairs_per_frame.min(backlog.field1.max(backlog.field2 * 2))
+        }
+        .min(max_cc_count);
+        let mut cc_count_rem = if self.output_padding {
+            max_cc_count
+        } else {
+            max_cc_count.min(cea608_pair_rem + backlog.triples)
+        };
+        while cc_count_rem > 0 {
+            if cea608_pair_rem > 0 {
+                if !backlog.last_cea608_was_field1 {
+                    if backlog.field1 > 0 {
+                        backlog.field1 -= 1;
+                        cc_count_rem -= 1;
+                    } else if backlog.field2 > 0 || self.output_cea608_padding {
+                        cc_count_rem -= 1;
                     }
-                    assert!(expected_service_iter.next().is_none());
+                    backlog.last_cea608_was_field1 = true;
+                } else {
+                    if backlog.field2 > 0 {
+                        backlog.field2 -= 1;
+                        cc_count_rem -= 1;
+                    } else if self.output_cea608_padding {
+                        cc_count_rem -= 1;
+                    }
+                    backlog.last_cea608_was_field1 = false;
                 }
-                assert_eq!(parser.cea608().as_ref(), cea608_iter.next());
+                cea608_pair_rem -= 1;
+            } else {
+                backlog.triples -= backlog.triples.min(cc_count_rem);
+                break;
             }
-            assert!(parser.pop_packet().is_none());
-            assert!(expected_iter.next().is_none());
-            assert!(cea608_iter.next().is_none());
         }
     }
 
-    static WRITE_CC_DATA: [TestCCData; 7] = [
-        // simple packet with a single service and single code
-        TestCCData {
-            framerate: Framerate::new(25, 1),
-            cc_data: &[&[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00]],
-            packets: &[PacketData {
-                sequence_no: 0,
-                services: &[ServiceData {
-                    service_no: 1,
-                    codes: &[tables::Code::LatinCapitalA],
-                }],
-            }],
-            cea608: &[],
-        },
-        // simple packet with a single service and two codes
+    /// Write the next cc_data packet taking the next relevant CEA-608 byte pairs and
+    /// [`DTVCCPacket`]s.  The framerate provided determines how many bytes are written.
+    pub fn write<W: std::io::Write>(
+        &mut self,
+        framerate: Framerate,
+        w: &mut W,
+    ) -> Result<(), std::io::Error> {
+        self.write_with_cc_count(framerate, framerate.max_cc_count(), w)
+    }
+
+    /// Write the next cc_data packet containing at most `cc_count` triples.  Any data that does
+    /// not fit is written in following cc_data packets.  The framerate provided determines how
+    /// many CEA-608 byte pairs are written.
+    ///
+    /// `cc_count` is limited to the 31 triples that can be signalled in a cc_data header.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// for _ in 0..10 {
+    ///     service.push_code(&Code::LatinCapitalA).unwrap();
+    /// }
+    /// let mut packet = DTVCCPacket::new(0);
+    /// packet.push_service(service).unwrap();
+    /// let mut writer = CCDataWriter::default();
+    /// writer.push_packet(packet);
+    ///
+    /// let mut written = vec![];
+    /// writer.write_with_cc_count(Framerate::new(25, 1), 2, &mut written).unwrap();
+    /// assert_eq!(written.len(), 2 + 2 * 3);
+    /// ```
+    pub fn write_with_cc_count<W: std::io::Write>(
+        &mut self,
+        framerate: Framerate,
+        cc_count: usize,
+        w: &mut W,
+    ) -> Result<(), std::io::Error> {
+        let max_cc_count = cc_count.min(31);
+        if self.strict_capacity {
+            self.check_capacity(framerate, max_cc_count)?;
+        }
+        let mut cea608_pair_rem = if self.output_cea608_padding {
+            framerate.cea608_pairs_per_frame()
+        } else {
+            framerate
+                .cea608_pairs_per_frame()
+                .min(self.cea608_1.len().max(self.cea608_2.len() * 2))
+        }
+        .min(max_cc_count);
+
+        let mut cc_count_rem = if self.output_padding {
+            max_cc_count
+        } else {
+            max_cc_count.min(
+                cea608_pair_rem
+                    + self.pending_packet_data.len() / 3
+                    + self.packets.iter().map(|p| p.cc_count()).sum::<usize>(),
+            )
+        };
+        trace!("writing with cc_count: {cc_count_rem} and {cea608_pair_rem} cea608 pairs");
+
+        let reserved = 0x80;
+        let process_cc_flag = 0x40;
+        w.write_all(&[
+            reserved | process_cc_flag | (cc_count_rem & 0x1f) as u8,
+            0xFF,
+        ])?;
+        while cc_count_rem > 0 {
+            if cea608_pair_rem > 0 {
+                if !self.last_cea608_was_field1 {
+                    trace!("attempting to write a cea608 byte pair from field 1");
+                    if let Some((byte0, byte1)) = self.cea608_1.pop_back() {
+                        w.write_all(&[0xFC, byte0, byte1])?;
+                        cc_count_rem -= 1;
+                    } else if !self.cea608_2.is_empty() {
+                        // need to write valid field 0 if we are going to write field 1
+                        w.write_all(&[0xFC, 0x80, 0x80])?;
+                        cc_count_rem -= 1;
+                    } else if self.output_cea608_padding {
+                        w.write_all(&[0xF8, 0x80, 0x80])?;
+                        cc_count_rem -= 1;
+                    }
+                    self.last_cea608_was_field1 = true;
+                } else {
+                    trace!("attempting to write a cea608 byte pair from field 2");
+                    if let Some((byte0, byte1)) = self.cea608_2.pop_back() {
+                        w.write_all(&[0xFD, byte0, byte1])?;
+                        cc_count_rem -= 1;
+                    } else if self.output_cea608_padding {
+                        w.write_all(&[0xF9, 0x80, 0x80])?;
+                        cc_count_rem -= 1;
+                    }
+                    self.last_cea608_was_field1 = false;
+                }
+                cea608_pair_rem -= 1;
+            } else {
+                let mut current_packet_data = &mut self.pending_packet_data;
+                let mut packet_offset = 0;
+                while packet_offset >= current_packet_data.len() {
+                    if let Some(packet) = self.packets.pop_back() {
+                        trace!("starting packet {packet:?}");
+                        packet.write_as_cc_data(&mut current_packet_data)?;
+                    } else {
+                        trace!("no packet to write");
+                        break;
+                    }
+                }
+
+                trace!("cea708 pending data length {}", current_packet_data.len(),);
+
+                while packet_offset < current_packet_data.len() && cc_count_rem > 0 {
+                    assert!(current_packet_data.len() >= packet_offset + 3);
+                    w.write_all(&current_packet_data[packet_offset..packet_offset + 3])?;
+                    packet_offset += 3;
+                    cc_count_rem -= 1;
+                }
+
+                self.pending_packet_data = current_packet_data[packet_offset..].to_vec();
+
+                if self.packets.is_empty() && self.pending_packet_data.is_empty() {
+                    // no more data to write
+                    if self.output_padding {
+                        trace!("writing {cc_count_rem} padding bytes");
+                        while cc_count_rem > 0 {
+                            w.write_all(&[0xFA, 0x00, 0x00])?;
+                            cc_count_rem -= 1;
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+        self.last_backlog = self.backlog().len();
+        Ok(())
+    }
+
+    fn check_capacity(
+        &self,
+        framerate: Framerate,
+        max_cc_count: usize,
+    ) -> Result<(), std::io::Error> {
+        let overflow = |len: usize| {
+            std::io::Error::new(
+                std::io::ErrorKind::Other,
+                // each triple or CEA-608 byte pair contains 2 bytes
+                WriterError::would_overflow(len * 2, OverflowContext::Writer),
+            )
+        };
+
+        let mut backlog = self.backlog();
+        if backlog.is_empty() {
+            return Ok(());
+        }
+        let pairs_per_frame = framerate.cea608_pairs_per_frame();
+        if max_cc_count == 0 || (pairs_per_frame == 0 && backlog.cea608_len() > 0) {
+            // nothing will ever be drained
+            return Err(overflow(backlog.len()));
+        }
+        self.drain_frame(&mut backlog, pairs_per_frame, max_cc_count);
+        if self.last_backlog > 0 && backlog.len() > self.last_backlog {
+            return Err(overflow(backlog.len() - self.last_backlog));
+        }
+        Ok(())
+    }
+
+    /// Write the next cc_data packet to an [AsyncWrite](tokio::io::AsyncWrite).  The written
+    /// bytes are identical to [write](Self::write).
+    #[cfg(feature = "tokio")]
+    pub async fn write_async<W: tokio::io::AsyncWrite + Unpin>(
+        &mut self,
+        framerate: Framerate,
+        w: &mut W,
+    ) -> Result<(), std::io::Error> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut data = Vec::with_capacity(2 + framerate.max_cc_count() * 3);
+        self.write(framerate, &mut data)?;
+        w.write_all(&data).await
+    }
+}
+
+// The amount of data queued in a CCDataWriter
+#[derive(Debug)]
+struct WriterBacklog {
+    field1: usize,
+    field2: usize,
+    triples: usize,
+    last_cea608_was_field1: bool,
+}
+
+impl WriterBacklog {
+    fn cea608_len(&self) -> usize {
+        self.field1 + self.field2
+    }
+
+    fn len(&self) -> usize {
+        self.cea608_len() + self.triples
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A packet in the `cc_data` bitstream
+///
+/// Two [DTVCCPacket]s are equal (and hash the same) if they have the same sequence number and
+/// [Service]s.  Any null padding and whether the [DTVCCPacket] was truncated are ignored.  Null
+/// padding carries no caption data and is not kept by [parse](DTVCCPacket::parse), so a
+/// [DTVCCPacket] compares equal to the result of parsing its written bytes.  As a result, equal
+/// [DTVCCPacket]s may be written with a different amount of padding.  Compare the output of
+/// [to_bytes](DTVCCPacket::to_bytes) to also compare the padding.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "serde_repr::DTVCCPacketRepr")
+)]
+pub struct DTVCCPacket {
+    seq_no: u8,
+    services: Vec<Service>,
+    null_padding: usize,
+    truncated: bool,
+}
+
+impl DTVCCPacket {
+    /// Create a new [DTVCCPacket] with the specified sequence number.
+    ///
+    /// # Panics
+    ///
+    /// * If seq_no >= 4
+    pub fn new(seq_no: u8) -> Self {
+        if seq_no > 3 {
+            panic!("DTVCCPacket sequence numbers must be between 0 and 3 inclusive, not {seq_no}");
+        }
+        Self {
+            seq_no,
+            services: vec![],
+            null_padding: 0,
+            truncated: false,
+        }
+    }
+
+    /// The sequence number of the DTVCCPacket
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// let packet = DTVCCPacket::new(2);
+    /// assert_eq!(2, packet.sequence_no());
+    /// ```
+    pub fn sequence_no(&self) -> u8 {
+        self.seq_no
+    }
+
+    /// The amount of free space (in bytes) that can by placed inside this [DTVCCPacket]
+    ///
+    /// Once any data has been added, `len() + free_space() == 128`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(2);
+    /// assert_eq!(127, packet.free_space());
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// packet.push_service(service).unwrap();
+    /// assert_eq!(125, packet.free_space());
+    /// ```
+    pub fn free_space(&self) -> usize {
+        // 128 is the max size of a DTVCCPacket, minus 1 for the header
+        if self.is_empty() {
+            127
+        } else {
+            128 - self.len()
+        }
+    }
+
+    /// The number of bytes this [DTVCCPacket] will use when written to a byte stream.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(2);
+    /// assert_eq!(0, packet.len());
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// packet.push_service(service);
+    /// assert_eq!(3, packet.len());
+    /// ```
+    pub fn len(&self) -> usize {
+        let services_len = self.services.iter().map(|s| s.len()).sum::<usize>() + self.null_padding;
+        if services_len > 0 {
+            1 + services_len
+        } else {
+            0
+        }
+    }
+
+    /// Whether this [DTVCCPacket] contains no data and would not be written.  A [DTVCCPacket]
+    /// containing only null padding from [push_null_padding](Self::push_null_padding) is not
+    /// empty.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(2);
+    /// assert!(packet.is_empty());
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// packet.push_service(service);
+    /// assert!(!packet.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Push a completed service block into this [DTVCCPacket]
+    ///
+    /// # Errors
+    ///
+    /// * [WriterError::WouldOverflow] if `service` does not fit into this [DTVCCPacket]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(2);
+    /// assert_eq!(0, packet.len());
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// packet.push_service(service);
+    /// assert_eq!(3, packet.len());
+    /// ```
+    pub fn push_service(&mut self, service: Service) -> Result<(), WriterError> {
+        if service.len() > self.free_space() {
+            return Err(WriterError::would_overflow(
+                service.len() - self.free_space(),
+                OverflowContext::Service {
+                    service_no: service.number(),
+                },
+            ));
+        }
+        self.services.push(service);
+        Ok(())
+    }
+
+    /// Append the service blocks of `other` to this [DTVCCPacket].  Codes for a service number
+    /// are added to the last service block with the same number if they fit, otherwise a new
+    /// service block is added.  The sequence number of this [DTVCCPacket] is kept.
+    ///
+    /// If the merged packet would be larger than 128 bytes, [WriterError::WouldOverflow] is
+    /// returned and neither [DTVCCPacket] is modified.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(2);
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// packet.push_service(service).unwrap();
+    /// let mut other = DTVCCPacket::new(3);
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalB).unwrap();
+    /// other.push_service(service).unwrap();
+    /// packet.merge(&other).unwrap();
+    /// assert_eq!(packet.sequence_no(), 2);
+    /// assert_eq!(packet.services().len(), 1);
+    /// assert_eq!(packet.len(), 4);
+    /// ```
+    pub fn merge(&mut self, other: &DTVCCPacket) -> Result<(), WriterError> {
+        let mut merged = self.clone();
+        for service in other.services.iter() {
+            if let Some(existing) = merged
+                .services
+                .iter_mut()
+                .rev()
+                .find(|existing| existing.number == service.number)
+            {
+                if existing.codes_len() + service.codes_len() <= 31 {
+                    existing.codes.extend(service.codes.iter().cloned());
+                    continue;
+                }
+            }
+            merged.services.push(service.clone());
+        }
+        merged.null_padding += other.null_padding;
+
+        let len = merged.len();
+        if len > 128 {
+            return Err(WriterError::would_overflow(
+                len - 128,
+                OverflowContext::Packet,
+            ));
+        }
+        *self = merged;
+        Ok(())
+    }
+
+    /// Add a number of null service block headers to the end of this [DTVCCPacket].  This can be
+    /// used to pad a [DTVCCPacket] to a desired size.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(2);
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// packet.push_service(service).unwrap();
+    /// packet.push_null_padding(3).unwrap();
+    /// assert_eq!(6, packet.len());
+    /// assert_eq!(packet.to_bytes(), [0x83, 0x21, 0x41, 0x00, 0x00, 0x00]);
+    /// ```
+    pub fn push_null_padding(&mut self, bytes: usize) -> Result<(), WriterError> {
+        let free_space = self.free_space();
+        if bytes > free_space {
+            return Err(WriterError::would_overflow(
+                bytes - free_space,
+                OverflowContext::Packet,
+            ));
+        }
+        self.null_padding += bytes;
+        Ok(())
+    }
+
+    fn parse_hdr_byte(byte: u8) -> (u8, usize) {
+        let seq_no = (byte & 0xC0) >> 6;
+        let len = byte & 0x3F;
+        let len = if len == 0 {
+            127usize
+        } else {
+            ((len as usize) * 2) - 1
+        };
+        (seq_no, len)
+    }
+
+    /// Parse bytes into a [DTVCCPacket]
+    ///
+    /// Will return [ParserError::LengthMismatch] if the data is longer than the length advertised
+    /// in the [DTVCCPacket] header.
+    ///
+    /// If the data is shorter than the length advertised in the [DTVCCPacket] header, all the
+    /// complete [tables::Code]s are parsed and [was_truncated](Self::was_truncated) will return
+    /// `true`.  This allows parsing the incomplete data of a stream.  Use
+    /// [parse_exact](Self::parse_exact) or [parse_minimum](Self::parse_minimum) to reject
+    /// truncated data.
+    ///
+    /// Will return errors from [Service::parse] if parsing the contained [Service]s fails.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let data = [0x02, 0x21, 0x41, 0x00];
+    /// let packet = DTVCCPacket::parse(&data).unwrap();
+    /// assert_eq!(3, packet.len());
+    /// assert_eq!(0, packet.sequence_no());
+    /// assert!(!packet.was_truncated());
+    /// ```
+    pub fn parse(data: &[u8]) -> Result<Self, ParserError> {
+        let packet = Self::parse_ref(data)?;
+        let (_seq_no, len) = Self::parse_hdr_byte(data[0]);
+        let truncated = data.len() < len + 1;
+        if truncated {
+            debug!(
+                "DTVCCPacket truncated, advertised {} bytes, have {} bytes",
+                len + 1,
+                data.len()
+            );
+        }
+        let mut services = vec![];
+        let mut iter = packet.services();
+        loop {
+            let remaining = iter.data;
+            let Some(service) = iter.next() else {
+                break;
+            };
+            match service {
+                Ok(service) => {
+                    let service = service.to_service()?;
+                    trace!("parsed service {service:?}, len:{}", service.len());
+                    services.push(service);
+                }
+                Err(ParserError::LengthMismatch { .. }) if truncated => {
+                    if let Some(service) = Service::parse_partial(remaining) {
+                        trace!("parsed partial service {service:?}, len:{}", service.len());
+                        services.push(service);
+                    }
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Self {
+            seq_no: packet.sequence_no(),
+            services,
+            null_padding: 0,
+            truncated,
+        })
+    }
+
+    /// Parse bytes that must contain exactly one complete [DTVCCPacket]
+    ///
+    /// Unlike [parse](Self::parse), [ParserError::LengthMismatch] is also returned if the data
+    /// is shorter than the length advertised in the [DTVCCPacket] header.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let packet = DTVCCPacket::parse_exact(&[0x02, 0x21, 0x41, 0x00]).unwrap();
+    /// assert_eq!(packet.services()[0].codes(), [Code::LatinCapitalA]);
+    /// assert_eq!(
+    ///     DTVCCPacket::parse_exact(&[0x02, 0x21, 0x41]),
+    ///     Err(ParserError::LengthMismatch { expected: 4, actual: 3 })
+    /// );
+    /// ```
+    pub fn parse_exact(data: &[u8]) -> Result<Self, ParserError> {
+        let Some(&hdr) = data.first() else {
+            return Err(ParserError::LengthMismatch {
+                expected: 1,
+                actual: 0,
+            });
+        };
+        let (_seq_no, len) = Self::parse_hdr_byte(hdr);
+        if data.len() != len + 1 {
+            return Err(ParserError::LengthMismatch {
+                expected: len + 1,
+                actual: data.len(),
+            });
+        }
+        Self::parse(data)
+    }
+
+    /// Parse a complete [DTVCCPacket] from the start of `data`.  Any data after the length
+    /// advertised in the [DTVCCPacket] header is ignored.
+    ///
+    /// Returns [ParserError::LengthMismatch] if the data is shorter than the length advertised
+    /// in the [DTVCCPacket] header.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let packet = DTVCCPacket::parse_minimum(&[0x02, 0x21, 0x41, 0x00, 0xFF]).unwrap();
+    /// assert_eq!(packet.len(), 3);
+    /// assert_eq!(
+    ///     DTVCCPacket::parse_minimum(&[0x02, 0x21, 0x41]),
+    ///     Err(ParserError::LengthMismatch { expected: 4, actual: 3 })
+    /// );
+    /// ```
+    pub fn parse_minimum(data: &[u8]) -> Result<Self, ParserError> {
+        let Some(&hdr) = data.first() else {
+            return Err(ParserError::LengthMismatch {
+                expected: 1,
+                actual: 0,
+            });
+        };
+        let (_seq_no, len) = Self::parse_hdr_byte(hdr);
+        if data.len() < len + 1 {
+            return Err(ParserError::LengthMismatch {
+                expected: len + 1,
+                actual: data.len(),
+            });
+        }
+        Self::parse(&data[..len + 1])
+    }
+
+    /// The first reserved C1 byte (0x93-0x96) in this [DTVCCPacket] and its position
+    fn find_reserved_byte(&self) -> Option<(u8, usize)> {
+        // skip the packet header
+        let mut position = 1;
+        for service in self.services.iter() {
+            position += service.header_len();
+            for code in service.codes.iter() {
+                if let tables::Code::Unknown(data) = code {
+                    if let Some(&byte @ 0x93..=0x96) = data.first() {
+                        return Some((byte, position));
+                    }
+                }
+                position += code.byte_len();
+            }
+        }
+        None
+    }
+
+    /// Whether this [DTVCCPacket] was parsed from data that was shorter than the length
+    /// advertised in the header.  A truncated [DTVCCPacket] only contains the [tables::Code]s
+    /// that were completely available.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// // advertises 3 bytes of service data but only 2 are available
+    /// let data = [0x02, 0x22, 0x41];
+    /// let packet = DTVCCPacket::parse(&data).unwrap();
+    /// assert!(packet.was_truncated());
+    /// assert_eq!(packet.services()[0].codes(), [Code::LatinCapitalA]);
+    /// ```
+    pub fn was_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Parse bytes into a [DTVCCPacketRef] without parsing or allocating any of the contained
+    /// [Service]s.
+    ///
+    /// Will return [ParserError::LengthMismatch] if the data is shorter than the length advertised in
+    /// the [DTVCCPacket] header.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let data = [0x02, 0x21, 0x41, 0x00];
+    /// let packet = DTVCCPacket::parse_ref(&data).unwrap();
+    /// assert_eq!(0, packet.sequence_no());
+    /// let service = packet.services().next().unwrap().unwrap();
+    /// assert_eq!(1, service.number());
+    /// ```
+    pub fn parse_ref(data: &[u8]) -> Result<DTVCCPacketRef<'_>, ParserError> {
+        if data.is_empty() {
+            return Err(ParserError::LengthMismatch {
+                expected: 1,
+                actual: 0,
+            });
+        }
+        let (seq_no, len) = Self::parse_hdr_byte(data[0]);
+        trace!(
+            "dtvcc seq:{seq_no} len {len} data {data_len}",
+            data_len = data.len()
+        );
+        if (len + 1) < data.len() {
+            return Err(ParserError::LengthMismatch {
+                expected: len + 1,
+                actual: data.len(),
+            });
+        }
+
+        Ok(DTVCCPacketRef {
+            seq_no,
+            data: &data[1..],
+        })
+    }
+
+    /// The [Service]s for this [DTVCCPacket]
+    pub fn services(&self) -> &[Service] {
+        &self.services
+    }
+
+    /// The windows referenced by any of the [Service]s in this [DTVCCPacket] as returned by
+    /// [Service::referenced_windows]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(0);
+    /// packet.push_service(Service::from_codes(1, [Code::SetCurrentWindow1]).unwrap()).unwrap();
+    /// packet.push_service(Service::from_codes(2, [Code::SetCurrentWindow4]).unwrap()).unwrap();
+    /// assert_eq!(packet.referenced_windows(), WindowBits::ONE | WindowBits::FOUR);
+    /// ```
+    pub fn referenced_windows(&self) -> tables::WindowBits {
+        self.services
+            .iter()
+            .fold(tables::WindowBits::NONE, |bits, service| {
+                bits | service.referenced_windows()
+            })
+    }
+
+    /// Whether this [DTVCCPacket] contains the same [Service] blocks as `other`.  The sequence
+    /// number and any padding are ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let service = Service::from_codes(1, [Code::LatinCapitalA]).unwrap();
+    /// let mut packet = DTVCCPacket::new(0);
+    /// packet.push_service(service.clone()).unwrap();
+    /// let mut other = DTVCCPacket::new(1);
+    /// other.push_service(service).unwrap();
+    /// assert!(packet.semantic_eq(&other));
+    /// assert_ne!(packet, other);
+    /// ```
+    pub fn semantic_eq(&self, other: &Self) -> bool {
+        self.services == other.services
+    }
+
+    /// A hash of the [Service] blocks in this [DTVCCPacket].  Packets that are
+    /// [semantic_eq](Self::semantic_eq) have the same hash.
+    ///
+    /// The hash is only intended for detecting repeated packets within a single process.  It is
+    /// not cryptographically meaningful and may change between versions of this crate or Rust.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let service = Service::from_codes(1, [Code::LatinCapitalA]).unwrap();
+    /// let mut packet = DTVCCPacket::new(0);
+    /// packet.push_service(service.clone()).unwrap();
+    /// let mut other = DTVCCPacket::new(1);
+    /// other.push_service(service).unwrap();
+    /// assert_eq!(packet.payload_hash(), other.payload_hash());
+    /// ```
+    pub fn payload_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for service in self.services.iter() {
+            service.to_bytes().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// The text of all the [Service] blocks with number `service_no` in this [DTVCCPacket] as
+    /// returned by [Service::text].  Returns `None` if there is no [Service] block with the
+    /// provided number.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(0);
+    /// let mut service = Service::new(1);
+    /// service.push_code(Code::LatinCapitalA).unwrap();
+    /// packet.push_service(service).unwrap();
+    /// assert_eq!(packet.service_text(1).as_deref(), Some("A"));
+    /// assert_eq!(packet.service_text(2), None);
+    /// ```
+    pub fn service_text(&self, service_no: u8) -> Option<String> {
+        let mut ret: Option<String> = None;
+        for service in self.services.iter() {
+            if service.number() == service_no {
+                ret.get_or_insert_with(String::new)
+                    .push_str(&service.text());
+            }
+        }
+        ret
+    }
+
+    /// The text of all the [Service] blocks in this [DTVCCPacket] as returned by [Service::text]
+    /// grouped by service number.  Service numbers are returned in the order they first appear
+    /// in this [DTVCCPacket].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(0);
+    /// let mut service = Service::new(2);
+    /// service.push_code(Code::LatinCapitalA).unwrap();
+    /// packet.push_service(service).unwrap();
+    /// let mut service = Service::new(1);
+    /// service.push_code(Code::LatinCapitalB).unwrap();
+    /// packet.push_service(service).unwrap();
+    /// assert_eq!(
+    ///     packet.service_texts(),
+    ///     [(2, "A".to_string()), (1, "B".to_string())]
+    /// );
+    /// ```
+    pub fn service_texts(&self) -> Vec<(u8, String)> {
+        let mut ret: Vec<(u8, String)> = vec![];
+        for service in self.services.iter() {
+            let text = service.text();
+            if let Some((_, existing)) = ret.iter_mut().find(|(no, _)| *no == service.number()) {
+                existing.push_str(&text);
+            } else {
+                ret.push((service.number(), text));
+            }
+        }
+        ret
+    }
+
+    /// Compute statistics over all the [Service]s in this [DTVCCPacket]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(0);
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// packet.push_service(service).unwrap();
+    /// let mut service = Service::new(2);
+    /// service.push_code(&Code::CR).unwrap();
+    /// packet.push_service(service).unwrap();
+    /// let stats = packet.statistics();
+    /// assert_eq!(stats.service_count, 2);
+    /// assert_eq!(stats.services.character_count, 1);
+    /// assert_eq!(stats.services.control_code_count, 1);
+    /// ```
+    pub fn statistics(&self) -> PacketStats {
+        let mut ret = PacketStats {
+            service_count: self.services.len(),
+            ..Default::default()
+        };
+        for service in self.services.iter() {
+            ret.services.add(&service.statistics());
+        }
+        ret
+    }
+
+    fn cc_count(&self) -> usize {
+        (self.len() + 1) / 2
+    }
+
+    fn hdr_byte(&self) -> u8 {
+        let packet_size_code = if self.len() >= 127 {
+            0
+        } else {
+            (self.len() + 1) / 2
+        };
+        (self.seq_no & 0x3) << 6 | packet_size_code as u8
+    }
+
+    /// Write the [DTVCCPacket] to a byte stream
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(2);
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// packet.push_service(service);
+    /// let mut written = vec![];
+    /// packet.write(&mut written);
+    /// let expected = [0x82, 0x21, 0x41, 0x00];
+    /// assert_eq!(written, expected);
+    /// ```
+    pub fn write<W: std::io::Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
+        // TODO: fail if we would overrun max size
+        w.write_all(&[self.hdr_byte()])?;
+        for service in self.services.iter() {
+            service.write(w)?;
+        }
+        if self.null_padding > 0 {
+            w.write_all(&vec![0x00; self.null_padding])?;
+        }
+        if self.len() % 2 == 1 {
+            w.write_all(&[0x00])?;
+        }
+        Ok(())
+    }
+
+    /// Write the [DTVCCPacket] into a new `Vec<u8>`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [DTVCCPacket] is larger than 128 bytes which cannot occur when it is
+    /// constructed using [push_service](Self::push_service).
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut packet = DTVCCPacket::new(2);
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// packet.push_service(service).unwrap();
+    /// assert_eq!(packet.to_bytes(), [0x82, 0x21, 0x41, 0x00]);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let len = self.len();
+        assert!(len <= 128, "DTVCCPacket length {len} is larger than 128");
+        let mut ret = Vec::with_capacity(len + 2);
+        self.write(&mut ret)
+            .expect("Writing to a Vec<u8> cannot fail");
+        ret
+    }
+
+    fn write_as_cc_data<W: std::io::Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
+        // TODO: fail if we would overrun max size
+        // TODO: handle framerate?
+        if self.is_empty() {
+            return Ok(());
+        }
+        let mut written = vec![];
+        for service in self.services.iter() {
+            service.write(&mut written)?;
+            trace!("wrote service {service:?}");
+        }
+        written.resize(written.len() + self.null_padding, 0x00);
+        w.write_all(&[0xFF, self.hdr_byte(), written[0]])?;
+        for pair in written[1..].chunks(2) {
+            let cc_valid = 0x04;
+            let cc_type = 0b10;
+            let reserved = 0xF8;
+            w.write_all(&[reserved | cc_valid | cc_type])?;
+            w.write_all(pair)?;
+            if pair.len() == 1 {
+                w.write_all(&[0x00])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes a multi-line summary of the [DTVCCPacket] and each contained [Service].  The first line
+/// contains the sequence number followed by each [Service] as described in the [Display]
+/// implementation of [Service] indented by two spaces.
+///
+/// The alternate form (`{:#}`) additionally includes the bytes of each [tables::Code].
+///
+/// [Display]: std::fmt::Display
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, tables::*};
+/// let mut service = Service::new(1);
+/// service.push_code(&Code::LatinCapitalA).unwrap();
+/// service.push_code(&Code::CR).unwrap();
+/// let mut packet = DTVCCPacket::new(2);
+/// packet.push_service(service).unwrap();
+/// assert_eq!(
+///     packet.to_string(),
+///     "DTVCCPacket seq:2 len:4\n  Service 1 len:3\n    'A'\n    Carriage Return\n"
+/// );
+/// assert_eq!(
+///     format!("{packet:#}"),
+///     "DTVCCPacket seq:2 len:4\n  Service 1 len:3\n    'A' [41]\n    Carriage Return [0d]\n"
+/// );
+/// ```
+impl std::fmt::Display for DTVCCPacket {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "DTVCCPacket seq:{} len:{}", self.seq_no, self.len())?;
+        for service in self.services.iter() {
+            service.fmt_indented(f, 2)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a [DTVCCPacket] using [DTVCCPacket::to_bytes]
+impl From<&DTVCCPacket> for Vec<u8> {
+    fn from(packet: &DTVCCPacket) -> Self {
+        packet.to_bytes()
+    }
+}
+
+/// Parses a [DTVCCPacket] using [DTVCCPacket::parse]
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, tables::*};
+/// # fn main() -> Result<(), ParserError> {
+/// let bytes = vec![0x02, 0x21, 0x41, 0x00];
+/// let packet: DTVCCPacket = bytes.as_slice().try_into()?;
+/// assert_eq!(packet.services()[0].codes(), [Code::LatinCapitalA]);
+/// # Ok(())
+/// # }
+/// ```
+impl TryFrom<&[u8]> for DTVCCPacket {
+    type Error = ParserError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        DTVCCPacket::parse(data)
+    }
+}
+
+// null_padding and truncated are deliberately not compared, see the DTVCCPacket documentation
+impl PartialEq for DTVCCPacket {
+    fn eq(&self, other: &Self) -> bool {
+        self.seq_no == other.seq_no && self.services == other.services
+    }
+}
+
+impl Eq for DTVCCPacket {}
+
+impl std::hash::Hash for DTVCCPacket {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.seq_no.hash(state);
+        self.services.hash(state);
+    }
+}
+
+impl IntoIterator for DTVCCPacket {
+    type Item = Service;
+    type IntoIter = std::vec::IntoIter<Service>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.services.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a DTVCCPacket {
+    type Item = &'a Service;
+    type IntoIter = std::slice::Iter<'a, Service>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.services.iter()
+    }
+}
+
+/// Statistics about the [tables::Code]s in a [Service]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ServiceStats {
+    /// The number of [tables::Code]s that have a utf8 character
+    pub character_count: usize,
+    /// The number of [tables::Code]s that are commands
+    pub control_code_count: usize,
+    /// The number of bytes used by all the [tables::Code]s
+    pub byte_count: usize,
+    /// The number of [tables::Code]s in each [tables::CodeCategory] indexed by
+    /// [tables::CodeCategory::index]
+    pub category_counts: [usize; tables::CodeCategory::COUNT],
+}
+
+impl ServiceStats {
+    fn add(&mut self, other: &ServiceStats) {
+        self.character_count += other.character_count;
+        self.control_code_count += other.control_code_count;
+        self.byte_count += other.byte_count;
+        for (count, other) in self
+            .category_counts
+            .iter_mut()
+            .zip(other.category_counts.iter())
+        {
+            *count += other;
+        }
+    }
+}
+
+/// Statistics about the [Service]s in a [DTVCCPacket]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PacketStats {
+    /// The number of [Service]s
+    pub service_count: usize,
+    /// The combined statistics of all [Service]s
+    pub services: ServiceStats,
+}
+
+/// How severe a [ValidationIssue] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValidationSeverity {
+    /// The [Service] may not be presented as intended
+    Warning,
+    /// The [Service] contains a command with invalid arguments
+    Error,
+}
+
+/// The kind of problem found by [Service::validate]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationIssueKind {
+    /// A [tables::Code::SetPenLocation] is outside of the most recently defined window
+    PenLocationOutOfBounds {
+        /// The requested row
+        row: u8,
+        /// The requested column
+        column: u8,
+        /// The number of rows in the window
+        row_count: u16,
+        /// The number of columns in the window
+        column_count: u16,
+    },
+    /// A [tables::Code::SetWindowAttributes] has an effect speed of 0
+    ZeroEffectSpeed,
+    /// A SetCurrentWindow command refers to a window that is not defined earlier in the same
+    /// [Service] block
+    UndefinedWindow {
+        /// The window identifier
+        window_id: u8,
+    },
+    /// A [tables::Code::DefineWindow] has a window or pen style identifier larger than 7
+    InvalidStyleId {
+        /// The style identifier
+        style_id: u8,
+    },
+}
+
+/// A problem found by [Service::validate]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValidationIssue {
+    /// The index of the offending [tables::Code] in [Service::codes]
+    pub code_index: usize,
+    /// How severe the issue is
+    pub severity: ValidationSeverity,
+    /// The kind of issue
+    pub kind: ValidationIssueKind,
+}
+
+/// A [Service] in a [DTVCCPacket]
+///
+/// As specified in CEA-708, there can be a maximum of 63 services.  Service 1 is the primary
+/// caption service and Service 2 is the secondary caption service.  All other services are
+/// undefined.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "serde_repr::ServiceRepr")
+)]
+pub struct Service {
+    number: u8,
+    codes: Vec<tables::Code>,
+}
+
+impl Service {
+    /// Create a new [Service]
+    ///
+    /// # Panics
+    ///
+    /// * if number >= 64
+    pub fn new(service_no: u8) -> Self {
+        Self::with_capacity(service_no, 0)
+    }
+
+    /// Create a new [Service] returning an error instead of panicking
+    ///
+    /// # Errors
+    ///
+    /// * [WriterError::InvalidServiceNumber] if number >= 64
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// assert_eq!(Service::try_new(1).unwrap().number(), 1);
+    /// assert_eq!(
+    ///     Service::try_new(64),
+    ///     Err(WriterError::InvalidServiceNumber { number: 64 })
+    /// );
+    /// ```
+    pub fn try_new(service_no: u8) -> Result<Self, WriterError> {
+        if service_no >= 64 {
+            return Err(WriterError::InvalidServiceNumber { number: service_no });
+        }
+        Ok(Self::new(service_no))
+    }
+
+    /// Create a new [Service] with space for at least `codes` [tables::Code]s without
+    /// reallocating
+    ///
+    /// # Panics
+    ///
+    /// * if number >= 64
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::with_capacity(1, 2);
+    /// assert_eq!(service.number(), 1);
+    /// assert!(service.capacity() >= 2);
+    /// ```
+    pub fn with_capacity(service_no: u8, codes: usize) -> Self {
+        if service_no >= 64 {
+            panic!("Service numbers must be between 0 and 63 inclusive, not {service_no}");
+        }
+        Self {
+            number: service_no,
+            codes: Vec::with_capacity(codes),
+        }
+    }
+
+    /// Reserve space for at least `additional` more [tables::Code]s
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.reserve(4);
+    /// assert!(service.capacity() >= 4);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.codes.reserve(additional)
+    }
+
+    /// The number of [tables::Code]s this [Service] can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        self.codes.capacity()
+    }
+
+    /// Returns the number of this [Service]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// assert_eq!(service.number(), 1);
+    /// ```
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    /// The length in bytes of the [tables::Code]s in this [Service] block without the service
+    /// header
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(7);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// assert_eq!(service.codes_len(), 1);
+    /// assert_eq!(service.len(), 3);
+    /// ```
+    pub fn codes_len(&self) -> usize {
+        self.codes.iter().map(|c| c.byte_len()).sum()
+    }
+
+    /// Whether `code` can be pushed into this [Service] block without overflowing
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// assert!(service.would_fit(&Code::LatinCapitalA));
+    /// ```
+    pub fn would_fit(&self, code: &tables::Code) -> bool {
+        code.byte_len() <= self.free_space()
+    }
+
+    /// The number of copies of `code` that can still be pushed into this [Service] block
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// assert_eq!(service.remaining_code_capacity_for(&Code::LatinCapitalA), 31);
+    /// assert_eq!(service.remaining_code_capacity_for(&Code::HideWindows(WindowBits::ZERO)), 15);
+    /// ```
+    pub fn remaining_code_capacity_for(&self, code: &tables::Code) -> usize {
+        let code_len = code.byte_len();
+        if code_len == 0 {
+            return 0;
+        }
+        self.free_space() / code_len
+    }
+
+    /// The amount of free space (in bytes) that can by placed inside this [Service] block
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let service = Service::new(1);
+    /// assert_eq!(service.free_space(), 31);
+    /// ```
+    pub fn free_space(&self) -> usize {
+        // 31 is the maximum size of a service block
+        31 - self.codes_len()
+    }
+
+    /// The length in bytes of this [Service] block
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// assert_eq!(service.len(), 0);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// assert_eq!(service.len(), 2);
+    /// service.push_code(&Code::LatinCapitalB).unwrap();
+    /// assert_eq!(service.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.header_len() + self.codes_len()
+    }
+
+    /// Whether this [Service] block is written with the extended (two byte) header.
+    ///
+    /// The service number 7 in the standard header signals that an extended header follows
+    /// and so service numbers 7 and above are always written with an extended header.  When
+    /// parsing, a standard header containing service number 7 and a non-zero block size is
+    /// always followed by an extended header.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::*;
+    /// assert!(!Service::new(6).is_extended());
+    /// assert!(Service::new(7).is_extended());
+    /// ```
+    pub fn is_extended(&self) -> bool {
+        self.number >= 7
+    }
+
+    /// The length in bytes of the header of this [Service] block.  0 if the block is empty or
+    /// the NULL Service (number 0) as nothing is written.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(10);
+    /// assert_eq!(service.header_len(), 0);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// assert_eq!(service.header_len(), 2);
+    /// ```
+    pub fn header_len(&self) -> usize {
+        if self.number == 0 || self.codes.is_empty() {
+            0
+        } else if self.is_extended() {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Whether this [Service] block contains no data and would not be written
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// assert!(service.is_empty());
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// assert!(!service.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Push a [tables::Code] to the end of this [Service]
+    ///
+    /// Anything that can be converted into a [tables::Code] can be pushed, including references to
+    /// a [tables::Code] and the command argument structs.
+    ///
+    /// # Errors
+    ///
+    /// * [WriterError::ReadOnly] if [Service] is number 0 (called the NULL Service)
+    /// * [WriterError::WouldOverflow] if adding the [tables::Code] would cause to [Service] to overflow
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// service.push_code(Code::LatinCapitalB).unwrap();
+    /// service.push_code(SetPenLocationArgs::new(1, 2)).unwrap();
+    /// assert_eq!(
+    ///     service.codes(),
+    ///     [
+    ///         Code::LatinCapitalA,
+    ///         Code::LatinCapitalB,
+    ///         Code::SetPenLocation(SetPenLocationArgs::new(1, 2))
+    ///     ]
+    /// );
+    /// ```
+    pub fn push_code(&mut self, code: impl Into<tables::Code>) -> Result<(), WriterError> {
+        // TODO: errors?
+        if self.number == 0 {
+            return Err(WriterError::ReadOnly);
+        }
+
+        let code = code.into();
+        if code.byte_len() > self.free_space() {
+            let overflow_bytes = code.byte_len() - self.free_space();
+            debug!("pushing would overflow by {overflow_bytes} bytes");
+            return Err(WriterError::would_overflow(
+                overflow_bytes,
+                OverflowContext::Service {
+                    service_no: self.number,
+                },
+            ));
+        }
+        trace!("pushing {code:?}");
+        self.codes.push(code);
+        Ok(())
+    }
+
+    /// Push as many whole [tables::Code]s from `codes` as fit into this [Service] and return the
+    /// [tables::Code]s that did not fit.
+    ///
+    /// Nothing is pushed into the NULL Service (number 0).
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let codes = vec![Code::LatinCapitalA; 40];
+    /// let mut service = Service::new(1);
+    /// let remaining = service.push_all(&codes);
+    /// assert_eq!(service.codes().len(), 31);
+    /// assert_eq!(remaining.len(), 9);
+    /// ```
+    pub fn push_all<'a>(&mut self, codes: &'a [tables::Code]) -> &'a [tables::Code] {
+        if self.number == 0 {
+            return codes;
+        }
+        let mut free_space = self.free_space();
+        let n_fit = codes
+            .iter()
+            .take_while(|code| {
+                let fits = code.byte_len() <= free_space;
+                if fits {
+                    free_space -= code.byte_len();
+                }
+                fits
+            })
+            .count();
+        trace!("pushing {n_fit} of {} codes", codes.len());
+        self.codes.extend_from_slice(&codes[..n_fit]);
+        &codes[n_fit..]
+    }
+
+    /// Create a new [Service] containing `codes`
+    ///
+    /// # Errors
+    ///
+    /// * [WriterError::ReadOnly] if [Service] is number 0 (called the NULL Service)
+    /// * [WriterError::WouldOverflow] if the [tables::Code]s do not fit in a single [Service]
+    ///
+    /// # Panics
+    ///
+    /// * if number >= 64
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let service = Service::from_codes(2, [Code::LatinCapitalA, Code::LatinCapitalB]).unwrap();
+    /// assert_eq!(service.number(), 2);
+    /// assert_eq!(service.codes(), [Code::LatinCapitalA, Code::LatinCapitalB]);
+    /// ```
+    pub fn from_codes<I: IntoIterator<Item = tables::Code>>(
+        service_no: u8,
+        codes: I,
+    ) -> Result<Self, WriterError> {
+        let mut service = Self::new(service_no);
+        for code in codes {
+            service.push_code(code)?;
+        }
+        Ok(service)
+    }
+
+    /// Remove and return the last [tables::Code] in this [Service]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// assert_eq!(service.pop_code(), Some(Code::LatinCapitalA));
+    /// assert_eq!(service.pop_code(), None);
+    /// ```
+    pub fn pop_code(&mut self) -> Option<tables::Code> {
+        self.codes.pop()
+    }
+
+    /// Remove and return the [tables::Code] at `index` in this [Service].  Returns `None` if
+    /// `index` is out of bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// service.push_code(&Code::LatinCapitalB).unwrap();
+    /// assert_eq!(service.remove_code(0), Some(Code::LatinCapitalA));
+    /// assert_eq!(service.remove_code(1), None);
+    /// assert_eq!(service.codes(), [Code::LatinCapitalB]);
+    /// ```
+    pub fn remove_code(&mut self, index: usize) -> Option<tables::Code> {
+        if index < self.codes.len() {
+            Some(self.codes.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Shorten this [Service] to contain only the first `n` [tables::Code]s
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// service.push_code(&Code::LatinCapitalB).unwrap();
+    /// service.truncate(1);
+    /// assert_eq!(service.codes(), [Code::LatinCapitalA]);
+    /// ```
+    pub fn truncate(&mut self, n: usize) {
+        self.codes.truncate(n)
+    }
+
+    /// Remove all [tables::Code]s from this [Service]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// service.clear();
+    /// assert!(service.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.codes.clear()
+    }
+
+    /// Parse a [Service] from a set of bytes
+    ///
+    /// # Errors
+    ///
+    /// * [ParserError::LengthMismatch] if the length of the data is less than the size advertised in the
+    ///   header
+    /// * [ParserError::InvalidServiceNumber] if an extended service block header contains a
+    ///   service number less than 7
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let bytes = [0x21, 0x41];
+    /// let service = Service::parse(&bytes).unwrap();
+    /// assert_eq!(service.number(), 1);
+    /// assert_eq!(service.codes()[0], Code::LatinCapitalA);
+    /// ```
+    pub fn parse(data: &[u8]) -> Result<Self, ParserError> {
+        Self::parse_ref(data)?.to_service()
+    }
+
+    /// Parse a [ServiceRef] from a set of bytes without parsing the contained [tables::Code]s
+    ///
+    /// # Errors
+    ///
+    /// * [ParserError::LengthMismatch] if the length of the data is less than the size advertised in the
+    ///   header
+    /// * [ParserError::InvalidServiceNumber] if an extended service block header contains a
+    ///   service number less than 7
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let bytes = [0x21, 0x41];
+    /// let service = Service::parse_ref(&bytes).unwrap();
+    /// assert_eq!(service.number(), 1);
+    /// assert_eq!(service.code_bytes(), [0x41]);
+    /// assert_eq!(service.codes().next(), Some(Ok(Code::LatinCapitalA)));
+    /// ```
+    pub fn parse_ref(data: &[u8]) -> Result<ServiceRef<'_>, ParserError> {
+        if data.is_empty() {
+            return Err(ParserError::LengthMismatch {
+                expected: 1,
+                actual: 0,
+            });
+        }
+        let byte = data[0];
+        let mut service_no = (byte & 0xE0) >> 5;
+        let block_size = (byte & 0x1F) as usize;
+        let mut idx = 1;
+        trace!("block_size: {block_size}");
+        if service_no == 7 && block_size != 0 {
+            if data.len() == 1 {
+                return Err(ParserError::LengthMismatch {
+                    expected: 2,
+                    actual: data.len(),
+                });
+            }
+            let byte2 = data[1];
+            service_no = byte2 & 0x3F;
+            idx += 1;
+            if service_no < 7 {
+                return Err(ParserError::InvalidServiceNumber { number: service_no });
+            }
+        }
+
+        if data.len() < idx + block_size {
+            return Err(ParserError::LengthMismatch {
+                expected: idx + block_size,
+                actual: data.len(),
+            });
+        }
+
+        Ok(ServiceRef {
+            number: service_no,
+            hdr_len: idx,
+            data: &data[idx..idx + block_size],
+        })
+    }
+
+    /// Parse the complete [tables::Code]s of a service block that is cut short
+    fn parse_partial(data: &[u8]) -> Option<Service> {
+        let byte = *data.first()?;
+        let mut service_no = (byte & 0xE0) >> 5;
+        let block_size = (byte & 0x1F) as usize;
+        let mut idx = 1;
+        if service_no == 7 && block_size != 0 {
+            service_no = data.get(1)? & 0x3F;
+            idx += 1;
+            if service_no < 7 {
+                return None;
+            }
+        }
+        if service_no == 0 {
+            return None;
+        }
+        let data = &data[idx..data.len().min(idx + block_size)];
+        let codes = tables::CodeIterator::new(data)
+            .map_while(|code| code.ok())
+            .collect::<Vec<_>>();
+        if codes.is_empty() {
+            return None;
+        }
+        Some(Service {
+            number: service_no,
+            codes,
+        })
+    }
+
+    /// The ordered list of [tables::Code]s present in this [Service] block
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// let codes = service.codes();
+    /// assert_eq!(codes, [Code::LatinCapitalA]);
+    /// ```
+    pub fn codes(&self) -> &[tables::Code] {
+        &self.codes
+    }
+
+    /// The windows referenced by the [tables::Code]s in this [Service] as returned by
+    /// [tables::Code::window_bits]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::SetCurrentWindow2).unwrap();
+    /// service.push_code(&Code::HideWindows(WindowBits::FIVE)).unwrap();
+    /// assert_eq!(service.referenced_windows(), WindowBits::TWO | WindowBits::FIVE);
+    /// ```
+    pub fn referenced_windows(&self) -> tables::WindowBits {
+        self.codes
+            .iter()
+            .fold(tables::WindowBits::NONE, |bits, code| {
+                bits | code.window_bits()
+            })
+    }
+
+    /// Compute statistics over the [tables::Code]s in this [Service]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// service.push_code(&Code::SetPenLocation(SetPenLocationArgs::new(1, 2))).unwrap();
+    /// let stats = service.statistics();
+    /// assert_eq!(stats.character_count, 1);
+    /// assert_eq!(stats.control_code_count, 1);
+    /// assert_eq!(stats.byte_count, 4);
+    /// assert_eq!(stats.category_counts[CodeCategory::G0.index()], 1);
+    /// assert_eq!(stats.category_counts[CodeCategory::C1.index()], 1);
+    /// ```
+    pub fn statistics(&self) -> ServiceStats {
+        let mut ret = ServiceStats::default();
+        for code in self.codes.iter() {
+            if code.char().is_some() {
+                ret.character_count += 1;
+            }
+            if code.is_control() {
+                ret.control_code_count += 1;
+            }
+            ret.byte_count += code.byte_len();
+            ret.category_counts[code.category().index()] += 1;
+        }
+        ret
+    }
+
+    /// Check the arguments of the commands in this [Service] for consistency.
+    ///
+    /// The following issues are reported:
+    /// - [ValidationIssueKind::PenLocationOutOfBounds] when a pen location is outside of the
+    ///   current window.  The current window is set by [tables::Code::DefineWindow] and the
+    ///   SetCurrentWindow commands and is only checked if it is defined earlier in this block.
+    /// - [ValidationIssueKind::ZeroEffectSpeed] when a window effect speed is 0.
+    /// - [ValidationIssueKind::UndefinedWindow] (as a warning) when a SetCurrentWindow command
+    ///   refers to a window that has not been defined earlier in this block.
+    /// - [ValidationIssueKind::InvalidStyleId] when a window or pen style identifier is larger
+    ///   than 7.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let service = Service::from_codes(1, [Code::SetCurrentWindow1]).unwrap();
+    /// assert_eq!(
+    ///     service.validate(),
+    ///     [ValidationIssue {
+    ///         code_index: 0,
+    ///         severity: ValidationSeverity::Warning,
+    ///         kind: ValidationIssueKind::UndefinedWindow { window_id: 1 },
+    ///     }]
+    /// );
+    /// ```
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut ret = vec![];
+        let mut defined = tables::WindowBits::NONE;
+        let mut windows: [Option<&tables::DefineWindowArgs>; 8] = [None; 8];
+        let mut current_window = None;
+        for (code_index, code) in self.codes.iter().enumerate() {
+            let mut issue = |severity, kind| {
+                ret.push(ValidationIssue {
+                    code_index,
+                    severity,
+                    kind,
+                })
+            };
+            match code {
+                tables::Code::SetCurrentWindow0
+                | tables::Code::SetCurrentWindow1
+                | tables::Code::SetCurrentWindow2
+                | tables::Code::SetCurrentWindow3
+                | tables::Code::SetCurrentWindow4
+                | tables::Code::SetCurrentWindow5
+                | tables::Code::SetCurrentWindow6
+                | tables::Code::SetCurrentWindow7 => {
+                    let bits = code.window_bits();
+                    let window_id = u8::from(bits).trailing_zeros() as usize;
+                    if (defined & bits) == tables::WindowBits::NONE {
+                        issue(
+                            ValidationSeverity::Warning,
+                            ValidationIssueKind::UndefinedWindow {
+                                window_id: window_id as u8,
+                            },
+                        );
+                    }
+                    current_window = Some(window_id);
+                }
+                tables::Code::DefineWindow(args) => {
+                    for style_id in [args.window_style_id, args.pen_style_id] {
+                        if style_id > 7 {
+                            issue(
+                                ValidationSeverity::Error,
+                                ValidationIssueKind::InvalidStyleId { style_id },
+                            );
+                        }
+                    }
+                    defined = defined | code.window_bits();
+                    let window_id = (args.window_id & 0x7) as usize;
+                    windows[window_id] = Some(args);
+                    current_window = Some(window_id);
+                }
+                tables::Code::SetWindowAttributes(args) if args.effect_speed == 0 => issue(
+                    ValidationSeverity::Error,
+                    ValidationIssueKind::ZeroEffectSpeed,
+                ),
+                tables::Code::SetPenLocation(args) => {
+                    if let Some(define) = current_window.and_then(|window_id| windows[window_id]) {
+                        if args.row > define.row_count || args.column > define.column_count {
+                            issue(
+                                ValidationSeverity::Error,
+                                ValidationIssueKind::PenLocationOutOfBounds {
+                                    row: args.row,
+                                    column: args.column,
+                                    row_count: u16::from(define.row_count) + 1,
+                                    column_count: u16::from(define.column_count) + 1,
+                                },
+                            );
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        ret
+    }
+
+    /// The text contained in this [Service] block.
+    ///
+    /// All characters are included, [tables::Code::CR] is converted to a newline and all other
+    /// commands are skipped.  [tables::Code::P16] characters are interpreted as a unicode code
+    /// point.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(Code::LatinCapitalA).unwrap();
+    /// service.push_code(Code::CR).unwrap();
+    /// service.push_code(SetPenLocationArgs::new(1, 2)).unwrap();
+    /// service.push_code(Code::Ext1(Ext1::TradeMarkSign)).unwrap();
+    /// assert_eq!(service.text(), "A\n™");
+    /// ```
+    pub fn text(&self) -> String {
+        self.codes
+            .iter()
+            .filter_map(|code| match code {
+                tables::Code::CR => Some('\n'),
+                tables::Code::P16(c) => char::from_u32(*c as u32),
+                _ => code.char(),
+            })
+            .collect()
+    }
+
+    fn fmt_indented(&self, f: &mut std::fmt::Formatter, indent: usize) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:indent$}Service {} len:{}",
+            "",
+            self.number,
+            self.len()
+        )?;
+        for code in self.codes.iter() {
+            write!(f, "{:indent$}  ", "")?;
+            if let Some(c) = code.char() {
+                write!(f, "{c:?}")?;
+            } else {
+                write!(f, "{code}")?;
+            }
+            if f.alternate() {
+                let mut bytes = vec![];
+                code.write(&mut bytes).map_err(|_| std::fmt::Error)?;
+                write!(f, " [")?;
+                for (i, byte) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{byte:02x}")?;
+                }
+                write!(f, "]")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+
+    /// Consume this [Service] and return the contained [tables::Code]s
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// assert_eq!(service.into_codes(), vec![Code::LatinCapitalA]);
+    /// ```
+    pub fn into_codes(self) -> Vec<tables::Code> {
+        self.codes
+    }
+
+    /// Only keep the [tables::Code]s that match the predicate
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// service.push_code(&Code::ETX).unwrap();
+    /// service.retain(|code| code.char().is_some());
+    /// assert_eq!(service.codes(), [Code::LatinCapitalA]);
+    /// ```
+    pub fn retain<F: FnMut(&tables::Code) -> bool>(&mut self, f: F) {
+        self.codes.retain(f)
+    }
+
+    /// The first [tables::Code] in this [Service] that has a printable character
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::ETX).unwrap();
+    /// assert_eq!(service.first_printable(), None);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// assert_eq!(service.first_printable(), Some(&Code::LatinCapitalA));
+    /// ```
+    pub fn first_printable(&self) -> Option<&tables::Code> {
+        self.codes.iter().find(|code| code.char().is_some())
+    }
+
+    /// The printable characters in this [Service] as a [String]
+    ///
+    /// See [tables::Code::text_from_codes].
+    #[deprecated(note = "Use Service::text() or tables::Code::text_from_codes() instead")]
+    pub fn to_text(&self) -> String {
+        tables::Code::text_from_codes(self.codes())
+    }
+
+    /// Write the [Service] block to a byte stream
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// let mut written = vec![];
+    /// service.write(&mut written);
+    /// let expected = [0x21, 0x41];
+    /// assert_eq!(written, expected);
+    /// ```
+    pub fn write<W: std::io::Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
+        // TODO: fail if we would overrun max size
+        let len = (self.codes_len() & 0x3F) as u8;
+        if self.is_extended() {
+            let mut buf = [0; 2];
+            buf[0] = 0xE0 | len;
+            buf[1] = self.number;
+            w.write_all(&buf)?;
+        } else {
+            let byte = (self.number & 0x7) << 5 | len;
+            w.write_all(&[byte])?;
+        }
+        for code in self.codes.iter() {
+            code.write(w)?;
+        }
+        Ok(())
+    }
+
+    /// Write the [Service] block into a new `Vec<u8>`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [tables::Code]s are larger than 31 bytes which cannot occur when the
+    /// [Service] is constructed using [push_code](Self::push_code).
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(&Code::LatinCapitalA).unwrap();
+    /// assert_eq!(service.to_bytes(), [0x21, 0x41]);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let codes_len = self.codes_len();
+        assert!(
+            codes_len <= 31,
+            "Service block length {codes_len} is larger than 31"
+        );
+        let mut ret = Vec::with_capacity(codes_len + 2);
+        self.write(&mut ret)
+            .expect("Writing to a Vec<u8> cannot fail");
+        ret
+    }
+}
+
+/// Writes a multi-line summary of the [Service].  The first line contains the service number
+/// followed by one line for each [tables::Code] indented by two spaces.  Characters are written
+/// quoted and commands are written using their [tables::Code::display_name].
+///
+/// The alternate form (`{:#}`) additionally includes the bytes of each [tables::Code].
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, tables::*};
+/// let mut service = Service::new(1);
+/// service.push_code(&Code::LatinCapitalA).unwrap();
+/// service.push_code(&Code::CR).unwrap();
+/// assert_eq!(service.to_string(), "Service 1 len:3\n  'A'\n  Carriage Return\n");
+/// assert_eq!(format!("{service:#}"), "Service 1 len:3\n  'A' [41]\n  Carriage Return [0d]\n");
+/// ```
+impl std::fmt::Display for Service {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// Collects [tables::Code]s into [Service] number 1.
+///
+/// [tables::Code]s are added until the first [tables::Code] that does not fit into the [Service].
+/// That [tables::Code] and all following [tables::Code]s are dropped.  Use [Service::from_codes]
+/// to be notified of overflow.
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, tables::*};
+/// let service: Service = std::iter::repeat(Code::LatinCapitalA).take(40).collect();
+/// assert_eq!(service.number(), 1);
+/// assert_eq!(service.codes().len(), 31);
+/// ```
+impl FromIterator<tables::Code> for Service {
+    fn from_iter<I: IntoIterator<Item = tables::Code>>(iter: I) -> Self {
+        let mut service = Service::new(1);
+        service.extend(iter);
+        service
+    }
+}
+
+/// Extends a [Service] with [tables::Code]s.
+///
+/// [tables::Code]s are added until the first [tables::Code] that does not fit into the [Service].
+/// That [tables::Code] and all following [tables::Code]s are dropped.  Use [Service::push_code]
+/// to be notified of overflow.
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, tables::*};
+/// let mut service = Service::new(1);
+/// service.extend([Code::LatinCapitalA, Code::LatinCapitalB]);
+/// assert_eq!(service.codes(), [Code::LatinCapitalA, Code::LatinCapitalB]);
+/// ```
+impl Extend<tables::Code> for Service {
+    fn extend<I: IntoIterator<Item = tables::Code>>(&mut self, iter: I) {
+        for code in iter {
+            if let Err(e) = self.push_code(code) {
+                debug!("dropping remaining codes: {e}");
+                break;
+            }
+        }
+    }
+}
+
+/// Parses a [Service] using [Service::parse]
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, tables::*};
+/// # fn main() -> Result<(), ParserError> {
+/// let bytes = vec![0x21, 0x41];
+/// let service: Service = bytes.as_slice().try_into()?;
+/// assert_eq!(service.codes(), [Code::LatinCapitalA]);
+/// # Ok(())
+/// # }
+/// ```
+impl TryFrom<&[u8]> for Service {
+    type Error = ParserError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Service::parse(data)
+    }
+}
+
+impl IntoIterator for Service {
+    type Item = tables::Code;
+    type IntoIter = std::vec::IntoIter<tables::Code>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.codes.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Service {
+    type Item = &'a tables::Code;
+    type IntoIter = std::slice::Iter<'a, tables::Code>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.codes.iter()
+    }
+}
+
+/// A borrowed [DTVCCPacket] whose [Service]s are parsed lazily
+///
+/// Created by [DTVCCPacket::parse_ref].
+#[derive(Debug, Clone, Copy)]
+pub struct DTVCCPacketRef<'a> {
+    seq_no: u8,
+    data: &'a [u8],
+}
+
+impl<'a> DTVCCPacketRef<'a> {
+    /// The sequence number of the DTVCCPacket
+    pub fn sequence_no(&self) -> u8 {
+        self.seq_no
+    }
+
+    /// An [Iterator] over the [ServiceRef]s in this packet.  Null and empty service blocks are
+    /// skipped.
+    pub fn services(&self) -> ServiceRefIter<'a> {
+        ServiceRefIter { data: self.data }
+    }
+}
+
+/// An [Iterator] over the [ServiceRef]s in a [DTVCCPacketRef]
+///
+/// Iteration stops after the first error is returned.
+#[derive(Debug, Clone)]
+pub struct ServiceRefIter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for ServiceRefIter<'a> {
+    type Item = Result<ServiceRef<'a>, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.data.is_empty() {
+            let service = match Service::parse_ref(self.data) {
+                Ok(service) => service,
+                Err(e) => {
+                    self.data = &[];
+                    return Some(Err(e));
+                }
+            };
+            if service.number() == 0 || service.block_len() == 0 {
+                self.data = &self.data[1..];
+                continue;
+            }
+            self.data = &self.data[service.hdr_len + service.block_len()..];
+            return Some(Ok(service));
+        }
+        None
+    }
+}
+
+impl std::iter::FusedIterator for ServiceRefIter<'_> {}
+
+/// A borrowed [Service] block whose [tables::Code]s are parsed lazily
+///
+/// Created by [Service::parse_ref].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServiceRef<'a> {
+    number: u8,
+    hdr_len: usize,
+    data: &'a [u8],
+}
+
+impl<'a> ServiceRef<'a> {
+    /// Returns the number of this service
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    /// The length in bytes of the code data in this service block as advertised in the header
+    pub fn block_len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The raw bytes of the [tables::Code]s in this service block
+    pub fn code_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// An [Iterator] parsing the [tables::Code]s in this service block
+    pub fn codes(&self) -> tables::CodeIterator<'a> {
+        tables::CodeIterator::new(self.data)
+    }
+
+    /// Parse all the [tables::Code]s into an owned [Service]
+    pub fn to_service(&self) -> Result<Service, ParserError> {
+        if self.number != 0 {
+            // every code is at least one byte
+            let mut service = Service::with_capacity(self.number, self.data.len());
+            for code in self.codes() {
+                service.codes.push(code?);
+            }
+            Ok(service)
+        } else {
+            Ok(Service {
+                number: 0,
+                codes: vec![],
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn simple_parse_dtvcc() {
+        test_init_log();
+        let data = [0x02, 0x01 << 5 | 0x01, 0x2A];
+        let dtvcc = DTVCCPacket::parse(&data).unwrap();
+        let services = dtvcc.services();
+        assert_eq!(services.len(), 1);
+        for service in services.iter() {
+            assert_eq!(service.number, 1);
+            let codes = service.codes();
+            for code in codes.iter() {
+                trace!("parsed {code:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn simple_write_dtvcc() {
+        test_init_log();
+        let mut service = Service::new(1);
+        let code = tables::Code::Asterisk;
+        service.push_code(&code).unwrap();
+        let mut dtvcc = DTVCCPacket::new(0);
+        dtvcc.push_service(service).unwrap();
+        let mut written = vec![];
+        dtvcc.write(&mut written).unwrap();
+        let data = [0x02, 0x01 << 5 | 0x01, 0x2A, 0x00];
+        assert_eq!(written, data);
+    }
+
+    #[derive(Debug)]
+    struct ServiceData<'a> {
+        service_no: u8,
+        codes: &'a [tables::Code],
+    }
+
+    #[derive(Debug)]
+    struct PacketData<'a> {
+        sequence_no: u8,
+        services: &'a [ServiceData<'a>],
+    }
+
+    #[derive(Debug)]
+    struct TestCCData<'a> {
+        framerate: Framerate,
+        cc_data: &'a [&'a [u8]],
+        packets: &'a [PacketData<'a>],
+        cea608: &'a [&'a [Cea608]],
+    }
+
+    static TEST_CC_DATA: [TestCCData; 8] = [
+        // simple packet with a single service and single code
+        TestCCData {
+            framerate: Framerate::new(25, 1),
+            cc_data: &[&[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00]],
+            packets: &[PacketData {
+                sequence_no: 0,
+                services: &[ServiceData {
+                    service_no: 1,
+                    codes: &[tables::Code::LatinCapitalA],
+                }],
+            }],
+            cea608: &[],
+        },
+        // simple packet with a single service and two codes
+        TestCCData {
+            framerate: Framerate::new(25, 1),
+            cc_data: &[&[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x02, 0x22, 0xFE, 0x41, 0x42]],
+            packets: &[PacketData {
+                sequence_no: 0,
+                services: &[ServiceData {
+                    service_no: 1,
+                    codes: &[tables::Code::LatinCapitalA, tables::Code::LatinCapitalB],
+                }],
+            }],
+            cea608: &[],
+        },
+        // two packets each with a single service and single code
+        TestCCData {
+            framerate: Framerate::new(25, 1),
+            cc_data: &[
+                &[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00],
+                &[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x42, 0x21, 0xFE, 0x42, 0x00],
+            ],
+            packets: &[
+                PacketData {
+                    sequence_no: 0,
+                    services: &[ServiceData {
+                        service_no: 1,
+                        codes: &[tables::Code::LatinCapitalA],
+                    }],
+                },
+                PacketData {
+                    sequence_no: 1,
+                    services: &[ServiceData {
+                        service_no: 1,
+                        codes: &[tables::Code::LatinCapitalB],
+                    }],
+                },
+            ],
+            cea608: &[],
+        },
+        // two packets with a single service and one code split across both packets
+        TestCCData {
+            framerate: Framerate::new(25, 1),
+            cc_data: &[
+                &[0x80 | 0x40 | 0x01, 0xFF, 0xFF, 0x02, 0x21],
+                &[0x80 | 0x40 | 0x01, 0xFF, 0xFE, 0x41, 0x00],
+            ],
+            packets: &[PacketData {
+                sequence_no: 0,
+                services: &[ServiceData {
+                    service_no: 1,
+                    codes: &[tables::Code::LatinCapitalA],
+                }],
+            }],
+            cea608: &[],
+        },
+        // simple packet with a single null service
+        TestCCData {
+            framerate: Framerate::new(25, 1),
+            cc_data: &[&[0x80 | 0x40 | 0x01, 0xFF, 0xFF, 0x01, 0x00]],
+            packets: &[PacketData {
+                sequence_no: 0,
+                services: &[],
+            }],
+            cea608: &[],
+        },
+        // DTVCCPacket with two services
+        TestCCData {
+            framerate: Framerate::new(25, 1),
+            cc_data: &[&[
+                0x80 | 0x40 | 0x03,
+                0xFF,
+                0xFF,
+                0x03,
+                0x21,
+                0xFE,
+                0x41,
+                0x41,
+                0xFE,
+                0x42,
+                0x00,
+            ]],
+            packets: &[PacketData {
+                sequence_no: 0,
+                services: &[
+                    ServiceData {
+                        service_no: 1,
+                        codes: &[tables::Code::LatinCapitalA],
+                    },
+                    ServiceData {
+                        service_no: 2,
+                        codes: &[tables::Code::LatinCapitalB],
+                    },
+                ],
+            }],
+            cea608: &[],
+        },
+        // cc_data with two DTVCCPacket
+        TestCCData {
+            framerate: Framerate::new(25, 1),
+            cc_data: &[&[
+                0x80 | 0x40 | 0x04,
+                0xFF,
+                0xFF,
+                0x02,
+                0x21,
+                0xFE,
+                0x41,
+                0x00,
+                0xFF,
+                0x42,
+                0x41,
+                0xFE,
+                0x42,
+                0x00,
+            ]],
+            packets: &[
+                PacketData {
+                    sequence_no: 0,
+                    services: &[ServiceData {
+                        service_no: 1,
+                        codes: &[tables::Code::LatinCapitalA],
+                    }],
+                },
+                PacketData {
+                    sequence_no: 1,
+                    services: &[ServiceData {
+                        service_no: 2,
+                        codes: &[tables::Code::LatinCapitalB],
+                    }],
+                },
+            ],
+            cea608: &[],
+        },
+        // two packets with a single service and one code split across both packets with 608
+        // padding data
+        TestCCData {
+            framerate: Framerate::new(25, 1),
+            cc_data: &[
+                &[
+                    0x80 | 0x40 | 0x03,
+                    0xFF,
+                    0xFC,
+                    0x61,
+                    0x62,
+                    0xFD,
+                    0x63,
+                    0x64,
+                    0xFF,
+                    0x02,
+                    0x21,
+                ],
+                &[
+                    0x80 | 0x40 | 0x03,
+                    0xFF,
+                    0xFC,
+                    0x41,
+                    0x42,
+                    0xFD,
+                    0x43,
+                    0x44,
+                    0xFE,
+                    0x41,
+                    0x00,
+                ],
+            ],
+            packets: &[PacketData {
+                sequence_no: 0,
+                services: &[ServiceData {
+                    service_no: 1,
+                    codes: &[tables::Code::LatinCapitalA],
+                }],
+            }],
+            cea608: &[
+                &[Cea608::Field1(0x61, 0x62), Cea608::Field2(0x63, 0x64)],
+                &[Cea608::Field1(0x41, 0x42), Cea608::Field2(0x43, 0x44)],
+            ],
+        },
+    ];
+
+    #[test]
+    fn cc_data_parse() {
+        test_init_log();
+        for (i, test_data) in TEST_CC_DATA.iter().enumerate() {
+            log::info!("parsing {i}: {test_data:?}");
+            let mut parser = CCDataParser::new();
+            if !test_data.cea608.is_empty() {
+                parser.handle_cea608();
+            }
+            let mut expected_iter = test_data.packets.iter();
+            let mut cea608_iter = test_data.cea608.iter();
+            for data in test_data.cc_data.iter() {
+                debug!("pushing {data:?}");
+                parser.push(data).unwrap();
+                while let Some(packet) = parser.pop_packet() {
+                    let expected = expected_iter.next().unwrap();
+                    assert_eq!(expected.sequence_no, packet.sequence_no());
+                    let services = packet.services();
+                    let mut expected_service_iter = expected.services.iter();
+                    for parsed_service in services.iter() {
+                        let expected_service = expected_service_iter.next().unwrap();
+                        assert_eq!(parsed_service.number(), expected_service.service_no);
+                        assert_eq!(expected_service.codes, parsed_service.codes());
+                    }
+                    assert!(expected_service_iter.next().is_none());
+                }
+                assert_eq!(parser.cea608().as_ref(), cea608_iter.next());
+            }
+            assert!(parser.pop_packet().is_none());
+            assert!(expected_iter.next().is_none());
+            assert!(cea608_iter.next().is_none());
+        }
+    }
+
+    fn parse_chunks(chunks: &[&[u8]], stream: bool) -> (Vec<DTVCCPacket>, Vec<Cea608>) {
+        let mut parser = CCDataParser::new();
+        parser.handle_cea608();
+        parser.set_accumulate_cea608(true);
+        let mut packets = vec![];
+        for chunk in chunks {
+            if stream {
+                parser.push_stream(chunk).unwrap();
+            } else {
+                parser.push(chunk).unwrap();
+            }
+            while let Some(packet) = parser.pop_packet() {
+                packets.push(packet);
+            }
+        }
+        (packets, parser.take_cea608())
+    }
+
+    #[test]
+    fn cc_data_push_stream() {
+        test_init_log();
+        for (i, test_data) in TEST_CC_DATA.iter().enumerate() {
+            log::info!("parsing {i}: {test_data:?}");
+            let expected = parse_chunks(test_data.cc_data, false);
+            let stream = test_data.cc_data.concat();
+            for split in 0..=stream.len() {
+                let (first, second) = stream.split_at(split);
+                assert_eq!(
+                    parse_chunks(&[first, second, &[]], true),
+                    expected,
+                    "split at {split}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cc_data_push_triples() {
+        test_init_log();
+        for (i, test_data) in TEST_CC_DATA.iter().enumerate() {
+            log::info!("parsing {i}: {test_data:?}");
+            let mut parser = CCDataParser::new();
+            let mut triples_parser = CCDataParser::new();
+            if !test_data.cea608.is_empty() {
+                parser.handle_cea608();
+                triples_parser.handle_cea608();
+            }
+            for data in test_data.cc_data.iter() {
+                parser.push(data).unwrap();
+                if data[0] & 0x40 > 0 {
+                    triples_parser.push_triples(&data[2..]).unwrap();
+                } else {
+                    triples_parser.push_triples(&[]).unwrap();
+                }
+                assert_eq!(parser.cea608(), triples_parser.cea608());
+                loop {
+                    let packet = parser.pop_packet();
+                    assert_eq!(packet, triples_parser.pop_packet());
+                    if packet.is_none() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut parser = CCDataParser::new();
+        assert_eq!(
+            parser.push_triples(&[0xFF, 0x02, 0x21, 0xFE]),
+            Err(ParserError::LengthMismatch {
+                expected: 6,
+                actual: 4
+            })
+        );
+    }
+
+    #[test]
+    fn cc_data_push_triple() {
+        test_init_log();
+        for (i, test_data) in TEST_CC_DATA.iter().enumerate() {
+            log::info!("parsing {i}: {test_data:?}");
+            let mut parser = CCDataParser::new();
+            if !test_data.cea608.is_empty() {
+                parser.handle_cea608();
+            }
+            let mut expected_iter = test_data.packets.iter();
+            let mut cea608_iter = test_data.cea608.iter();
+            for data in test_data.cc_data.iter() {
+                if data[0] & 0x40 > 0 {
+                    for triple in data[2..].chunks_exact(3) {
+                        parser
+                            .push_triple(triple[0] & 0x04 > 0, triple[0], [triple[1], triple[2]])
+                            .unwrap();
+                    }
+                }
+                parser.end_of_frame().unwrap();
+                while let Some(packet) = parser.pop_packet() {
+                    let expected = expected_iter.next().unwrap();
+                    assert_eq!(expected.sequence_no, packet.sequence_no());
+                    let services = packet.services();
+                    assert_eq!(services.len(), expected.services.len());
+                    for (parsed, expected) in services.iter().zip(expected.services.iter()) {
+                        assert_eq!(parsed.number(), expected.service_no);
+                        assert_eq!(parsed.codes(), expected.codes);
+                    }
+                }
+                assert_eq!(parser.cea608().as_ref(), cea608_iter.next());
+            }
+            assert!(parser.pop_packet().is_none());
+            assert!(expected_iter.next().is_none());
+            assert!(cea608_iter.next().is_none());
+        }
+    }
+
+    #[test]
+    fn push_triple_cea608_after_cea708() {
+        test_init_log();
+        let data = [
+            0x43, 0xFF, 0xFC, 0x80, 0x81, 0xFF, 0x02, 0x21, 0xFC, 0x82, 0x83,
+        ];
+        let mut parser = CCDataParser::new();
+        parser.handle_cea608();
+        let expected = parser.push(&data).unwrap_err();
+
+        let mut triple_parser = CCDataParser::new();
+        triple_parser.handle_cea608();
+        triple_parser.push_triple(true, 0b00, [0x80, 0x81]).unwrap();
+        triple_parser.push_triple(true, 0b11, [0x02, 0x21]).unwrap();
+        assert_eq!(
+            triple_parser.push_triple(true, 0b00, [0x82, 0x83]),
+            Err(expected)
+        );
+        assert_eq!(triple_parser.cea608(), parser.cea608());
+        assert_eq!(
+            triple_parser.end_of_frame(),
+            Ok(PushOutcome {
+                consumed: 0,
+                pending: 0,
+                packets_ready: 0
+            })
+        );
+    }
+
+    static WRITE_CC_DATA: [TestCCData; 7] = [
+        // simple packet with a single service and single code
+        TestCCData {
+            framerate: Framerate::new(25, 1),
+            cc_data: &[&[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00]],
+            packets: &[PacketData {
+                sequence_no: 0,
+                services: &[ServiceData {
+                    service_no: 1,
+                    codes: &[tables::Code::LatinCapitalA],
+                }],
+            }],
+            cea608: &[],
+        },
+        // simple packet with a single service and two codes
         TestCCData {
             framerate: Framerate::new(25, 1),
             cc_data: &[&[0x80 | 0x40 | 0x02, 0xFF, 0xFF, 0x02, 0x22, 0xFE, 0x41, 0x42]],
@@ -1378,160 +4607,2632 @@ mod test {
                 &[
                     0x80 | 0x40 | 0x0A,
                     0xFF,
-                    0xFC,
-                    0x20,
-                    0x42,
+                    0xFC,
+                    0x20,
+                    0x42,
+                    0xFF,
+                    0xC0 | 0x11,
+                    0x20 | 0x1F,
+                    0xFE,
+                    0x41,
+                    0x42,
+                    0xFE,
+                    0x43,
+                    0x44,
+                    0xFE,
+                    0x45,
+                    0x46,
+                    0xFE,
+                    0x47,
+                    0x48,
+                    0xFE,
+                    0x49,
+                    0x4A,
+                    0xFE,
+                    0x4B,
+                    0x4C,
+                    0xFE,
+                    0x4D,
+                    0x4E,
+                    0xFE,
+                    0x4F,
+                    0x50,
+                ],
+                &[
+                    0x80 | 0x40 | 0x09,
+                    0xFF,
+                    0xFD,
+                    0x21,
+                    0x43,
+                    0xFE,
+                    0x51,
+                    0x52,
+                    0xFE,
+                    0x53,
+                    0x54,
+                    0xFE,
+                    0x55,
+                    0x56,
+                    0xFE,
+                    0x57,
+                    0x58,
+                    0xFE,
+                    0x59,
+                    0x5A,
+                    0xFE,
+                    0x61,
+                    0x62,
+                    0xFE,
+                    0x63,
+                    0x64,
+                    0xFE,
+                    0x65,
+                    0x0,
+                ],
+            ],
+            packets: &[PacketData {
+                sequence_no: 3,
+                services: &[ServiceData {
+                    service_no: 1,
+                    codes: &[
+                        tables::Code::LatinCapitalA,
+                        tables::Code::LatinCapitalB,
+                        tables::Code::LatinCapitalC,
+                        tables::Code::LatinCapitalD,
+                        tables::Code::LatinCapitalE,
+                        tables::Code::LatinCapitalF,
+                        tables::Code::LatinCapitalG,
+                        tables::Code::LatinCapitalH,
+                        tables::Code::LatinCapitalI,
+                        tables::Code::LatinCapitalJ,
+                        tables::Code::LatinCapitalK,
+                        tables::Code::LatinCapitalL,
+                        tables::Code::LatinCapitalM,
+                        tables::Code::LatinCapitalN,
+                        tables::Code::LatinCapitalO,
+                        tables::Code::LatinCapitalP,
+                        tables::Code::LatinCapitalQ,
+                        tables::Code::LatinCapitalR,
+                        tables::Code::LatinCapitalS,
+                        tables::Code::LatinCapitalT,
+                        tables::Code::LatinCapitalU,
+                        tables::Code::LatinCapitalV,
+                        tables::Code::LatinCapitalW,
+                        tables::Code::LatinCapitalX,
+                        tables::Code::LatinCapitalY,
+                        tables::Code::LatinCapitalZ,
+                        tables::Code::LatinLowerA,
+                        tables::Code::LatinLowerB,
+                        tables::Code::LatinLowerC,
+                        tables::Code::LatinLowerD,
+                        tables::Code::LatinLowerE,
+                    ],
+                }],
+            }],
+            cea608: &[&[Cea608::Field1(0x20, 0x42), Cea608::Field2(0x21, 0x43)]],
+        },
+        // simple packet with multiple cea608 that will span two outputs
+        TestCCData {
+            framerate: Framerate::new(24, 1),
+            cc_data: &[
+                &[0x80 | 0x40 | 0x02, 0xFF, 0xFC, 0x20, 0x42, 0xFD, 0x21, 0x43],
+                &[0x80 | 0x40 | 0x02, 0xFF, 0xFC, 0x22, 0x44, 0xFD, 0x23, 0x45],
+            ],
+            packets: &[PacketData {
+                sequence_no: 3,
+                services: &[],
+            }],
+            cea608: &[
+                &[Cea608::Field1(0x20, 0x42), Cea608::Field2(0x21, 0x43)],
+                &[Cea608::Field1(0x22, 0x44), Cea608::Field2(0x23, 0x45)],
+            ],
+        },
+    ];
+
+    fn check_write_cc_data(mut write: impl FnMut(&mut CCDataWriter, Framerate, &mut Vec<u8>)) {
+        for test_data in WRITE_CC_DATA.iter() {
+            log::info!("writing {test_data:?}");
+            let mut packet_iter = test_data.packets.iter();
+            let mut cea608_iter = test_data.cea608.iter();
+            let mut writer = CCDataWriter::default();
+            for cc_data in test_data.cc_data.iter() {
+                if let Some(packet_data) = packet_iter.next() {
+                    let mut pack = DTVCCPacket::new(packet_data.sequence_no);
+                    for service_data in packet_data.services.iter() {
+                        let mut service = Service::new(service_data.service_no);
+                        for code in service_data.codes.iter() {
+                            service.push_code(code).unwrap();
+                        }
+                        pack.push_service(service).unwrap();
+                    }
+                    writer.push_packet(pack);
+                }
+                if let Some(&cea608) = cea608_iter.next() {
+                    for pair in cea608 {
+                        writer.push_cea608(*pair);
+                    }
+                }
+                let mut written = vec![];
+                write(&mut writer, test_data.framerate, &mut written);
+                assert_eq!(cc_data, &written);
+            }
+        }
+    }
+
+    #[test]
+    fn packet_write_cc_data() {
+        test_init_log();
+        check_write_cc_data(|writer, framerate, written| {
+            writer.write(framerate, written).unwrap();
+        });
+    }
+
+    #[test]
+    fn write_preserve_cea608_padding() {
+        test_init_log();
+        let framerate = Framerate::new(30, 1);
+        let mut writer = CCDataWriter::default();
+        assert!(!writer.preserve_cea608_padding());
+        writer.push_cea608(Cea608::Field1(0x80, 0x80));
+        let mut written = vec![];
+        writer.write(framerate, &mut written).unwrap();
+        assert_eq!(written, [0x80 | 0x40, 0xFF]);
+
+        writer.set_preserve_cea608_padding(true);
+        writer.push_cea608(Cea608::Field1(0x80, 0x80));
+        writer.push_cea608(Cea608::Field2(0x80, 0x80));
+        let mut written = vec![];
+        writer.write(framerate, &mut written).unwrap();
+        assert_eq!(
+            written,
+            [0x80 | 0x40 | 0x02, 0xFF, 0xFC, 0x80, 0x80, 0xFD, 0x80, 0x80]
+        );
+    }
+
+    #[test]
+    fn write_with_cc_count() {
+        test_init_log();
+        let mut packet = DTVCCPacket::new(1);
+        packet
+            .push_service(Service::from_codes(1, text_codes(&"A".repeat(31))).unwrap())
+            .unwrap();
+        packet
+            .push_service(Service::from_codes(2, text_codes(&"B".repeat(31))).unwrap())
+            .unwrap();
+        let cc_count = packet.cc_count();
+        assert_eq!(cc_count, 33);
+
+        let mut writer = CCDataWriter::default();
+        writer.push_packet(packet.clone());
+        let mut parser = CCDataParser::new();
+        let mut frames = 0;
+        loop {
+            let mut written = vec![];
+            writer
+                .write_with_cc_count(Framerate::new(25, 1), 4, &mut written)
+                .unwrap();
+            if written.len() == 2 {
+                break;
+            }
+            assert!(written.len() <= 2 + 4 * 3);
+            assert_eq!(written[0] & 0x1F, (written.len() as u8 - 2) / 3);
+            parser.push(&written).unwrap();
+            frames += 1;
+        }
+        assert_eq!(frames, (cc_count + 3) / 4);
+        assert_eq!(parser.pop_packet(), Some(packet));
+        assert_eq!(parser.pop_packet(), None);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn packet_write_cc_data_async() {
+        test_init_log();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        check_write_cc_data(|writer, framerate, written| {
+            runtime
+                .block_on(writer.write_async(framerate, written))
+                .unwrap();
+        });
+        let mut writer = CCDataWriter::default();
+        writer.set_output_padding(true);
+        runtime
+            .block_on(writer.write_async(Framerate::new(30, 1), &mut tokio::io::sink()))
+            .unwrap();
+    }
+
+    fn text_codes(text: &str) -> impl Iterator<Item = tables::Code> + '_ {
+        text.chars().map(|c| tables::Code::from_char(c).unwrap())
+    }
+
+    fn packet_with_services(seq_no: u8, services: &[(u8, usize)]) -> DTVCCPacket {
+        let mut packet = DTVCCPacket::new(seq_no);
+        for &(service_no, len) in services {
+            let codes = std::iter::repeat(tables::Code::LatinCapitalA).take(len);
+            packet
+                .push_service(Service::from_codes(service_no, codes).unwrap())
+                .unwrap();
+        }
+        packet
+    }
+
+    #[test]
+    fn packet_merge() {
+        test_init_log();
+        let mut packet = packet_with_services(1, &[(1, 4)]);
+        let other = packet_with_services(2, &[(2, 3), (3, 2)]);
+        packet.merge(&other).unwrap();
+        assert_eq!(packet.sequence_no(), 1);
+        assert_eq!(
+            packet
+                .services()
+                .iter()
+                .map(|s| (s.number(), s.codes_len()))
+                .collect::<Vec<_>>(),
+            [(1, 4), (2, 3), (3, 2)]
+        );
+        assert_eq!(packet.len(), 1 + 5 + 4 + 3);
+    }
+
+    #[test]
+    fn packet_merge_same_service() {
+        test_init_log();
+        let mut packet = packet_with_services(0, &[(1, 20), (2, 1)]);
+        packet
+            .merge(&packet_with_services(1, &[(1, 11), (2, 5)]))
+            .unwrap();
+        assert_eq!(
+            packet
+                .services()
+                .iter()
+                .map(|s| (s.number(), s.codes_len()))
+                .collect::<Vec<_>>(),
+            [(1, 31), (2, 6)]
+        );
+        // no space left in the service 1 block
+        packet.merge(&packet_with_services(1, &[(1, 1)])).unwrap();
+        assert_eq!(
+            packet
+                .services()
+                .iter()
+                .map(|s| (s.number(), s.codes_len()))
+                .collect::<Vec<_>>(),
+            [(1, 31), (2, 6), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn packet_merge_overflow() {
+        test_init_log();
+        let mut packet = packet_with_services(0, &[(1, 31), (2, 31), (3, 31)]);
+        let other = packet_with_services(1, &[(4, 31)]);
+        let expected = packet.clone();
+        let expected_other = other.clone();
+        assert_eq!(
+            packet.merge(&other),
+            Err(WriterError::would_overflow(
+                1 + 3 * 32 + 32 - 128,
+                OverflowContext::Packet
+            ))
+        );
+        assert_eq!(packet, expected);
+        assert_eq!(other, expected_other);
+        assert_eq!(other.len(), 33);
+    }
+
+    #[test]
+    fn packet_truncated_in_service() {
+        test_init_log();
+        let mut packet = DTVCCPacket::new(1);
+        packet
+            .push_service(Service::from_codes(1, text_codes("ABCD")).unwrap())
+            .unwrap();
+        packet
+            .push_service(Service::from_codes(2, text_codes("EF")).unwrap())
+            .unwrap();
+        let data = packet.to_bytes();
+        // cut in the middle of the second service block
+        let parsed = DTVCCPacket::parse(&data[..data.len() - 2]).unwrap();
+        assert!(parsed.was_truncated());
+        assert_eq!(parsed.sequence_no(), 1);
+        assert_eq!(parsed.services().len(), 2);
+        assert_eq!(parsed.services()[0], packet.services()[0]);
+        assert_eq!(
+            parsed.services()[1].codes(),
+            text_codes("E").collect::<Vec<_>>()
+        );
+        // cut between service blocks
+        let parsed = DTVCCPacket::parse(&data[..6]).unwrap();
+        assert!(parsed.was_truncated());
+        assert_eq!(parsed.services(), &packet.services()[..1]);
+
+        let parsed = DTVCCPacket::parse(&data).unwrap();
+        assert!(!parsed.was_truncated());
+    }
+
+    #[test]
+    fn packet_truncated_in_code() {
+        test_init_log();
+        let mut service = Service::new(1);
+        service.push_code(tables::Code::LatinCapitalA).unwrap();
+        service
+            .push_code(tables::DefineWindowArgs::new(
+                0,
+                0,
+                tables::Anchor::TopLeft,
+                false,
+                0,
+                0,
+                0,
+                0,
+                true,
+                true,
+                true,
+                1,
+                1,
+            ))
+            .unwrap();
+        let mut packet = DTVCCPacket::new(0);
+        packet.push_service(service).unwrap();
+        let data = packet.to_bytes();
+        for cut in 3..data.len() - 2 {
+            let parsed = DTVCCPacket::parse(&data[..cut]).unwrap();
+            assert!(parsed.was_truncated());
+            assert_eq!(parsed.services()[0].codes(), [tables::Code::LatinCapitalA]);
+        }
+        // the header alone
+        let parsed = DTVCCPacket::parse(&data[..1]).unwrap();
+        assert!(parsed.was_truncated());
+        assert!(parsed.services().is_empty());
+    }
+
+    #[test]
+    fn parser_truncated_packet() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        // DTVCCPacket advertising 6 bytes, interrupted by the start of the next packet
+        parser
+            .push(&[0x42, 0xFF, 0xFF, 0x03, 0x23, 0xFE, 0x41, 0x42])
+            .unwrap();
+        parser
+            .push(&[0x42, 0xFF, 0xFF, 0x42, 0x21, 0xFE, 0x43, 0x00])
+            .unwrap();
+        let packet = parser.pop_packet().unwrap();
+        assert!(packet.was_truncated());
+        assert_eq!(packet.services()[0].text(), "AB");
+        let packet = parser.pop_packet().unwrap();
+        assert!(!packet.was_truncated());
+        assert_eq!(packet.services()[0].text(), "C");
+    }
+
+    #[test]
+    fn parser_bounded_queue() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        parser.set_max_queued_packets(Some(4));
+        // each header interrupts the previous incomplete packet
+        for _ in 0..100 {
+            parser
+                .push(&[0x42, 0xFF, 0xFF, 0x00, 0x21, 0xFE, 0x41, 0x42])
+                .unwrap();
+            assert!(parser.packets.len() <= 4);
+        }
+        assert_eq!(parser.dropped_packets(), 99 - 4);
+        let mut count = 0;
+        while let Some(packet) = parser.pop_packet() {
+            assert!(packet.was_truncated());
+            count += 1;
+        }
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn parser_cea608_after_cea708_policy() {
+        test_init_log();
+        let data = [
+            0x44, 0xFF, 0xFC, 0x80, 0x81, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00, 0xFD, 0x80, 0x82,
+        ];
+        for policy in [
+            Cea608AfterCea708Policy::Error,
+            Cea608AfterCea708Policy::Ignore,
+            Cea608AfterCea708Policy::Accept,
+        ] {
+            let mut parser = CCDataParser::new();
+            parser.handle_cea608();
+            assert_eq!(
+                parser.cea608_after_cea708_policy(),
+                Cea608AfterCea708Policy::Error
+            );
+            parser.set_cea608_after_cea708_policy(policy);
+            let ret = parser.push(&data);
+            assert_eq!(parser.cea608_after_cea708_count(), 1);
+            match policy {
+                Cea608AfterCea708Policy::Error => {
+                    assert_eq!(ret, Err(ParserError::Cea608AfterCea708 { byte_pos: 9 }));
+                    assert!(parser.pop_packet().is_none());
+                    continue;
+                }
+                Cea608AfterCea708Policy::Ignore => {
+                    ret.unwrap();
+                    assert_eq!(parser.cea608_field1(), [(0x80, 0x81)]);
+                    assert_eq!(parser.cea608_field2(), []);
+                }
+                Cea608AfterCea708Policy::Accept => {
+                    ret.unwrap();
+                    assert_eq!(parser.cea608_field1(), [(0x80, 0x81)]);
+                    assert_eq!(parser.cea608_field2(), [(0x80, 0x82)]);
+                }
+            }
+            let packet = parser.pop_packet().unwrap();
+            assert_eq!(packet.services()[0].codes(), [tables::Code::LatinCapitalA]);
+
+            // push_triple() behaves the same
+            let mut parser = CCDataParser::new();
+            parser.set_cea608_after_cea708_policy(policy);
+            for triple in data[2..].chunks_exact(3) {
+                parser
+                    .push_triple(
+                        triple[0] & 0x04 > 0,
+                        triple[0] & 0x03,
+                        [triple[1], triple[2]],
+                    )
+                    .unwrap();
+            }
+            parser.end_of_frame().unwrap();
+            assert!(parser.pop_packet().is_some());
+        }
+    }
+
+    #[test]
+    fn parser_reset_sequence_tracking() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        // first stream: 0, 1 then the start of a packet with sequence number 2
+        parser
+            .push(&[0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00])
+            .unwrap();
+        parser
+            .push(&[0x42, 0xFF, 0xFF, 0x42, 0x21, 0xFE, 0x41, 0x00])
+            .unwrap();
+        parser.push(&[0x41, 0xFF, 0xFF, 0x82, 0x21]).unwrap();
+        assert_eq!(parser.pop_packet().unwrap().sequence_no(), 0);
+        assert_eq!(parser.pop_packet().unwrap().sequence_no(), 1);
+
+        // second stream continues the pending packet and then jumps to 0
+        parser.reset_sequence_tracking();
+        parser.push(&[0x41, 0xFF, 0xFE, 0x41, 0x00]).unwrap();
+        parser
+            .push(&[0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00])
+            .unwrap();
+        assert_eq!(parser.pop_packet().unwrap().sequence_no(), 2);
+        parser.reset_sequence_tracking();
+        assert_eq!(parser.pop_packet().unwrap().sequence_no(), 0);
+        assert_eq!(parser.sequence_discontinuities(), 0);
+
+        // without a reset the jump is counted
+        parser
+            .push(&[0x42, 0xFF, 0xFF, 0xC2, 0x21, 0xFE, 0x41, 0x00])
+            .unwrap();
+        assert_eq!(parser.pop_packet().unwrap().sequence_no(), 3);
+        assert_eq!(parser.sequence_discontinuities(), 1);
+    }
+
+    #[test]
+    fn parser_cea608_queue() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        parser.push(&[0x41, 0xFF, 0xFC, 0x80, 0x81]).unwrap();
+        assert_eq!(parser.pop_cea608(), None);
+
+        parser.handle_cea608();
+        parser
+            .push(&[0x42, 0xFF, 0xFC, 0x80, 0x81, 0xFD, 0x82, 0x83])
+            .unwrap();
+        parser
+            .push(&[
+                0x43, 0xFF, 0xFC, 0x84, 0x85, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00,
+            ])
+            .unwrap();
+        // only the last cc_data is available here
+        assert_eq!(parser.cea608(), Some(&[Cea608::Field1(0x84, 0x85)][..]));
+        let mut cea608 = vec![];
+        while let Some(pair) = parser.pop_cea608() {
+            cea608.push(pair);
+        }
+        assert_eq!(
+            cea608,
+            [
+                Cea608::Field1(0x80, 0x81),
+                Cea608::Field2(0x82, 0x83),
+                Cea608::Field1(0x84, 0x85)
+            ]
+        );
+        assert!(parser.pop_packet().is_some());
+    }
+
+    #[test]
+    fn parser_dedupe_repeated() {
+        test_init_log();
+        for (dedupe, test_data) in [false, true].into_iter().flat_map(|dedupe| {
+            TEST_CC_DATA
+                .iter()
+                .map(move |test_data| (dedupe, test_data))
+        }) {
+            let mut parser = CCDataParser::new();
+            parser.handle_cea608();
+            assert!(!parser.dedupe_repeated());
+            parser.set_dedupe_repeated(dedupe);
+            let mut packets = vec![];
+            let mut cea608 = vec![];
+            for data in test_data.cc_data.iter() {
+                for _ in 0..2 {
+                    parser.push(data).unwrap();
+                    while let Some(packet) = parser.pop_packet() {
+                        packets.push(packet);
+                    }
+                    while let Some(pair) = parser.pop_cea608() {
+                        cea608.push(pair);
+                    }
+                }
+            }
+            let n_cc_data = test_data.cc_data.len();
+            if dedupe {
+                assert_eq!(parser.repeated_frames(), n_cc_data);
+                assert_eq!(packets.len(), test_data.packets.len());
+                for (packet, expected) in packets.iter().zip(test_data.packets.iter()) {
+                    assert_eq!(packet.sequence_no(), expected.sequence_no);
+                }
+                assert_eq!(parser.sequence_discontinuities(), 0);
+            } else {
+                assert_eq!(parser.repeated_frames(), 0);
+                assert!(packets.len() >= test_data.packets.len());
+            }
+            let expected_cea608 = test_data.cea608.iter().map(|c| c.len()).sum::<usize>();
+            if dedupe {
+                assert_eq!(cea608.len(), expected_cea608);
+            } else {
+                assert_eq!(cea608.len(), 2 * expected_cea608);
+            }
+        }
+    }
+
+    #[test]
+    fn parser_multiple_packets_order() {
+        test_init_log();
+        for (first, second) in [(1u8, 2u8), (3, 0)] {
+            let mut parser = CCDataParser::new();
+            let outcome = parser
+                .push(&[
+                    0x44,
+                    0xFF,
                     0xFF,
-                    0xC0 | 0x11,
-                    0x20 | 0x1F,
+                    first << 6 | 0x02,
+                    0x21,
                     0xFE,
                     0x41,
-                    0x42,
-                    0xFE,
-                    0x43,
-                    0x44,
-                    0xFE,
-                    0x45,
-                    0x46,
-                    0xFE,
-                    0x47,
-                    0x48,
-                    0xFE,
-                    0x49,
-                    0x4A,
-                    0xFE,
-                    0x4B,
-                    0x4C,
+                    0x00,
+                    0xFF,
+                    second << 6 | 0x02,
+                    0x21,
                     0xFE,
-                    0x4D,
-                    0x4E,
+                    0x42,
+                    0x00,
+                ])
+                .unwrap();
+            assert_eq!(outcome.packets_ready, 2);
+            // packets are returned in the order they appear in the cc_data
+            let packet = parser.pop_packet().unwrap();
+            assert_eq!(packet.sequence_no(), first);
+            assert_eq!(packet.services()[0].text(), "A");
+            let packet = parser.pop_packet().unwrap();
+            assert_eq!(packet.sequence_no(), second);
+            assert_eq!(packet.services()[0].text(), "B");
+            assert!(parser.pop_packet().is_none());
+        }
+    }
+
+    #[test]
+    fn service_demux_concatenates_services() {
+        test_init_log();
+        for (i, test_data) in TEST_CC_DATA.iter().enumerate() {
+            log::info!("demuxing {i}: {test_data:?}");
+            let mut parser = CCDataParser::new();
+            if !test_data.cea608.is_empty() {
+                parser.handle_cea608();
+            }
+            let mut demux = ServiceDemux::new();
+            for data in test_data.cc_data.iter() {
+                parser.push(data).unwrap();
+                demux.push_from_parser(&mut parser);
+            }
+            let mut expected: Vec<(u8, Vec<tables::Code>)> = vec![];
+            for service in test_data.packets.iter().flat_map(|p| p.services.iter()) {
+                match expected
+                    .iter_mut()
+                    .find(|(no, _)| *no == service.service_no)
+                {
+                    Some((_, codes)) => codes.extend_from_slice(service.codes),
+                    None => expected.push((service.service_no, service.codes.to_vec())),
+                }
+            }
+            expected.sort_by_key(|(service_no, _)| *service_no);
+            assert_eq!(
+                demux.services_with_data().collect::<Vec<_>>(),
+                expected.iter().map(|(no, _)| *no).collect::<Vec<_>>()
+            );
+            for (service_no, codes) in expected {
+                assert_eq!(demux.pop(service_no), Some(codes));
+                assert_eq!(demux.pop(service_no), None);
+            }
+            assert!(demux.services_with_data().next().is_none());
+        }
+    }
+
+    #[test]
+    fn service_demux_extended_service() {
+        test_init_log();
+        let mut demux = ServiceDemux::new();
+        for (seq_no, code) in [tables::Code::LatinCapitalA, tables::Code::LatinCapitalB]
+            .into_iter()
+            .enumerate()
+        {
+            let mut packet = DTVCCPacket::new(seq_no as u8);
+            for service_no in [63, 7, 1] {
+                let mut service = Service::new(service_no);
+                service.push_code(&code).unwrap();
+                packet.push_service(service).unwrap();
+            }
+            let mut written = vec![];
+            packet.write(&mut written).unwrap();
+            demux.push_packet(DTVCCPacket::parse(&written).unwrap());
+        }
+        assert_eq!(demux.services_with_data().collect::<Vec<_>>(), [1, 7, 63]);
+        let expected = vec![tables::Code::LatinCapitalA, tables::Code::LatinCapitalB];
+        assert_eq!(demux.pop(7), Some(expected.clone()));
+        assert_eq!(demux.services_with_data().collect::<Vec<_>>(), [1, 63]);
+        assert_eq!(demux.pop(64), None);
+        demux.flush();
+        assert_eq!(demux.pop(63), None);
+        assert!(demux.services_with_data().next().is_none());
+    }
+
+    #[test]
+    fn parser_flush_keeps_settings() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        parser.handle_cea608();
+        parser.set_strict(true);
+        parser.set_lenient_cc_count(true);
+        parser.set_cea608_after_cea708_policy(Cea608AfterCea708Policy::Accept);
+        parser.set_dedupe_repeated(true);
+        parser.set_accumulate_cea608(true);
+        parser.set_max_pending_bytes(Some(16));
+        parser.set_max_queued_packets(Some(4));
+        // cc_count of 1 followed by a zero byte
+        parser.push(&[0x41, 0xFF, 0xFC, 0x80, 0x81, 0x00]).unwrap();
+        // the start of a DTVCCPacket
+        parser.push(&[0x41, 0xFF, 0xFF, 0x02, 0x21]).unwrap();
+        parser.push_stream(&[0x41]).unwrap();
+        assert_eq!(parser.cc_count_mismatches(), 1);
+
+        parser.flush();
+        assert!(parser.strict());
+        assert!(parser.lenient_cc_count());
+        assert_eq!(
+            parser.cea608_after_cea708_policy(),
+            Cea608AfterCea708Policy::Accept
+        );
+        assert!(parser.dedupe_repeated());
+        assert_eq!(parser.max_pending_bytes(), Some(16));
+        assert_eq!(parser.max_queued_packets(), Some(4));
+        assert_eq!(parser.cc_count_mismatches(), 1);
+        assert_eq!(parser.cea608(), Some(&[][..]));
+        assert_eq!(parser.pop_cea608(), None);
+        assert!(parser.pop_packet().is_none());
+
+        // accumulating CEA-608 is still enabled and the incomplete cc_data from push_stream()
+        // has been removed
+        parser.push_stream(&[0x41, 0xFF, 0xFC, 0x82, 0x83]).unwrap();
+        parser.push(&[0x41, 0xFF, 0xFC, 0x84, 0x85]).unwrap();
+        assert_eq!(
+            parser.take_cea608(),
+            [Cea608::Field1(0x82, 0x83), Cea608::Field1(0x84, 0x85)]
+        );
+        // the incomplete DTVCCPacket has been removed
+        parser.push(&[0x41, 0xFF, 0xFE, 0x41, 0x00]).unwrap();
+        assert!(parser.pop_packet().is_none());
+    }
+
+    #[test]
+    fn parser_last_header() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        parser.push(TEST_CC_DATA[1].cc_data[0]).unwrap();
+        assert_eq!(parser.last_cc_count(), Some(2));
+        assert_eq!(parser.last_process_flag(), Some(true));
+
+        // process_cc_data_flag not set
+        parser.push(&[0x81, 0xFF, 0xFC, 0x80, 0x80]).unwrap();
+        assert_eq!(parser.last_cc_count(), Some(1));
+        assert_eq!(parser.last_process_flag(), Some(false));
+
+        parser.push(&[0xC0]).unwrap();
+        assert_eq!(parser.last_cc_count(), None);
+        assert_eq!(parser.last_process_flag(), None);
+
+        parser.push(&[0xC0, 0xFF]).unwrap();
+        assert_eq!(parser.last_cc_count(), Some(0));
+
+        // no header
+        parser.push_triples(&[0xFC, 0x80, 0x80]).unwrap();
+        assert_eq!(parser.last_cc_count(), None);
+    }
+
+    #[test]
+    fn parser_finish() {
+        test_init_log();
+        // simple packet that will span two outputs
+        let test_data = &WRITE_CC_DATA[5];
+        let expected = &test_data.packets[0];
+        let mut parser = CCDataParser::new();
+        parser.push(test_data.cc_data[0]).unwrap();
+        assert!(parser.pop_packet().is_none());
+        let packet = parser.finish().unwrap();
+        assert!(packet.was_truncated());
+        assert_eq!(packet.sequence_no(), expected.sequence_no);
+        let service = &packet.services()[0];
+        assert_eq!(service.number(), expected.services[0].service_no);
+        assert_eq!(service.codes().len(), 16);
+        assert_eq!(service.codes(), &expected.services[0].codes[..16]);
+        // finish() resets the parser
+        assert!(parser.finish().is_none());
+        parser.push(test_data.cc_data[1]).unwrap();
+        assert!(parser.pop_packet().is_none());
+
+        // a complete stream has no pending packet
+        let mut parser = CCDataParser::new();
+        for cc_data in test_data.cc_data {
+            parser.push(cc_data).unwrap();
+        }
+        assert!(parser.finish().is_none());
+    }
+
+    #[test]
+    fn parser_finish_keeps_settings() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        parser.handle_cea608();
+        parser.set_strict(true);
+        parser.set_max_queued_packets(Some(1));
+        parser
+            .push(&[0x42, 0xFF, 0xFF, 0x03, 0x22, 0xFE, 0x41, 0x42])
+            .unwrap();
+        assert!(parser.finish().is_some());
+
+        assert!(parser.strict());
+        assert_eq!(parser.max_queued_packets(), Some(1));
+        parser.push(&[0x41, 0xFF, 0xFC, 0x80, 0x81]).unwrap();
+        assert_eq!(parser.cea608(), Some(&[Cea608::Field1(0x80, 0x81)][..]));
+        // a DTVCCPacket containing the reserved byte 0x93 in service 1
+        assert_eq!(
+            parser.push(&[0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x93, 0x00]),
+            Err(ParserError::ReservedByte {
+                byte: 0x93,
+                position: 2
+            })
+        );
+        // two complete DTVCCPackets with only the last one kept
+        parser
+            .push(&[
+                0x44, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00, 0xFF, 0x42, 0x21, 0xFE, 0x42, 0x00,
+            ])
+            .unwrap();
+        assert_eq!(parser.pop_packet().unwrap().sequence_no(), 1);
+        assert!(parser.pop_packet().is_none());
+    }
+
+    #[test]
+    fn parser_cea608_queue_bounded() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        parser.handle_cea608();
+        assert_eq!(
+            parser.max_queued_cea608(),
+            Some(CCDataParser::DEFAULT_MAX_QUEUED_CEA608)
+        );
+        // only the last cc_data is retrieved, the queue is never used
+        for i in 0..200u8 {
+            parser.push(&[0x41, 0xFF, 0xFC, 0x80, i]).unwrap();
+            assert_eq!(parser.cea608(), Some(&[Cea608::Field1(0x80, i)][..]));
+        }
+        assert_eq!(
+            parser.cea608_queue.len(),
+            CCDataParser::DEFAULT_MAX_QUEUED_CEA608
+        );
+        assert_eq!(
+            parser.dropped_cea608(),
+            200 - CCDataParser::DEFAULT_MAX_QUEUED_CEA608
+        );
+        // the newest byte pairs are kept
+        assert_eq!(
+            parser.pop_cea608(),
+            Some(Cea608::Field1(
+                0x80,
+                (200 - CCDataParser::DEFAULT_MAX_QUEUED_CEA608) as u8
+            ))
+        );
+
+        parser.set_max_queued_cea608(None);
+        for i in 0..200u8 {
+            parser.push(&[0x41, 0xFF, 0xFD, 0x80, i]).unwrap();
+        }
+        assert_eq!(
+            parser.cea608_queue.len(),
+            CCDataParser::DEFAULT_MAX_QUEUED_CEA608 - 1 + 200
+        );
+    }
+
+    #[test]
+    fn parser_default_limits() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        assert_eq!(
+            parser.max_pending_bytes(),
+            Some(CCDataParser::DEFAULT_MAX_PENDING_BYTES)
+        );
+        assert_eq!(
+            parser.max_queued_packets(),
+            Some(CCDataParser::DEFAULT_MAX_QUEUED_PACKETS)
+        );
+        // packets are never retrieved
+        for i in 0..100u8 {
+            parser
+                .push(&[
+                    0x42,
+                    0xFF,
+                    0xFF,
+                    (i & 0x3) << 6 | 0x02,
+                    0x21,
                     0xFE,
-                    0x4F,
-                    0x50,
-                ],
+                    0x41,
+                    0x00,
+                ])
+                .unwrap();
+            assert!(parser.packets.len() <= CCDataParser::DEFAULT_MAX_QUEUED_PACKETS);
+        }
+        assert_eq!(
+            parser.dropped_packets(),
+            100 - CCDataParser::DEFAULT_MAX_QUEUED_PACKETS
+        );
+        // the newest packets are kept
+        assert_eq!(
+            parser.pop_packet().unwrap().sequence_no() as usize,
+            (100 - CCDataParser::DEFAULT_MAX_QUEUED_PACKETS) & 0x3
+        );
+    }
+
+    #[test]
+    fn parser_unprocessed_frames() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        let mut cc_data = TEST_CC_DATA[0].cc_data[0].to_vec();
+        // clear the process_cc_data_flag
+        cc_data[0] &= !0x40;
+        let outcome = parser.push(&cc_data).unwrap();
+        assert_eq!(outcome.consumed, 0);
+        assert_eq!(parser.unprocessed_frames(), 1);
+        assert_eq!(parser.empty_frames(), 0);
+        assert!(parser.pop_packet().is_none());
+
+        let outcome = parser.push(&[0xC0, 0xFF]).unwrap();
+        assert_eq!(outcome.consumed, 0);
+        assert_eq!(parser.unprocessed_frames(), 1);
+        assert_eq!(parser.empty_frames(), 1);
+
+        parser.push(TEST_CC_DATA[0].cc_data[0]).unwrap();
+        assert_eq!(parser.unprocessed_frames(), 1);
+        assert_eq!(parser.empty_frames(), 1);
+        assert!(parser.pop_packet().is_some());
+    }
+
+    #[test]
+    fn parser_drain_packets() {
+        test_init_log();
+        for test_data in TEST_CC_DATA.iter() {
+            let mut popping = CCDataParser::new();
+            let mut draining = CCDataParser::new();
+            let mut popped = vec![];
+            let mut drained = vec![];
+            for data in test_data.cc_data.iter() {
+                popping.push(data).unwrap();
+                draining.push(data).unwrap();
+            }
+            while let Some(packet) = popping.pop_packet() {
+                popped.push(packet);
+            }
+            drained.extend(draining.drain_packets());
+            assert_eq!(popped.len(), test_data.packets.len());
+            assert_eq!(drained, popped);
+            assert_eq!(
+                draining.sequence_discontinuities(),
+                popping.sequence_discontinuities()
+            );
+        }
+
+        // partially consuming keeps the remaining packets
+        let mut parser = CCDataParser::new();
+        parser
+            .push(&[
+                0x46, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00, 0xFF, 0x42, 0x21, 0xFE, 0x42, 0x00,
+                0xFF, 0x82, 0x21, 0xFE, 0x43, 0x00,
+            ])
+            .unwrap();
+        assert_eq!(parser.drain_packets().next().unwrap().sequence_no(), 0);
+        assert_eq!(parser.pop_packet().unwrap().sequence_no(), 1);
+        assert_eq!(parser.drain_packets().count(), 1);
+    }
+
+    #[test]
+    fn parser_lenient_cc_count() {
+        test_init_log();
+        let padded = [0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00, 0x00, 0x00];
+        // truncated in the middle of the third triple
+        let truncated = [0x43, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00, 0xFE];
+
+        let mut parser = CCDataParser::new();
+        assert!(!parser.lenient_cc_count());
+        assert_eq!(
+            parser.push(&padded),
+            Err(ParserError::LengthMismatch {
+                expected: 7,
+                actual: 10
+            })
+        );
+        assert_eq!(
+            parser.push(&truncated),
+            Err(ParserError::LengthMismatch {
+                expected: 10,
+                actual: 9
+            })
+        );
+        assert!(parser.pop_packet().is_none());
+        assert_eq!(parser.cc_count_mismatches(), 0);
+
+        parser.set_lenient_cc_count(true);
+        let outcome = parser.push(&padded).unwrap();
+        assert_eq!(outcome.consumed, 8);
+        assert_eq!(parser.cc_count_mismatches(), 1);
+        let packet = parser.pop_packet().unwrap();
+        assert_eq!(packet.services()[0].codes(), [tables::Code::LatinCapitalA]);
+
+        let outcome = parser.push(&truncated).unwrap();
+        assert_eq!(outcome.consumed, 8);
+        assert_eq!(parser.cc_count_mismatches(), 2);
+        let packet = parser.pop_packet().unwrap();
+        assert_eq!(packet.services()[0].codes(), [tables::Code::LatinCapitalA]);
+        assert!(parser.pop_packet().is_none());
+    }
+
+    #[test]
+    fn parser_sequence_discontinuities() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        // 2 is skipped, 3 -> 0 wraps around
+        for seq_no in [0, 1, 3, 0, 1, 1] {
+            parser
+                .push(&[0x42, 0xFF, 0xFF, seq_no << 6 | 0x02, 0x21, 0xFE, 0x41, 0x00])
+                .unwrap();
+        }
+        let mut seq_nos = vec![];
+        let mut discontinuities = vec![];
+        while let Some(packet) = parser.pop_packet() {
+            seq_nos.push(packet.sequence_no());
+            discontinuities.push(parser.sequence_discontinuities());
+        }
+        assert_eq!(seq_nos, [0, 1, 3, 0, 1, 1]);
+        assert_eq!(discontinuities, [0, 0, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn parser_bounded_pending() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        parser.set_max_pending_bytes(Some(6));
+        // advertises a 127 byte packet
+        parser
+            .push(&[
+                0x43, 0xFF, 0xFF, 0x00, 0x3F, 0xFE, 0x41, 0x42, 0xFE, 0x43, 0x44,
+            ])
+            .unwrap();
+        assert_eq!(parser.pending_data.len(), 6);
+        assert_eq!(parser.dropped_pending_bytes(), 0);
+        let outcome = parser
+            .push(&[0x42, 0xFF, 0xFE, 0x45, 0x46, 0xFE, 0x47, 0x48])
+            .unwrap();
+        assert_eq!(outcome.pending, 0);
+        assert_eq!(parser.dropped_pending_bytes(), 10);
+        // data without a header is ignored until the next header
+        let outcome = parser
+            .push(&[0x42, 0xFF, 0xFE, 0x45, 0x46, 0xFE, 0x47, 0x48])
+            .unwrap();
+        assert_eq!(outcome.pending, 0);
+        parser
+            .push(&[0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00])
+            .unwrap();
+        assert_eq!(parser.pop_packet().unwrap().services()[0].text(), "A");
+        assert_eq!(parser.dropped_pending_bytes(), 10);
+    }
+
+    #[test]
+    fn parser_bounded_long_running() {
+        test_init_log();
+        // headers advertising 127 byte packets that are never completed, each followed by a
+        // complete packet
+        let incomplete = [
+            0x4A, 0xFF, 0xFF, 0x00, 0x3F, 0xFE, 0x41, 0x42, 0xFE, 0x43, 0x44, 0xFE, 0x45, 0x46,
+            0xFE, 0x47, 0x48, 0xFE, 0x49, 0x4A, 0xFE, 0x4B, 0x4C, 0xFE, 0x4D, 0x4E, 0xFE, 0x4F,
+            0x50, 0xFE, 0x51, 0x52,
+        ];
+        let complete = [0x42, 0xFF, 0xFF, 0x02, 0x21, 0xFE, 0x41, 0x00];
+
+        let mut parser = CCDataParser::new();
+        parser.set_max_pending_bytes(Some(8));
+        parser.set_max_queued_packets(Some(16));
+        assert_eq!(parser.max_pending_bytes(), Some(8));
+        assert_eq!(parser.max_queued_packets(), Some(16));
+        for i in 0..1000 {
+            parser.push(&incomplete).unwrap();
+            assert!(parser.pending_data.len() <= 8);
+            parser.push(&complete).unwrap();
+            assert!(parser.packets.len() <= 16);
+            assert_eq!(parser.dropped_pending_bytes(), (i + 1) * 20);
+        }
+        assert_eq!(parser.dropped_packets(), 1000 - 16);
+        assert_eq!(parser.drain_packets().count(), 16);
+
+        // without limits the pending data is still bounded by the maximum packet size
+        let mut parser = CCDataParser::new();
+        parser.set_max_pending_bytes(None);
+        for _ in 0..1000 {
+            parser.push(&incomplete).unwrap();
+            assert!(parser.pending_data.len() <= 128);
+        }
+        assert_eq!(parser.dropped_pending_bytes(), 0);
+    }
+
+    #[test]
+    fn cc_data_reader() {
+        test_init_log();
+        for test_data in TEST_CC_DATA.iter() {
+            if !test_data.cea608.is_empty() {
+                continue;
+            }
+            let mut parser = CCDataParser::new();
+            let mut expected = vec![];
+            let mut triples = vec![];
+            for data in test_data.cc_data.iter() {
+                parser.push(data).unwrap();
+                while let Some(packet) = parser.pop_packet() {
+                    expected.push(packet);
+                }
+                triples.extend_from_slice(&data[2..]);
+            }
+            for cc_count in [1, 3, 20, 31] {
+                let mut reader = CCDataReader::new(std::io::Cursor::new(&triples));
+                reader.set_cc_count(cc_count);
+                let packets = reader.collect::<Result<Vec<_>, _>>().unwrap();
+                assert_eq!(packets, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn push_outcome_pending() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        // ignored without the process_cc_data_flag
+        assert_eq!(
+            parser.push(&[0x81, 0xFF, 0xFF, 0x02, 0x21]).unwrap(),
+            PushOutcome::default()
+        );
+        // a DTVCCPacket of 6 bytes (3 services bytes + header) split across two cc_data
+        let outcome = parser
+            .push(&[0x42, 0xFF, 0xFF, 0x03, 0x23, 0xFE, 0x41, 0x42])
+            .unwrap();
+        assert_eq!(
+            outcome,
+            PushOutcome {
+                consumed: 8,
+                pending: 4,
+                packets_ready: 0,
+            }
+        );
+        let outcome = parser
+            .push(&[0x42, 0xFF, 0xFE, 0x43, 0x00, 0xFF, 0x42, 0x21])
+            .unwrap();
+        assert_eq!(
+            outcome,
+            PushOutcome {
+                consumed: 8,
+                pending: 2,
+                packets_ready: 1,
+            }
+        );
+        let packet = parser.pop_packet().unwrap();
+        assert_eq!(packet.services()[0].text(), "ABC");
+    }
+
+    #[test]
+    fn to_bytes_matches_write() {
+        test_init_log();
+        for test_data in TEST_CC_DATA.iter() {
+            let mut parser = CCDataParser::new();
+            for data in test_data.cc_data.iter() {
+                parser.push(data).unwrap();
+                while let Some(packet) = parser.pop_packet() {
+                    let mut written = vec![];
+                    packet.write(&mut written).unwrap();
+                    assert_eq!(packet.to_bytes(), written);
+                    assert_eq!(Vec::<u8>::from(&packet), written);
+                    for service in packet.services() {
+                        let mut written = vec![];
+                        service.write(&mut written).unwrap();
+                        assert_eq!(service.to_bytes(), written);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cea608_fields() {
+        test_init_log();
+        let test_data = &TEST_CC_DATA[7];
+        let mut parser = CCDataParser::new();
+        parser.handle_cea608();
+        for (data, cea608) in test_data.cc_data.iter().zip(test_data.cea608.iter()) {
+            parser.push(data).unwrap();
+            let field1 = cea608
+                .iter()
+                .filter_map(|pair| match pair {
+                    Cea608::Field1(a, b) => Some((*a, *b)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let field2 = cea608
+                .iter()
+                .filter_map(|pair| match pair {
+                    Cea608::Field2(a, b) => Some((*a, *b)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert!(!field1.is_empty());
+            assert!(!field2.is_empty());
+            assert_eq!(parser.cea608_field1(), field1);
+            assert_eq!(parser.cea608_field2(), field2);
+        }
+        parser.push(&[0x41, 0xFF, 0xFA, 0x00, 0x00]).unwrap();
+        assert!(parser.cea608_field1().is_empty());
+        assert!(parser.cea608_field2().is_empty());
+    }
+
+    #[test]
+    fn cea608_accumulate() {
+        test_init_log();
+        let mut parser = CCDataParser::new();
+        parser.handle_cea608();
+        parser.set_accumulate_cea608(true);
+        parser
+            .push(&[0x42, 0xFF, 0xFC, 0x80, 0x81, 0xFD, 0x82, 0x83])
+            .unwrap();
+        parser.push(&[0x41, 0xFF, 0xFC, 0x84, 0x85]).unwrap();
+        assert_eq!(
+            parser.cea608(),
+            Some(
                 &[
-                    0x80 | 0x40 | 0x09,
-                    0xFF,
-                    0xFD,
-                    0x21,
-                    0x43,
-                    0xFE,
-                    0x51,
-                    0x52,
-                    0xFE,
-                    0x53,
-                    0x54,
-                    0xFE,
-                    0x55,
-                    0x56,
-                    0xFE,
-                    0x57,
-                    0x58,
-                    0xFE,
-                    0x59,
-                    0x5A,
-                    0xFE,
-                    0x61,
-                    0x62,
-                    0xFE,
-                    0x63,
-                    0x64,
-                    0xFE,
-                    0x65,
-                    0x0,
-                ],
+                    Cea608::Field1(0x80, 0x81),
+                    Cea608::Field2(0x82, 0x83),
+                    Cea608::Field1(0x84, 0x85)
+                ][..]
+            )
+        );
+        assert_eq!(
+            parser.take_cea608(),
+            [
+                Cea608::Field1(0x80, 0x81),
+                Cea608::Field2(0x82, 0x83),
+                Cea608::Field1(0x84, 0x85)
+            ]
+        );
+        assert_eq!(parser.cea608(), Some(&[][..]));
+
+        // the default only keeps the last cc_data
+        parser.set_accumulate_cea608(false);
+        parser.push(&[0x41, 0xFF, 0xFC, 0x80, 0x81]).unwrap();
+        parser.push(&[0x41, 0xFF, 0xFC, 0x84, 0x85]).unwrap();
+        assert_eq!(parser.take_cea608(), [Cea608::Field1(0x84, 0x85)]);
+    }
+
+    #[test]
+    fn cc_triples() {
+        test_init_log();
+        let cc_types = iter_cc_triples(TEST_CC_DATA[7].cc_data[1])
+            .unwrap()
+            .map(|triple| triple.cc_type)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cc_types,
+            [
+                CcType::Cea608Field1,
+                CcType::Cea608Field2,
+                CcType::DtvccData
+            ]
+        );
+        let cc_types = iter_cc_triples(TEST_CC_DATA[0].cc_data[0])
+            .unwrap()
+            .map(|triple| triple.cc_type)
+            .collect::<Vec<_>>();
+        assert_eq!(cc_types, [CcType::DtvccStart, CcType::DtvccData]);
+
+        // unset process_cc_data_flag
+        assert_eq!(
+            iter_cc_triples(&[0x01, 0xFF, 0xFC, 0x80, 0x81])
+                .unwrap()
+                .count(),
+            0
+        );
+        assert_eq!(
+            iter_cc_triples(&[0x42, 0xFF, 0xFC, 0x80, 0x81]).err(),
+            Some(ParserError::LengthMismatch {
+                expected: 8,
+                actual: 5
+            })
+        );
+        assert!(iter_cc_triples(&[0x40]).is_err());
+    }
+
+    #[test]
+    fn service_validate() {
+        test_init_log();
+        let define = |window_style_id, pen_style_id| {
+            tables::Code::DefineWindow(tables::DefineWindowArgs::new(
+                2,
+                0,
+                tables::Anchor::TopLeft,
+                false,
+                0,
+                0,
+                1,
+                9,
+                true,
+                true,
+                true,
+                window_style_id,
+                pen_style_id,
+            ))
+        };
+        let mut attrs = tables::SetWindowAttributesArgs::new(
+            tables::Justify::Left,
+            tables::Direction::LeftToRight,
+            tables::Direction::BottomToTop,
+            false,
+            tables::DisplayEffect::Snap,
+            tables::Direction::LeftToRight,
+            1,
+            tables::Color::BLACK,
+            tables::Opacity::Solid,
+            tables::BorderType::None,
+            tables::Color::BLACK,
+        );
+        let clean = Service::from_codes(
+            1,
+            [
+                define(1, 1),
+                tables::Code::SetWindowAttributes(attrs),
+                tables::Code::SetCurrentWindow2,
+                tables::Code::SetPenLocation(tables::SetPenLocationArgs::new(1, 9)),
+                tables::Code::LatinCapitalA,
             ],
-            packets: &[PacketData {
-                sequence_no: 3,
-                services: &[ServiceData {
-                    service_no: 1,
-                    codes: &[
-                        tables::Code::LatinCapitalA,
-                        tables::Code::LatinCapitalB,
-                        tables::Code::LatinCapitalC,
-                        tables::Code::LatinCapitalD,
-                        tables::Code::LatinCapitalE,
-                        tables::Code::LatinCapitalF,
-                        tables::Code::LatinCapitalG,
-                        tables::Code::LatinCapitalH,
-                        tables::Code::LatinCapitalI,
-                        tables::Code::LatinCapitalJ,
-                        tables::Code::LatinCapitalK,
-                        tables::Code::LatinCapitalL,
-                        tables::Code::LatinCapitalM,
-                        tables::Code::LatinCapitalN,
-                        tables::Code::LatinCapitalO,
-                        tables::Code::LatinCapitalP,
-                        tables::Code::LatinCapitalQ,
-                        tables::Code::LatinCapitalR,
-                        tables::Code::LatinCapitalS,
-                        tables::Code::LatinCapitalT,
-                        tables::Code::LatinCapitalU,
-                        tables::Code::LatinCapitalV,
-                        tables::Code::LatinCapitalW,
-                        tables::Code::LatinCapitalX,
-                        tables::Code::LatinCapitalY,
-                        tables::Code::LatinCapitalZ,
-                        tables::Code::LatinLowerA,
-                        tables::Code::LatinLowerB,
-                        tables::Code::LatinLowerC,
-                        tables::Code::LatinLowerD,
-                        tables::Code::LatinLowerE,
-                    ],
-                }],
-            }],
-            cea608: &[&[Cea608::Field1(0x20, 0x42), Cea608::Field2(0x21, 0x43)]],
-        },
-        // simple packet with multiple cea608 that will span two outputs
-        TestCCData {
-            framerate: Framerate::new(24, 1),
-            cc_data: &[
-                &[0x80 | 0x40 | 0x02, 0xFF, 0xFC, 0x20, 0x42, 0xFD, 0x21, 0x43],
-                &[0x80 | 0x40 | 0x02, 0xFF, 0xFC, 0x22, 0x44, 0xFD, 0x23, 0x45],
+        )
+        .unwrap();
+        assert!(clean.validate().is_empty());
+
+        attrs.effect_speed = 0;
+        let service = Service::from_codes(
+            1,
+            [
+                tables::Code::SetCurrentWindow2,
+                define(8, 9),
+                tables::Code::SetWindowAttributes(attrs),
+                tables::Code::SetPenLocation(tables::SetPenLocationArgs::new(2, 9)),
             ],
-            packets: &[PacketData {
-                sequence_no: 3,
-                services: &[],
-            }],
-            cea608: &[
-                &[Cea608::Field1(0x20, 0x42), Cea608::Field2(0x21, 0x43)],
-                &[Cea608::Field1(0x22, 0x44), Cea608::Field2(0x23, 0x45)],
+        )
+        .unwrap();
+        let issue = |code_index, severity, kind| ValidationIssue {
+            code_index,
+            severity,
+            kind,
+        };
+        assert_eq!(
+            service.validate(),
+            [
+                issue(
+                    0,
+                    ValidationSeverity::Warning,
+                    ValidationIssueKind::UndefinedWindow { window_id: 2 }
+                ),
+                issue(
+                    1,
+                    ValidationSeverity::Error,
+                    ValidationIssueKind::InvalidStyleId { style_id: 8 }
+                ),
+                issue(
+                    1,
+                    ValidationSeverity::Error,
+                    ValidationIssueKind::InvalidStyleId { style_id: 9 }
+                ),
+                issue(
+                    2,
+                    ValidationSeverity::Error,
+                    ValidationIssueKind::ZeroEffectSpeed
+                ),
+                issue(
+                    3,
+                    ValidationSeverity::Error,
+                    ValidationIssueKind::PenLocationOutOfBounds {
+                        row: 2,
+                        column: 9,
+                        row_count: 2,
+                        column_count: 10
+                    }
+                ),
+            ]
+        );
+
+        // the pen location is checked against the current window, not the last defined window
+        let define_window = |window_id, row_count, column_count| {
+            tables::Code::DefineWindow(tables::DefineWindowArgs::new(
+                window_id,
+                0,
+                tables::Anchor::TopLeft,
+                false,
+                0,
+                0,
+                row_count,
+                column_count,
+                true,
+                true,
+                true,
+                1,
+                1,
+            ))
+        };
+        let service = Service::from_codes(
+            1,
+            [
+                define_window(0, 9, 30),
+                define_window(1, 0, 4),
+                tables::Code::SetCurrentWindow0,
+                tables::Code::SetPenLocation(tables::SetPenLocationArgs::new(5, 20)),
+                tables::Code::SetCurrentWindow1,
+                tables::Code::SetPenLocation(tables::SetPenLocationArgs::new(1, 2)),
+                tables::Code::SetCurrentWindow3,
+                tables::Code::SetPenLocation(tables::SetPenLocationArgs::new(11, 40)),
             ],
-        },
-    ];
+        )
+        .unwrap();
+        assert_eq!(
+            service.validate(),
+            [
+                issue(
+                    5,
+                    ValidationSeverity::Error,
+                    ValidationIssueKind::PenLocationOutOfBounds {
+                        row: 1,
+                        column: 2,
+                        row_count: 1,
+                        column_count: 5
+                    }
+                ),
+                issue(
+                    6,
+                    ValidationSeverity::Warning,
+                    ValidationIssueKind::UndefinedWindow { window_id: 3 }
+                ),
+            ]
+        );
+
+        // the largest possible window sizes do not overflow
+        let service = Service::from_codes(
+            1,
+            [
+                define_window(0, 255, 0),
+                tables::Code::SetPenLocation(tables::SetPenLocationArgs::new(1, 1)),
+                define_window(1, 0, 255),
+                tables::Code::SetPenLocation(tables::SetPenLocationArgs::new(1, 1)),
+                define_window(2, 255, 255),
+                tables::Code::SetPenLocation(tables::SetPenLocationArgs::new(255, 255)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            service.validate(),
+            [
+                issue(
+                    1,
+                    ValidationSeverity::Error,
+                    ValidationIssueKind::PenLocationOutOfBounds {
+                        row: 1,
+                        column: 1,
+                        row_count: 256,
+                        column_count: 1
+                    }
+                ),
+                issue(
+                    3,
+                    ValidationSeverity::Error,
+                    ValidationIssueKind::PenLocationOutOfBounds {
+                        row: 1,
+                        column: 1,
+                        row_count: 1,
+                        column_count: 256
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn service_push_all() {
+        test_init_log();
+        let one = tables::Code::LatinCapitalA;
+        let two = tables::Code::HideWindows(tables::WindowBits::ZERO);
+        let seven = tables::Code::DefineWindow(tables::DefineWindowArgs::new(
+            0,
+            0,
+            tables::Anchor::TopLeft,
+            false,
+            0,
+            0,
+            0,
+            0,
+            true,
+            true,
+            true,
+            1,
+            1,
+        ));
+        for (boundary, boundary_len) in [(&one, 1), (&two, 2), (&seven, 7)] {
+            // fill the service so that only 1 byte is free then try the boundary code
+            let mut codes = vec![one.clone(); 30];
+            codes.push(boundary.clone());
+            codes.push(one.clone());
+            let mut service = Service::new(1);
+            let remaining = service.push_all(&codes);
+            let n_pushed = service.codes().len();
+            assert_eq!(&codes[n_pushed..], remaining);
+            if boundary_len == 1 {
+                assert_eq!(n_pushed, 31);
+            } else {
+                assert_eq!(n_pushed, 30);
+                assert_eq!(remaining[0], *boundary);
+            }
+            assert!(service.codes_len() <= 31);
+
+            let mut next = Service::new(1);
+            assert!(next.push_all(remaining).is_empty());
+            assert_eq!(
+                [service.codes(), next.codes()].concat(),
+                codes,
+                "boundary length {boundary_len}"
+            );
+        }
+
+        let mut null = Service::new(0);
+        assert_eq!(null.push_all(&[one]).len(), 1);
+    }
+
+    #[test]
+    fn packet_semantic_eq() {
+        test_init_log();
+        let services = [
+            Service::from_codes(1, text_codes("Hello")).unwrap(),
+            Service::from_codes(2, text_codes("World")).unwrap(),
+        ];
+        let mut packet = DTVCCPacket::new(0);
+        let mut repeated = DTVCCPacket::new(1);
+        for service in services.iter() {
+            packet.push_service(service.clone()).unwrap();
+            repeated.push_service(service.clone()).unwrap();
+        }
+        assert_ne!(packet, repeated);
+        assert!(packet.semantic_eq(&repeated));
+        assert_eq!(packet.payload_hash(), repeated.payload_hash());
+
+        // service order is significant
+        let mut reordered = DTVCCPacket::new(0);
+        for service in services.iter().rev() {
+            reordered.push_service(service.clone()).unwrap();
+        }
+        assert!(!packet.semantic_eq(&reordered));
+        assert_ne!(packet.payload_hash(), reordered.payload_hash());
+
+        let mut different = DTVCCPacket::new(0);
+        different
+            .push_service(Service::from_codes(1, text_codes("Hellp")).unwrap())
+            .unwrap();
+        assert!(!packet.semantic_eq(&different));
+        assert_ne!(packet.payload_hash(), different.payload_hash());
+        assert!(DTVCCPacket::new(0).semantic_eq(&DTVCCPacket::new(3)));
+    }
+
+    #[test]
+    fn cea708_error() {
+        test_init_log();
+        fn add_text(data: &[u8], code_data: &[u8]) -> Result<Vec<u8>, Cea708Error> {
+            let mut packet = DTVCCPacket::parse(data)?;
+            let codes = tables::Code::from_data(code_data)?;
+            let mut service = Service::new(2);
+            for code in codes {
+                service.push_code(code)?;
+            }
+            packet.push_service(service)?;
+            Ok(packet.to_bytes())
+        }
+
+        let data = [0x02, 0x21, 0x41, 0x00];
+        assert_eq!(
+            add_text(&data, &[0x42]).unwrap(),
+            [0x03, 0x21, 0x41, 0x41, 0x42, 0x00]
+        );
+        assert!(matches!(
+            add_text(&[], &[0x42]),
+            Err(Cea708Error::Parser(ParserError::LengthMismatch { .. }))
+        ));
+        assert!(matches!(
+            add_text(&data, &[0x90]),
+            Err(Cea708Error::Code(tables::CodeError::UnexpectedEnd { .. }))
+        ));
+        assert_eq!(
+            add_text(&data, &[0x42; 32]),
+            Err(Cea708Error::Writer(WriterError::would_overflow(
+                1,
+                OverflowContext::Service { service_no: 2 }
+            )))
+        );
+    }
+
+    #[test]
+    fn service_referenced_windows() {
+        test_init_log();
+        let define = tables::Code::DefineWindow(tables::DefineWindowArgs::new(
+            2,
+            0,
+            tables::Anchor::TopLeft,
+            false,
+            0,
+            0,
+            0,
+            0,
+            true,
+            true,
+            true,
+            1,
+            1,
+        ));
+        let service = Service::from_codes(
+            1,
+            [
+                define,
+                tables::Code::ClearWindows(tables::WindowBits::ZERO | tables::WindowBits::ONE),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            service.referenced_windows(),
+            tables::WindowBits::ZERO | tables::WindowBits::ONE | tables::WindowBits::TWO
+        );
+
+        let text = Service::from_codes(2, text_codes("Hello")).unwrap();
+        assert_eq!(text.referenced_windows(), tables::WindowBits::NONE);
+
+        let mut packet = DTVCCPacket::new(0);
+        packet.push_service(text).unwrap();
+        assert_eq!(packet.referenced_windows(), tables::WindowBits::NONE);
+        packet.push_service(service).unwrap();
+        assert_eq!(
+            packet.referenced_windows(),
+            tables::WindowBits::ZERO | tables::WindowBits::ONE | tables::WindowBits::TWO
+        );
+    }
+
+    #[test]
+    fn service_would_fit() {
+        test_init_log();
+        let one = tables::Code::LatinCapitalA;
+        let two = tables::Code::HideWindows(tables::WindowBits::ZERO);
+        let seven = tables::Code::DefineWindow(tables::DefineWindowArgs::new(
+            0,
+            0,
+            tables::Anchor::TopLeft,
+            false,
+            0,
+            0,
+            0,
+            0,
+            true,
+            true,
+            true,
+            1,
+            1,
+        ));
+        let mut service = Service::new(1);
+        for _ in 0..24 {
+            service.push_code(&one).unwrap();
+        }
+        assert_eq!(service.codes_len(), 24);
+        assert_eq!(service.free_space(), 7);
+        assert!(service.would_fit(&seven));
+        assert_eq!(service.remaining_code_capacity_for(&seven), 1);
+        assert_eq!(service.remaining_code_capacity_for(&two), 3);
+        assert_eq!(service.remaining_code_capacity_for(&one), 7);
+
+        service.push_code(&one).unwrap();
+        assert!(!service.would_fit(&seven));
+        assert_eq!(service.remaining_code_capacity_for(&seven), 0);
+        assert!(service.push_code(&seven).is_err());
+        assert_eq!(service.remaining_code_capacity_for(&two), 3);
+
+        for _ in 0..5 {
+            service.push_code(&one).unwrap();
+        }
+        assert_eq!(service.codes_len(), 30);
+        assert!(service.would_fit(&one));
+        assert!(!service.would_fit(&two));
+        assert_eq!(service.remaining_code_capacity_for(&two), 0);
+        service.push_code(&one).unwrap();
+        assert!(!service.would_fit(&one));
+        assert_eq!(service.remaining_code_capacity_for(&one), 0);
+    }
+
+    #[test]
+    fn service_with_capacity() {
+        test_init_log();
+        let mut service = Service::new(1);
+        let mut with_capacity = Service::with_capacity(1, 31);
+        assert!(with_capacity.capacity() >= 31);
+        assert_eq!(service, with_capacity);
+        for _ in 0..31 {
+            service.push_code(&tables::Code::LatinCapitalA).unwrap();
+            with_capacity
+                .push_code(&tables::Code::LatinCapitalA)
+                .unwrap();
+        }
+        assert_eq!(service, with_capacity);
+        assert_eq!(service.len(), with_capacity.len());
+        assert_eq!(service.free_space(), with_capacity.free_space());
+        assert_eq!(
+            with_capacity.push_code(&tables::Code::LatinCapitalA),
+            service.push_code(&tables::Code::LatinCapitalA)
+        );
+    }
+
+    #[test]
+    fn service_truncate() {
+        test_init_log();
+        let mut service = Service::new(1);
+        let spl = tables::Code::SetPenLocation(tables::SetPenLocationArgs::new(1, 1));
+        for _ in 0..10 {
+            service.push_code(&spl).unwrap();
+        }
+        service.push_code(&tables::Code::LatinCapitalA).unwrap();
+        assert_eq!(service.free_space(), 0);
+        assert_eq!(service.len(), 32);
+
+        service.truncate(8);
+        assert_eq!(service.free_space(), 7);
+        assert_eq!(service.len(), 25);
+        service.push_code(&spl).unwrap();
+        service.push_code(&spl).unwrap();
+        assert_eq!(service.free_space(), 1);
+
+        assert_eq!(service.pop_code(), Some(spl.clone()));
+        assert_eq!(service.free_space(), 4);
+        assert_eq!(service.remove_code(0), Some(spl));
+        assert_eq!(service.free_space(), 7);
+
+        service.clear();
+        assert_eq!(service.free_space(), 31);
+        assert_eq!(service.len(), 0);
+        assert!(service.is_empty());
+    }
+
+    #[test]
+    fn service_collect() {
+        test_init_log();
+        let codes = [
+            tables::Code::LatinCapitalA,
+            tables::Code::SetPenLocation(tables::SetPenLocationArgs::new(1, 1)),
+            tables::Code::LatinCapitalB,
+        ];
+        let service: Service = codes.iter().cloned().collect();
+        assert_eq!(service.number(), 1);
+        assert_eq!(service.codes(), codes);
+
+        // 6 * 5 bytes + 1 byte and the next 3 byte code does not fit
+        let service: Service = codes.iter().cloned().cycle().take(100).collect();
+        assert_eq!(service.codes().len(), 19);
+        assert_eq!(service.free_space(), 0);
+    }
+
+    #[test]
+    fn service_extend() {
+        test_init_log();
+        let mut service = Service::new(1);
+        service.extend(std::iter::repeat(tables::Code::LatinCapitalA).take(29));
+        assert_eq!(service.free_space(), 2);
+        // the 3 byte code does not fit so it and everything after it is dropped
+        service.extend([
+            tables::Code::SetPenLocation(tables::SetPenLocationArgs::new(1, 1)),
+            tables::Code::LatinCapitalB,
+        ]);
+        assert_eq!(service.free_space(), 2);
+        service.extend([tables::Code::LatinCapitalB, tables::Code::LatinCapitalC]);
+        assert_eq!(service.free_space(), 0);
+        assert_eq!(
+            &service.codes()[29..],
+            [tables::Code::LatinCapitalB, tables::Code::LatinCapitalC]
+        );
+
+        assert_eq!(
+            Service::from_codes(1, std::iter::repeat(tables::Code::LatinCapitalA).take(32)),
+            Err(WriterError::would_overflow(
+                1,
+                OverflowContext::Service { service_no: 1 }
+            ))
+        );
+    }
 
     #[test]
-    fn packet_write_cc_data() {
+    fn packet_display() {
         test_init_log();
-        for test_data in WRITE_CC_DATA.iter() {
-            log::info!("writing {test_data:?}");
-            let mut packet_iter = test_data.packets.iter();
-            let mut cea608_iter = test_data.cea608.iter();
+        let mut packet = DTVCCPacket::new(3);
+        let mut service = Service::new(1);
+        service
+            .push_code(tables::Code::SetPenLocation(
+                tables::SetPenLocationArgs::new(1, 2),
+            ))
+            .unwrap();
+        service.push_code(tables::Code::LatinCapitalA).unwrap();
+        service.push_code(tables::Code::Space).unwrap();
+        packet.push_service(service).unwrap();
+        let mut service = Service::new(9);
+        service
+            .push_code(tables::Code::Ext1(tables::Ext1::TradeMarkSign))
+            .unwrap();
+        service.push_code(tables::Code::ETX).unwrap();
+        packet.push_service(service).unwrap();
+
+        assert_eq!(
+            packet.to_string(),
+            "DTVCCPacket seq:3 len:12
+  Service 1 len:6
+    SetPenLocation
+    'A'
+    ' '
+  Service 9 len:5
+    '™'
+    End of Text
+"
+        );
+        assert_eq!(
+            format!("{packet:#}"),
+            "DTVCCPacket seq:3 len:12
+  Service 1 len:6
+    SetPenLocation [92 01 02]
+    'A' [41]
+    ' ' [20]
+  Service 9 len:5
+    '™' [10 39]
+    End of Text [03]
+"
+        );
+    }
+
+    #[test]
+    fn service_push_args() {
+        test_init_log();
+        let args = tables::SetPenColorArgs::new(
+            tables::Color::WHITE,
+            tables::Opacity::Solid,
+            tables::Color::BLACK,
+            tables::Opacity::Translucent,
+            tables::Color::RED,
+        );
+        let mut service = Service::new(1);
+        service.push_code(args).unwrap();
+        service.push_code(tables::Ext1::TradeMarkSign).unwrap();
+        assert_eq!(
+            service.codes(),
+            [
+                tables::Code::SetPenColor(args),
+                tables::Code::Ext1(tables::Ext1::TradeMarkSign)
+            ]
+        );
+    }
+
+    #[test]
+    fn packet_service_text() {
+        test_init_log();
+        let mut service = Service::new(1);
+        service
+            .push_code(tables::DefineWindowArgs::new(
+                0,
+                0,
+                tables::Anchor::BottomMiddle,
+                true,
+                100,
+                50,
+                2,
+                32,
+                true,
+                true,
+                true,
+                1,
+                1,
+            ))
+            .unwrap();
+        service.push_code(tables::Code::SetCurrentWindow0).unwrap();
+        for c in "Hi".chars() {
+            service
+                .push_code(tables::Code::from_char(c).unwrap())
+                .unwrap();
+        }
+        service.push_code(tables::Code::CR).unwrap();
+        service.push_code(tables::Code::ETX).unwrap();
+        service
+            .push_code(tables::Code::Ext1(tables::Ext1::HorizontalElipses))
+            .unwrap();
+        service.push_code(tables::Code::P16(0x4E2D)).unwrap();
+        let mut packet = DTVCCPacket::new(0);
+        packet.push_service(service).unwrap();
+
+        let mut service = Service::new(2);
+        service
+            .push_code(tables::Code::ClearWindows(tables::WindowBits::ZERO))
+            .unwrap();
+        service.push_code(tables::Code::LatinLowerA).unwrap();
+        packet.push_service(service).unwrap();
+        let mut service = Service::new(2);
+        service.push_code(tables::Code::LatinLowerB).unwrap();
+        packet.push_service(service).unwrap();
+
+        assert_eq!(packet.service_text(1).as_deref(), Some("Hi\n…中"));
+        assert_eq!(packet.service_text(2).as_deref(), Some("ab"));
+        assert_eq!(packet.service_text(3), None);
+        assert_eq!(
+            packet.service_texts(),
+            [(1, "Hi\n…中".to_string()), (2, "ab".to_string())]
+        );
+        assert!(DTVCCPacket::new(0).service_texts().is_empty());
+    }
+
+    #[test]
+    fn service_iterate() {
+        test_init_log();
+        let codes = [
+            tables::Code::LatinCapitalA,
+            tables::Code::CR,
+            tables::Code::LatinCapitalB,
+        ];
+        let mut service = Service::new(1);
+        service.extend(codes.iter().cloned());
+
+        let mut borrowed = vec![];
+        for code in &service {
+            borrowed.push(code.clone());
+        }
+        assert_eq!(borrowed, codes);
+
+        let mut owned = vec![];
+        for code in service {
+            owned.push(code);
+        }
+        assert_eq!(owned, codes);
+    }
+
+    #[test]
+    fn packet_equality() {
+        test_init_log();
+        let build = |seq_no, code: &tables::Code| {
+            let mut service = Service::new(1);
+            service.push_code(code).unwrap();
+            let mut packet = DTVCCPacket::new(seq_no);
+            packet.push_service(service).unwrap();
+            packet
+        };
+        let packet = build(1, &tables::Code::LatinCapitalA);
+        assert_eq!(packet, build(1, &tables::Code::LatinCapitalA));
+        assert_ne!(packet, build(2, &tables::Code::LatinCapitalA));
+        assert_ne!(packet, build(1, &tables::Code::LatinCapitalB));
+
+        let mut written = vec![];
+        packet.write(&mut written).unwrap();
+        assert_eq!(DTVCCPacket::parse(&written).unwrap(), packet);
+
+        // padding is not part of the comparison
+        let mut padded = packet.clone();
+        padded.push_null_padding(8).unwrap();
+        assert_eq!(padded, packet);
+        let mut written = vec![];
+        padded.write(&mut written).unwrap();
+        assert_eq!(DTVCCPacket::parse(&written).unwrap(), packet);
+        // but is part of the written bytes
+        assert_ne!(padded.to_bytes(), packet.to_bytes());
+        let mut set = std::collections::HashSet::new();
+        set.insert(packet);
+        assert!(set.contains(&padded));
+    }
+
+    #[test]
+    fn packet_service_for_loop() {
+        test_init_log();
+        let mut packet = DTVCCPacket::new(0);
+        let mut service = Service::new(1);
+        service.push_code(&tables::Code::LatinCapitalA).unwrap();
+        service.push_code(&tables::Code::LatinCapitalB).unwrap();
+        packet.push_service(service).unwrap();
+        let mut service = Service::new(2);
+        service.push_code(&tables::Code::LatinCapitalC).unwrap();
+        packet.push_service(service).unwrap();
+
+        let mut numbers = vec![];
+        let mut codes = vec![];
+        for service in &packet {
+            numbers.push(service.number());
+            for code in service {
+                codes.push(code.clone());
+            }
+        }
+        assert_eq!(numbers, [1, 2]);
+        assert_eq!(
+            codes,
+            [
+                tables::Code::LatinCapitalA,
+                tables::Code::LatinCapitalB,
+                tables::Code::LatinCapitalC
+            ]
+        );
+    }
+
+    #[test]
+    fn packet_parse_lengths() {
+        test_init_log();
+        let exact = [0x02, 0x21, 0x41, 0x00];
+        let over = [0x02, 0x21, 0x41, 0x00, 0x00];
+        let under = [0x02, 0x21, 0x41];
+        let expected = DTVCCPacket::parse(&exact).unwrap();
+        assert!(!expected.was_truncated());
+
+        assert_eq!(DTVCCPacket::parse_exact(&exact), Ok(expected.clone()));
+        assert_eq!(
+            DTVCCPacket::parse_exact(&over),
+            Err(ParserError::LengthMismatch {
+                expected: 4,
+                actual: 5
+            })
+        );
+        assert_eq!(
+            DTVCCPacket::parse_exact(&under),
+            Err(ParserError::LengthMismatch {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            DTVCCPacket::parse_exact(&[]),
+            Err(ParserError::LengthMismatch {
+                expected: 1,
+                actual: 0
+            })
+        );
+
+        assert_eq!(DTVCCPacket::parse_minimum(&exact), Ok(expected.clone()));
+        assert_eq!(DTVCCPacket::parse_minimum(&over), Ok(expected));
+        assert_eq!(
+            DTVCCPacket::parse_minimum(&under),
+            Err(ParserError::LengthMismatch {
+                expected: 4,
+                actual: 3
+            })
+        );
+
+        // parse() accepts truncated data but not trailing data
+        assert!(DTVCCPacket::parse(&under).unwrap().was_truncated());
+        assert!(DTVCCPacket::parse(&over).is_err());
+    }
+
+    #[test]
+    fn packet_is_empty() {
+        test_init_log();
+        let mut packet = DTVCCPacket::new(0);
+        assert!(packet.is_empty());
+        assert_eq!(packet.len(), 0);
+        packet.push_service(service_with_len(1, 4)).unwrap();
+        assert!(!packet.is_empty());
+        let mut packet = DTVCCPacket::new(0);
+        packet.push_null_padding(2).unwrap();
+        assert!(!packet.is_empty());
+    }
+
+    #[test]
+    fn packet_services_by_value() {
+        test_init_log();
+        let mut packet = DTVCCPacket::new(0);
+        let mut service = Service::new(1);
+        service.push_code(&tables::Code::LatinCapitalA).unwrap();
+        packet.push_service(service).unwrap();
+        let mut service = Service::new(2);
+        service.push_code(&tables::Code::LatinCapitalB).unwrap();
+        service.push_code(&tables::Code::LatinCapitalC).unwrap();
+        packet.push_service(service).unwrap();
+
+        let mut services = vec![];
+        let mut codes = vec![];
+        for service in packet {
+            services.push(service.number());
+            codes.extend(service);
+        }
+        assert_eq!(services, [1, 2]);
+        assert_eq!(
+            codes,
+            [
+                tables::Code::LatinCapitalA,
+                tables::Code::LatinCapitalB,
+                tables::Code::LatinCapitalC
+            ]
+        );
+    }
+
+    fn service_with_len(service_no: u8, len: usize) -> Service {
+        let mut service = Service::new(service_no);
+        let hdr_len = service.len().max(if service_no >= 7 { 2 } else { 1 });
+        for _ in hdr_len..len {
+            service.push_code(&tables::Code::LatinCapitalA).unwrap();
+        }
+        assert_eq!(service.len(), len);
+        service
+    }
+
+    #[test]
+    fn packet_free_space() {
+        test_init_log();
+        let mut packet = DTVCCPacket::new(0);
+        assert_eq!(packet.len(), 0);
+        assert_eq!(packet.free_space(), 127);
+
+        for _ in 0..3 {
+            packet.push_service(service_with_len(1, 32)).unwrap();
+            assert_eq!(packet.len() + packet.free_space(), 128);
+        }
+        assert_eq!(packet.free_space(), 31);
+        assert_eq!(
+            packet.push_service(service_with_len(1, 32)),
+            Err(WriterError::would_overflow(
+                1,
+                OverflowContext::Service { service_no: 1 }
+            ))
+        );
+
+        // one byte remaining
+        packet.push_service(service_with_len(1, 30)).unwrap();
+        assert_eq!(packet.len(), 127);
+        assert_eq!(packet.free_space(), 1);
+        assert_eq!(
+            packet.push_service(service_with_len(1, 2)),
+            Err(WriterError::would_overflow(
+                1,
+                OverflowContext::Service { service_no: 1 }
+            ))
+        );
+
+        // full
+        packet.push_null_padding(1).unwrap();
+        assert_eq!(packet.len(), 128);
+        assert_eq!(packet.free_space(), 0);
+        assert_eq!(
+            packet.push_null_padding(1),
+            Err(WriterError::would_overflow(1, OverflowContext::Packet))
+        );
+    }
+
+    #[test]
+    fn null_padding_roundtrip() {
+        test_init_log();
+        let mut service = Service::new(1);
+        service.push_code(&tables::Code::LatinCapitalA).unwrap();
+        let mut packet = DTVCCPacket::new(1);
+        packet.push_service(service.clone()).unwrap();
+        packet.push_null_padding(8).unwrap();
+        assert_eq!(packet.len(), 11);
+        assert_eq!(packet.cc_count(), 6);
+
+        let mut written = vec![];
+        packet.write(&mut written).unwrap();
+        assert_eq!(written.len(), 12);
+        let parsed = DTVCCPacket::parse(&written).unwrap();
+        assert_eq!(parsed.sequence_no(), 1);
+        assert_eq!(parsed.services().len(), 1);
+        assert_eq!(parsed.services()[0].number(), 1);
+        assert_eq!(parsed.services()[0].codes(), service.codes());
+
+        let mut writer = CCDataWriter::default();
+        writer.push_packet(packet);
+        let mut written = vec![];
+        writer.write(Framerate::new(30, 1), &mut written).unwrap();
+        assert_eq!(written[0] & 0x1F, 6);
+        let mut parser = CCDataParser::new();
+        parser.push(&written).unwrap();
+        let parsed = parser.pop_packet().unwrap();
+        assert_eq!(parsed.services().len(), 1);
+        assert_eq!(parsed.services()[0].codes(), service.codes());
+        assert!(parser.pop_packet().is_none());
+    }
+
+    #[test]
+    fn packet_hash() {
+        test_init_log();
+        let mut set = std::collections::HashSet::new();
+        let packet = packet_with_services(0, &[(1, 4), (2, 8)]);
+        assert!(set.insert(packet.services()[0].clone()));
+        assert!(set.insert(packet.services()[1].clone()));
+        assert!(!set.insert(packet.services()[0].clone()));
+
+        let mut packets = std::collections::HashSet::new();
+        assert!(packets.insert(packet.clone()));
+        assert!(!packets.insert(packet));
+        assert!(packets.insert(packet_with_services(1, &[(1, 4), (2, 8)])));
+    }
+
+    #[test]
+    fn service_try_new() {
+        test_init_log();
+        assert_eq!(Service::try_new(63).map(|service| service.number()), Ok(63));
+        assert_eq!(
+            Service::try_new(200),
+            Err(WriterError::InvalidServiceNumber { number: 200 })
+        );
+    }
+
+    #[test]
+    fn writer_auto_sequence_numbers() {
+        test_init_log();
+        let mut writer = CCDataWriter::default();
+        for _ in 0..5 {
+            writer
+                .push_packet_auto_seq(vec![service_with_len(1, 2)])
+                .unwrap();
+        }
+        let mut parser = CCDataParser::new();
+        let mut seq_nos = vec![];
+        for _ in 0..5 {
+            let mut written = vec![];
+            writer.write(Framerate::new(60, 1), &mut written).unwrap();
+            parser.push(&written).unwrap();
+            while let Some(packet) = parser.pop_packet() {
+                seq_nos.push(packet.sequence_no());
+            }
+        }
+        assert_eq!(seq_nos, [0, 1, 2, 3, 0]);
+        assert_eq!(parser.sequence_discontinuities(), 0);
+    }
+
+    #[test]
+    fn writer_multiple_packets_per_frame() {
+        test_init_log();
+        // 5 triples per packet: exactly two packets per 60fps frame.  4 triples per packet:
+        // packets straddle frames
+        for service_len in [8, 6] {
             let mut writer = CCDataWriter::default();
-            for cc_data in test_data.cc_data.iter() {
-                if let Some(packet_data) = packet_iter.next() {
-                    let mut pack = DTVCCPacket::new(packet_data.sequence_no);
-                    for service_data in packet_data.services.iter() {
-                        let mut service = Service::new(service_data.service_no);
-                        for code in service_data.codes.iter() {
-                            service.push_code(code).unwrap();
+            let mut expected = vec![];
+            for i in 0..6 {
+                let mut service = Service::new(1);
+                for _ in 0..service_len - 1 {
+                    service
+                        .push_code(tables::Code::from_char((b'A' + i) as char).unwrap())
+                        .unwrap();
+                }
+                let mut packet = DTVCCPacket::new(i & 0x3);
+                packet.push_service(service).unwrap();
+                expected.push(packet.clone());
+                writer.push_packet(packet);
+            }
+            let cc_count = expected[0].cc_count();
+            trace!("packet cc_count {cc_count}");
+            assert!(10 / cc_count >= 2);
+
+            let mut parser = CCDataParser::new();
+            let mut parsed = vec![];
+            let mut n_frames = 0;
+            while writer.buffered_packet_bytes() > 0 {
+                let mut written = vec![];
+                writer.write(Framerate::new(60, 1), &mut written).unwrap();
+                let remaining = expected.len() * cc_count - (n_frames * 10);
+                assert_eq!(written.len(), 2 + 3 * remaining.min(10));
+                parser.push(&written).unwrap();
+                while let Some(packet) = parser.pop_packet() {
+                    parsed.push(packet);
+                }
+                n_frames += 1;
+            }
+            assert_eq!(parsed, expected);
+            assert_eq!(n_frames, (expected.len() * cc_count).div_ceil(10));
+        }
+    }
+
+    #[test]
+    fn writer_frames_remaining() {
+        test_init_log();
+        for framerate in [
+            Framerate::new(60, 1),
+            Framerate::new(30000, 1001),
+            Framerate::new(25, 1),
+            Framerate::new(24, 1),
+        ] {
+            for (n_codes, n_field1, n_field2, padding) in [
+                (0usize, 0, 0, false),
+                (40, 0, 0, false),
+                (40, 0, 0, true),
+                (90, 3, 0, false),
+                (90, 0, 3, false),
+                (10, 5, 2, true),
+                (0, 4, 4, false),
+            ] {
+                let mut writer = CCDataWriter::default();
+                writer.set_output_padding(padding);
+                writer.set_output_cea608_padding(padding);
+                if n_codes > 0 {
+                    let mut packet = DTVCCPacket::new(0);
+                    for service_no in 1..=n_codes.div_ceil(30) {
+                        let mut service = Service::new(service_no as u8);
+                        for _ in 0..(n_codes - (service_no - 1) * 30).min(30) {
+                            service.push_code(&tables::Code::LatinCapitalA).unwrap();
                         }
-                        pack.push_service(service).unwrap();
+                        packet.push_service(service).unwrap();
                     }
-                    writer.push_packet(pack);
+                    writer.push_packet(packet);
                 }
-                if let Some(&cea608) = cea608_iter.next() {
-                    for pair in cea608 {
-                        writer.push_cea608(*pair);
+                for _ in 0..n_field1 {
+                    writer.push_cea608(Cea608::Field1(0x41, 0x42));
+                }
+                for _ in 0..n_field2 {
+                    writer.push_cea608(Cea608::Field2(0x41, 0x42));
+                }
+                let expected = writer.frames_remaining(framerate);
+                let mut n_frames = 0;
+                while writer.buffered_packet_bytes() > 0
+                    || !writer.cea608_1.is_empty()
+                    || !writer.cea608_2.is_empty()
+                {
+                    assert_eq!(writer.frames_remaining(framerate), expected - n_frames);
+                    let mut written = vec![];
+                    writer.write(framerate, &mut written).unwrap();
+                    n_frames += 1;
+                }
+                assert_eq!(
+                    n_frames, expected,
+                    "{framerate:?} {n_codes} {n_field1} {n_field2}"
+                );
+                assert_eq!(writer.frames_remaining(framerate), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn writer_strict_capacity() {
+        test_init_log();
+        let framerate = Framerate::new(60, 1);
+        let mut writer = CCDataWriter::default();
+        writer.set_strict_capacity(true);
+        assert!(writer.strict_capacity());
+        let mut written = vec![];
+        // 12 triples are pushed for each frame with only 10 triples available per frame
+        for (i, overflow_bytes) in [None, Some(2 * 2), Some(14 * 2)].into_iter().enumerate() {
+            for service_no in 1..=2 {
+                // 9 codes + 2 header bytes is 6 triples
+                let mut service = Service::new(service_no);
+                for _ in 0..9 {
+                    service.push_code(&tables::Code::LatinCapitalA).unwrap();
+                }
+                let mut packet = DTVCCPacket::new(i as u8);
+                packet.push_service(service).unwrap();
+                assert_eq!(packet.cc_count(), 6);
+                writer.push_packet(packet);
+            }
+            written.clear();
+            let Some(overflow_bytes) = overflow_bytes else {
+                writer.write(framerate, &mut written).unwrap();
+                assert_eq!(written.len(), 2 + 10 * 3);
+                continue;
+            };
+            let err = writer.write(framerate, &mut written).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::Other);
+            assert_eq!(
+                err.into_inner().unwrap().downcast_ref::<WriterError>(),
+                Some(&WriterError::would_overflow(
+                    overflow_bytes,
+                    OverflowContext::Writer
+                ))
+            );
+            assert!(written.is_empty());
+        }
+
+        // the same data is carried over when not strict
+        writer.set_strict_capacity(false);
+        let mut n_frames = 0;
+        while writer.buffered_packet_bytes() > 0 {
+            written.clear();
+            writer.write(framerate, &mut written).unwrap();
+            n_frames += 1;
+        }
+        assert_eq!(n_frames, 3);
+
+        // a cc_count of 0 can never drain any data
+        writer.set_strict_capacity(true);
+        writer.push_cea608(Cea608::Field1(0x41, 0x42));
+        let err = writer
+            .write_with_cc_count(framerate, 0, &mut written)
+            .unwrap_err();
+        assert_eq!(
+            err.into_inner().unwrap().downcast_ref::<WriterError>(),
+            Some(&WriterError::would_overflow(2, OverflowContext::Writer))
+        );
+    }
+
+    #[test]
+    fn writer_strict_capacity_max_size_packet() {
+        test_init_log();
+        for framerate in [
+            Framerate::new(60, 1),
+            Framerate::new(30, 1),
+            Framerate::new(24, 1),
+            Framerate::new(10, 1),
+        ] {
+            let mut writer = CCDataWriter::default();
+            writer.set_strict_capacity(true);
+            let mut packet = DTVCCPacket::new(0);
+            for service_no in 1..=4 {
+                let mut service = Service::new(service_no);
+                for _ in 0..30 {
+                    service.push_code(&tables::Code::LatinCapitalA).unwrap();
+                }
+                packet.push_service(service).unwrap();
+            }
+            assert_eq!(packet.len(), 125);
+            writer.push_packet(packet.clone());
+
+            let expected_frames = writer.frames_remaining(framerate);
+            let mut parser = CCDataParser::new();
+            let mut n_frames = 0;
+            while writer.buffered_packet_bytes() > 0 {
+                let mut written = vec![];
+                writer.write(framerate, &mut written).unwrap();
+                parser.push(&written).unwrap();
+                n_frames += 1;
+            }
+            assert_eq!(n_frames, expected_frames);
+            assert_eq!(parser.pop_packet(), Some(packet));
+        }
+    }
+
+    #[test]
+    fn writer_queue_full() {
+        test_init_log();
+        let mut writer = CCDataWriter::default();
+        assert_eq!(writer.max_queued_packets(), None);
+        writer.set_max_queued_packets(Some(2));
+        writer.try_push_packet(DTVCCPacket::new(0)).unwrap();
+        writer.try_push_packet(DTVCCPacket::new(1)).unwrap();
+        assert_eq!(
+            writer.try_push_packet(DTVCCPacket::new(2)),
+            Err(WriterError::QueueFull)
+        );
+        // push_packet() is not limited
+        writer.push_packet(DTVCCPacket::new(2));
+        writer.set_max_queued_packets(None);
+        writer.try_push_packet(DTVCCPacket::new(3)).unwrap();
+    }
+
+    #[test]
+    fn packet_overflow_service() {
+        test_init_log();
+        let mut packet = DTVCCPacket::new(0);
+        for _ in 0..4 {
+            packet.push_service(service_with_len(1, 31)).unwrap();
+        }
+        assert_eq!(packet.free_space(), 3);
+        let err = packet.push_service(service_with_len(12, 6)).unwrap_err();
+        assert_eq!(
+            err,
+            WriterError::would_overflow(3, OverflowContext::Service { service_no: 12 })
+        );
+        assert_eq!(
+            err.to_string(),
+            "Writing the data would overflow by 3 bytes in service 12"
+        );
+        assert_eq!(
+            packet.push_null_padding(4).unwrap_err().to_string(),
+            "Writing the data would overflow by 1 bytes in the packet"
+        );
+    }
+
+    #[test]
+    fn null_padding_overflow() {
+        test_init_log();
+        let mut packet = DTVCCPacket::new(0);
+        assert_eq!(
+            packet.push_null_padding(128),
+            Err(WriterError::would_overflow(1, OverflowContext::Packet))
+        );
+        packet.push_null_padding(127).unwrap();
+        assert_eq!(packet.len(), 128);
+        let mut written = vec![];
+        packet.write(&mut written).unwrap();
+        assert_eq!(written.len(), 128);
+        assert_eq!(written[0], 0x00);
+    }
+
+    #[test]
+    fn service_ref_matches_service() {
+        test_init_log();
+        for test_data in TEST_CC_DATA.iter() {
+            let mut parser = CCDataParser::new();
+            for data in test_data.cc_data.iter() {
+                parser.push(data).unwrap();
+                while let Some(packet) = parser.pop_packet() {
+                    let mut written = vec![];
+                    packet.write(&mut written).unwrap();
+                    let packet_ref = DTVCCPacket::parse_ref(&written).unwrap();
+                    assert_eq!(packet_ref.sequence_no(), packet.sequence_no());
+                    let mut service_refs = packet_ref.services();
+                    for service in packet.services() {
+                        let service_ref = service_refs.next().unwrap().unwrap();
+                        assert_eq!(service_ref.number(), service.number());
+                        assert_eq!(service_ref.block_len(), service.codes_len());
+                        let codes = service_ref.codes().collect::<Result<Vec<_>, _>>().unwrap();
+                        assert_eq!(codes, service.codes());
                     }
+                    assert!(service_refs.next().is_none());
                 }
+            }
+        }
+    }
+
+    #[test]
+    fn service_extended_roundtrip() {
+        test_init_log();
+        for service_no in 1..64 {
+            for code_len in [1, 2, 31] {
+                let mut service = Service::new(service_no);
+                for _ in 0..code_len {
+                    service.push_code(&tables::Code::LatinCapitalA).unwrap();
+                }
+                assert_eq!(service.is_extended(), service_no >= 7);
+                let hdr_len = if service_no >= 7 { 2 } else { 1 };
+                assert_eq!(service.header_len(), hdr_len);
+                assert_eq!(service.len(), hdr_len + service.codes_len());
+
                 let mut written = vec![];
-                writer.write(test_data.framerate, &mut written).unwrap();
-                assert_eq!(cc_data, &written);
+                service.write(&mut written).unwrap();
+                assert_eq!(written.len(), service.len());
+                if service_no >= 7 {
+                    assert_eq!(written[0] >> 5, 7);
+                    assert_eq!(written[1] & 0x3F, service_no);
+                } else {
+                    assert_eq!(written[0] >> 5, service_no);
+                }
+                assert_eq!(written[0] & 0x1F, service.codes_len() as u8);
+
+                let parsed = Service::parse(&written).unwrap();
+                assert_eq!(parsed, service);
+                let mut rewritten = vec![];
+                parsed.write(&mut rewritten).unwrap();
+                assert_eq!(rewritten, written);
+
+                let mut packet = DTVCCPacket::new(1);
+                packet.push_service(parsed).unwrap();
+                let bytes = packet.to_bytes();
+                let parsed = DTVCCPacket::parse(&bytes).unwrap();
+                assert_eq!(parsed.services()[0].number(), service_no);
+                assert_eq!(parsed.to_bytes(), bytes);
             }
         }
     }
 
+    #[test]
+    fn service_number_7_roundtrip() {
+        test_init_log();
+        let service = Service::from_codes(7, [tables::Code::LatinCapitalA]).unwrap();
+        assert!(service.is_extended());
+        assert_eq!(service.header_len(), 2);
+        let data = service.to_bytes();
+        assert_eq!(data, [0xE0 | 0x01, 0x07, 0x41]);
+        assert_eq!(data.len(), service.len());
+        assert_eq!(Service::parse(&data).unwrap(), service);
+        // the null fill bits of the extended header are ignored
+        assert_eq!(
+            Service::parse(&[0xE0 | 0x01, 0xC0 | 0x07, 0x41]).unwrap(),
+            service
+        );
+
+        let service = Service::from_codes(6, [tables::Code::LatinCapitalA]).unwrap();
+        assert!(!service.is_extended());
+        assert_eq!(service.header_len(), 1);
+        assert_eq!(service.to_bytes(), [0xC0 | 0x01, 0x41]);
+    }
+
+    #[test]
+    fn service_invalid_extended_number() {
+        test_init_log();
+        assert_eq!(
+            Service::parse(&[0xE1, 0x03, 0x41]),
+            Err(ParserError::InvalidServiceNumber { number: 3 })
+        );
+        assert_eq!(Service::parse(&[0xE1, 0x07, 0x41]).unwrap().number(), 7);
+    }
+
+    #[test]
+    fn strict_reserved_byte() {
+        test_init_log();
+        // service 1 contains 'A', reserved 0x94 and 'B'
+        let data = [
+            0x43, 0xFF, 0xFF, 0x03, 0x23, 0xFE, 0x41, 0x94, 0xFE, 0x42, 0x00,
+        ];
+
+        let mut parser = CCDataParser::new();
+        assert!(!parser.strict());
+        parser.push(&data).unwrap();
+        let packet = parser.pop_packet().unwrap();
+        assert_eq!(
+            packet.services()[0].codes(),
+            &[
+                tables::Code::LatinCapitalA,
+                tables::Code::Unknown(vec![0x94]),
+                tables::Code::LatinCapitalB
+            ]
+        );
+
+        parser.set_strict(true);
+        assert_eq!(
+            parser.push(&data),
+            Err(ParserError::ReservedByte {
+                byte: 0x94,
+                position: 3
+            })
+        );
+        assert!(parser.pop_packet().is_none());
+    }
+
+    #[test]
+    fn strict_invalid_service_number() {
+        test_init_log();
+        // extended service header with service number 2
+        let data = [0x42, 0xFF, 0xFF, 0x02, 0xE1, 0xFE, 0x02, 0x41];
+
+        let mut parser = CCDataParser::new();
+        parser.push(&data).unwrap();
+        assert!(parser.pop_packet().is_none());
+
+        parser.set_strict(true);
+        assert_eq!(
+            parser.push(&data),
+            Err(ParserError::InvalidServiceNumber { number: 2 })
+        );
+        assert!(parser.pop_packet().is_none());
+    }
+
     #[test]
     fn framerate_cea608_pairs_per_frame() {
         assert_eq!(Framerate::new(60, 1).cea608_pairs_per_frame(), 1);
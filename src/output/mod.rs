@@ -0,0 +1,91 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for converting CEA-708 caption data into other subtitle formats
+//!
+//! The extractors in this module track the text content and visibility of the 8 caption
+//! windows of a single [Service](crate::Service) using a
+//! [TimedCaptionState].  Styling is not taken
+//! into account.
+
+use std::time::Duration;
+
+use crate::timing::{TimedCaption, TimedCaptionState};
+use crate::DTVCCPacket;
+
+mod srt;
+mod text;
+mod webvtt;
+
+pub use srt::{SrtEntry, SrtExtractor, SrtTimecode};
+pub use text::TextExtractor;
+pub use webvtt::{WebVttCue, WebVttExtractor, WebVttPosition};
+
+/// A caption that was visible on screen for a period of time
+#[derive(Debug, Clone, PartialEq)]
+struct Cue {
+    end: Duration,
+    caption: TimedCaption,
+}
+
+/// Produces a [Cue] for every distinct text that is visible in a window
+#[derive(Debug)]
+struct CueTracker {
+    service_no: u8,
+    state: TimedCaptionState,
+    finished: Vec<TimedCaption>,
+}
+
+impl CueTracker {
+    fn new(service_no: u8) -> Self {
+        Self {
+            service_no,
+            state: TimedCaptionState::new(),
+            finished: vec![],
+        }
+    }
+
+    fn process_packet(&mut self, packet: &DTVCCPacket, pts: Duration) {
+        self.state
+            .apply_packet_with_pts(packet, self.service_no, pts);
+    }
+
+    fn finish(&mut self, pts: Duration) {
+        for mut caption in self.state.flush() {
+            caption.end.get_or_insert(pts);
+            self.finished.push(caption);
+        }
+    }
+
+    fn take_cues(&mut self) -> Vec<Cue> {
+        let mut captions = std::mem::take(&mut self.finished);
+        captions.extend(self.state.drain_completed_captions(Duration::MAX));
+        captions.sort_by_key(|caption| (caption.start, caption.window_id));
+        captions
+            .into_iter()
+            .filter_map(|caption| {
+                let end = caption.end.filter(|end| *end > caption.start)?;
+                Some(Cue { end, caption })
+            })
+            .collect()
+    }
+}
+
+fn write_timestamp(
+    f: &mut dyn std::fmt::Write,
+    timestamp: Duration,
+    separator: char,
+) -> std::fmt::Result {
+    let millis = timestamp.as_millis();
+    write!(
+        f,
+        "{:02}:{:02}:{:02}{separator}{:03}",
+        millis / 3_600_000,
+        (millis / 60_000) % 60,
+        (millis / 1000) % 60,
+        millis % 1000
+    )
+}
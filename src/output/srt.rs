@@ -0,0 +1,240 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::time::Duration;
+
+use super::{write_timestamp, CueTracker};
+use crate::DTVCCPacket;
+
+/// A timestamp in a SRT file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct SrtTimecode(pub Duration);
+
+/// Formats as `HH:MM:SS,mmm`
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use cea708_types::output::SrtTimecode;
+/// assert_eq!(SrtTimecode(Duration::from_millis(3_723_456)).to_string(), "01:02:03,456");
+/// ```
+impl std::fmt::Display for SrtTimecode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write_timestamp(f, self.0, ',')
+    }
+}
+
+impl From<Duration> for SrtTimecode {
+    fn from(value: Duration) -> Self {
+        Self(value)
+    }
+}
+
+/// An entry in a SRT file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrtEntry {
+    /// The 1-based index of the entry
+    pub srt_index: u32,
+    /// The time the text is first shown
+    pub start: SrtTimecode,
+    /// The time the text is removed
+    pub end: SrtTimecode,
+    /// The text of the entry.  Multiple lines are separated with `\n`.
+    pub text: String,
+}
+
+/// Writes the entry in the SRT format including the terminating blank line.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use cea708_types::output::SrtEntry;
+/// let entry = SrtEntry {
+///     srt_index: 1,
+///     start: Duration::from_millis(1500).into(),
+///     end: Duration::from_secs(3).into(),
+///     text: "Hello".to_string(),
+/// };
+/// assert_eq!(entry.to_string(), "1\n00:00:01,500 --> 00:00:03,000\nHello\n\n");
+/// ```
+impl std::fmt::Display for SrtEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{}", self.srt_index)?;
+        writeln!(f, "{} --> {}", self.start, self.end)?;
+        writeln!(f, "{}", self.text)?;
+        writeln!(f)
+    }
+}
+
+/// Produces [SrtEntry]s from the windows of a single CEA-708 service
+///
+/// A new entry is produced every time the visible text of a window changes.  Scrolling
+/// (roll-up) windows produce an entry for every row added, and snap (pop-on) windows produce an
+/// entry for the time the window is displayed.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use cea708_types::{*, tables::*, output::SrtExtractor};
+/// let mut extractor = SrtExtractor::new(1);
+/// let mut service = Service::new(1);
+/// service.push_code(DefineWindowArgs::new(
+///     0, 0, Anchor::BottomMiddle, true, 90, 50, 1, 31, true, true, true, 1, 1,
+/// )).unwrap();
+/// service.push_code(Code::LatinCapitalA).unwrap();
+/// let mut packet = DTVCCPacket::new(0);
+/// packet.push_service(service).unwrap();
+/// extractor.process_packet(&packet, Duration::from_secs(1));
+/// extractor.finish(Duration::from_secs(2));
+///
+/// let mut srt = vec![];
+/// extractor.write_to(&mut srt).unwrap();
+/// assert_eq!(srt, b"1\n00:00:01,000 --> 00:00:02,000\nA\n\n");
+/// ```
+#[derive(Debug)]
+pub struct SrtExtractor {
+    tracker: CueTracker,
+    next_index: u32,
+}
+
+impl SrtExtractor {
+    /// Create a new [SrtExtractor] for the service number `service_no`
+    pub fn new(service_no: u8) -> Self {
+        Self {
+            tracker: CueTracker::new(service_no),
+            next_index: 1,
+        }
+    }
+
+    /// Apply the codes for the service in `packet` that is presented at `pts`
+    pub fn process_packet(&mut self, packet: &DTVCCPacket, pts: Duration) {
+        self.tracker.process_packet(packet, pts)
+    }
+
+    /// End all currently visible text at `pts`.  Any entries ended will be returned from the next
+    /// call to [take_entries](Self::take_entries).
+    pub fn finish(&mut self, pts: Duration) {
+        self.tracker.finish(pts)
+    }
+
+    /// Take all the entries that have been completed since the last call, ordered by start time
+    pub fn take_entries(&mut self) -> Vec<SrtEntry> {
+        self.tracker
+            .take_cues()
+            .into_iter()
+            .map(|cue| {
+                let srt_index = self.next_index;
+                self.next_index += 1;
+                SrtEntry {
+                    srt_index,
+                    start: cue.caption.start.into(),
+                    end: cue.end.into(),
+                    text: cue.caption.text,
+                }
+            })
+            .collect()
+    }
+
+    /// Write all the completed entries to `w` in the SRT format
+    pub fn write_to<W: std::io::Write>(&mut self, w: &mut W) -> Result<(), std::io::Error> {
+        for entry in self.take_entries() {
+            write!(w, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tables::*;
+    use crate::tests::*;
+    use crate::Service;
+
+    fn packet(seq_no: u8, codes: &[Code]) -> DTVCCPacket {
+        let service = Service::from_codes(1, codes.iter().cloned()).unwrap();
+        let mut packet = DTVCCPacket::new(seq_no);
+        packet.push_service(service).unwrap();
+        packet
+    }
+
+    fn text(s: &str) -> Vec<Code> {
+        s.chars().map(|c| Code::from_char(c).unwrap()).collect()
+    }
+
+    fn define_window(id: u8, visible: bool, rows: u8) -> Code {
+        Code::DefineWindow(DefineWindowArgs::new(
+            id,
+            0,
+            Anchor::BottomLeft,
+            true,
+            90,
+            10,
+            rows - 1,
+            31,
+            true,
+            true,
+            visible,
+            1,
+            1,
+        ))
+    }
+
+    #[test]
+    fn scroll_window() {
+        test_init_log();
+        let mut extractor = SrtExtractor::new(1);
+        let mut codes = vec![define_window(0, true, 2)];
+        codes.extend(text("One"));
+        extractor.process_packet(&packet(0, &codes), Duration::from_secs(1));
+        let mut codes = vec![Code::CR];
+        codes.extend(text("Two"));
+        extractor.process_packet(&packet(1, &codes), Duration::from_secs(2));
+        assert_eq!(
+            extractor.take_entries(),
+            [SrtEntry {
+                srt_index: 1,
+                start: Duration::from_secs(1).into(),
+                end: Duration::from_secs(2).into(),
+                text: "One".to_string(),
+            }]
+        );
+        // an empty CR scrolls "One" off the top
+        extractor.process_packet(&packet(2, &[Code::CR]), Duration::from_secs(3));
+        extractor.finish(Duration::from_secs(4));
+        let mut srt = vec![];
+        extractor.write_to(&mut srt).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&srt).unwrap(),
+            "2\n00:00:02,000 --> 00:00:03,000\nOne\nTwo\n\n\
+             3\n00:00:03,000 --> 00:00:04,000\nTwo\n\n"
+        );
+    }
+
+    #[test]
+    fn snap_window() {
+        test_init_log();
+        let mut extractor = SrtExtractor::new(1);
+        let mut codes = vec![define_window(2, true, 1)];
+        codes.extend(text("Snap"));
+        extractor.process_packet(&packet(0, &codes), Duration::from_millis(500));
+        extractor.process_packet(
+            &packet(1, &[Code::ClearWindows(WindowBits::TWO)]),
+            Duration::from_millis(2500),
+        );
+        // nothing left visible
+        extractor.finish(Duration::from_secs(3));
+        assert_eq!(
+            extractor.take_entries(),
+            [SrtEntry {
+                srt_index: 1,
+                start: Duration::from_millis(500).into(),
+                end: Duration::from_millis(2500).into(),
+                text: "Snap".to_string(),
+            }]
+        );
+    }
+}
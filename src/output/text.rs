@@ -0,0 +1,226 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::renderer::{CaptionState, WindowState};
+use crate::tables::{Code, WindowBits};
+use crate::DTVCCPacket;
+
+/// Produces lines of plain text from the windows of a single CEA-708 service
+///
+/// Text is produced once it has left the screen.  Scrolling (roll-up) windows produce each row as
+/// it scrolls off the top of the window.  All the text of a window is produced when the window is
+/// hidden, cleared, deleted or redefined with a different definition while visible.  Text in a
+/// window that is never displayed is discarded.
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, tables::*, output::TextExtractor};
+/// let mut service = Service::new(1);
+/// service.push_code(DefineWindowArgs::new(
+///     0, 0, Anchor::BottomMiddle, true, 90, 50, 1, 31, true, true, true, 1, 1,
+/// )).unwrap();
+/// service.push_code(Code::LatinCapitalA).unwrap();
+/// service.push_code(Code::HideWindows(WindowBits::ZERO)).unwrap();
+/// let mut packet = DTVCCPacket::new(0);
+/// packet.push_service(service).unwrap();
+///
+/// let mut extractor = TextExtractor::new(1);
+/// assert_eq!(extractor.process_packet(&packet), ["A"]);
+/// ```
+#[derive(Debug)]
+pub struct TextExtractor {
+    service_no: u8,
+    state: CaptionState,
+}
+
+fn window_lines(window: &WindowState, lines: &mut Vec<String>) {
+    if window.is_visible() {
+        lines.extend(window.plain_text_rows().filter(|row| !row.is_empty()));
+    }
+}
+
+impl TextExtractor {
+    /// Create a new [TextExtractor] for the service number `service_no`
+    pub fn new(service_no: u8) -> Self {
+        Self {
+            service_no,
+            state: CaptionState::new(),
+        }
+    }
+
+    fn visible_lines(&self, bits: WindowBits, lines: &mut Vec<String>) {
+        for id in 0..8 {
+            if (bits & WindowBits::from_window_id(id)) != WindowBits::NONE {
+                if let Some(window) = self.state.window(id) {
+                    window_lines(window, lines);
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, code: &Code, lines: &mut Vec<String>) {
+        let current = self.state.window(self.state.current_window());
+        let mut clear_window = None;
+        match code {
+            Code::DefineWindow(args) => {
+                let id = args.window_id & 0x7;
+                if let Some(window) = self.state.window(id) {
+                    if window.definition() != *args {
+                        window_lines(window, lines);
+                        clear_window = Some(id);
+                    }
+                }
+            }
+            Code::ClearWindows(bits)
+            | Code::HideWindows(bits)
+            | Code::ToggleWindows(bits)
+            | Code::DeleteWindows(bits) => self.visible_lines(*bits, lines),
+            Code::Reset => self.visible_lines(WindowBits::from(0xFF), lines),
+            Code::FF => {
+                if let Some(window) = current {
+                    window_lines(window, lines);
+                }
+            }
+            Code::CR => {
+                if let Some(window) = current.filter(|window| window.is_visible()) {
+                    if window.pen_state().row + 1 >= window.row_count() {
+                        if let Some(row) = window.plain_text_rows().next() {
+                            if !row.is_empty() {
+                                lines.push(row);
+                            }
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+        self.state.apply_code(code);
+        if let Some(id) = clear_window {
+            self.state
+                .apply_code(&Code::ClearWindows(WindowBits::from_window_id(id)));
+        }
+    }
+
+    /// Process a [DTVCCPacket] and return the lines of text that have been removed from the
+    /// screen
+    pub fn process_packet(&mut self, packet: &DTVCCPacket) -> Vec<String> {
+        let mut lines = vec![];
+        for service in packet.services() {
+            if service.number() != self.service_no {
+                continue;
+            }
+            for code in service.codes() {
+                self.apply(code, &mut lines);
+            }
+        }
+        lines
+    }
+
+    /// Return the lines of text that are still visible at the end of the stream.  All windows
+    /// are deleted.
+    pub fn finish(&mut self) -> Vec<String> {
+        let mut lines = vec![];
+        self.apply(&Code::Reset, &mut lines);
+        lines
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tables::*;
+    use crate::tests::*;
+    use crate::Service;
+
+    fn packet(codes: &[Code]) -> DTVCCPacket {
+        let mut packet = DTVCCPacket::new(0);
+        for chunk in codes.chunks(8) {
+            let service = Service::from_codes(1, chunk.iter().cloned()).unwrap();
+            packet.push_service(service).unwrap();
+        }
+        packet
+    }
+
+    fn text(s: &str) -> Vec<Code> {
+        s.chars().map(|c| Code::from_char(c).unwrap()).collect()
+    }
+
+    fn define_window(id: u8, visible: bool, rows: u8) -> Code {
+        Code::DefineWindow(DefineWindowArgs::new(
+            id,
+            0,
+            Anchor::BottomLeft,
+            true,
+            90,
+            10,
+            rows - 1,
+            31,
+            true,
+            true,
+            visible,
+            1,
+            1,
+        ))
+    }
+
+    #[test]
+    fn scroll_window() {
+        test_init_log();
+        let mut extractor = TextExtractor::new(1);
+        let mut codes = vec![define_window(0, true, 2)];
+        codes.extend(text("One"));
+        codes.push(Code::CR);
+        codes.extend(text("Two"));
+        assert!(extractor.process_packet(&packet(&codes)).is_empty());
+        // repeating the same definition does not remove any text
+        let mut codes = vec![define_window(0, true, 2), Code::CR];
+        codes.extend(text("Six"));
+        assert_eq!(extractor.process_packet(&packet(&codes)), ["One"]);
+        // other services are ignored
+        let service = Service::from_codes(2, [Code::CR, Code::CR]).unwrap();
+        let mut other = DTVCCPacket::new(0);
+        other.push_service(service).unwrap();
+        assert!(extractor.process_packet(&other).is_empty());
+
+        assert_eq!(extractor.process_packet(&packet(&[Code::CR])), ["Two"]);
+        assert_eq!(extractor.finish(), ["Six"]);
+    }
+
+    #[test]
+    fn pop_on_window() {
+        test_init_log();
+        let mut extractor = TextExtractor::new(1);
+        let mut codes = vec![define_window(1, false, 2)];
+        codes.extend(text("One"));
+        codes.push(Code::CR);
+        codes.extend(text("Two"));
+        codes.push(define_window(2, false, 2));
+        codes.extend(text("Three"));
+        codes.push(Code::ToggleWindows(WindowBits::ONE));
+        assert!(extractor.process_packet(&packet(&codes)).is_empty());
+
+        let codes = [
+            Code::ToggleWindows(WindowBits::ONE | WindowBits::TWO),
+            Code::ClearWindows(WindowBits::ONE),
+        ];
+        assert_eq!(extractor.process_packet(&packet(&codes)), ["One", "Two"]);
+
+        // hidden windows do not produce any text
+        let mut codes = vec![Code::SetCurrentWindow1];
+        codes.extend(text("Four"));
+        codes.push(Code::ClearWindows(WindowBits::ONE));
+        assert!(extractor.process_packet(&packet(&codes)).is_empty());
+
+        // redefining a visible window replaces its content
+        let codes = [define_window(2, true, 1), Code::LatinCapitalA];
+        assert_eq!(extractor.process_packet(&packet(&codes)), ["Three"]);
+        assert_eq!(
+            extractor.process_packet(&packet(&[Code::DeleteWindows(WindowBits::TWO)])),
+            ["A"]
+        );
+        assert!(extractor.finish().is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::time::Duration;
+
+use super::{write_timestamp, CueTracker};
+use crate::tables::Anchor;
+use crate::timing::TimedCaption;
+use crate::DTVCCPacket;
+
+/// Position of a [WebVttCue] on the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WebVttPosition {
+    /// The point of the cue that is placed at the position
+    pub anchor: Anchor,
+    /// Vertical position as a percentage of the video height
+    pub line: f32,
+    /// Horizontal position as a percentage of the video width
+    pub position: f32,
+}
+
+impl WebVttPosition {
+    fn from_caption(caption: &TimedCaption) -> Option<Self> {
+        if !caption.anchor.is_defined() {
+            return None;
+        }
+        let (position, line) = caption.position;
+        Some(Self {
+            anchor: caption.anchor,
+            line: line * 100.,
+            position: position * 100.,
+        })
+    }
+}
+
+/// A WebVTT cue
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebVttCue {
+    /// The time the cue is first shown
+    pub start: Duration,
+    /// The time the cue is removed
+    pub end: Duration,
+    /// The text of the cue.  Multiple lines are separated with `\n`.
+    pub text: String,
+    /// Where the cue was positioned
+    pub position: Option<WebVttPosition>,
+}
+
+/// Writes the cue in the WebVTT cue block format including the position as cue settings.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use cea708_types::output::WebVttCue;
+/// let cue = WebVttCue {
+///     start: Duration::from_millis(1500),
+///     end: Duration::from_secs(3723),
+///     text: "Hello".to_string(),
+///     position: None,
+/// };
+/// assert_eq!(cue.to_string(), "00:00:01.500 --> 01:02:03.000\nHello\n");
+/// ```
+impl std::fmt::Display for WebVttCue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write_timestamp(f, self.start, '.')?;
+        write!(f, " --> ")?;
+        write_timestamp(f, self.end, '.')?;
+        if let Some(position) = self.position {
+            let anchor = u8::from(position.anchor);
+            let line_align = ["start", "center", "end"][anchor as usize / 3];
+            let position_align = ["line-left", "center", "line-right"][anchor as usize % 3];
+            write!(
+                f,
+                " line:{:.0}%,{line_align} position:{:.0}%,{position_align}",
+                position.line, position.position
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{}", self.text)
+    }
+}
+
+/// Produces [WebVttCue]s from the windows of a single CEA-708 service
+///
+/// A new cue is produced every time the visible text of a window changes.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use cea708_types::{*, tables::*, output::WebVttExtractor};
+/// let mut extractor = WebVttExtractor::new(1);
+/// let mut service = Service::new(1);
+/// service.push_code(DefineWindowArgs::new(
+///     0, 0, Anchor::BottomMiddle, true, 90, 50, 1, 31, true, true, true, 1, 1,
+/// )).unwrap();
+/// service.push_code(Code::LatinCapitalA).unwrap();
+/// let mut packet = DTVCCPacket::new(0);
+/// packet.push_service(service).unwrap();
+/// extractor.process_packet(&packet, Duration::from_secs(1));
+///
+/// let mut service = Service::new(1);
+/// service.push_code(Code::HideWindows(WindowBits::ZERO)).unwrap();
+/// let mut packet = DTVCCPacket::new(1);
+/// packet.push_service(service).unwrap();
+/// extractor.process_packet(&packet, Duration::from_secs(2));
+///
+/// let cues = extractor.take_cues();
+/// assert_eq!(cues.len(), 1);
+/// assert_eq!(cues[0].start, Duration::from_secs(1));
+/// assert_eq!(cues[0].end, Duration::from_secs(2));
+/// assert_eq!(cues[0].text, "A");
+/// ```
+#[derive(Debug)]
+pub struct WebVttExtractor {
+    tracker: CueTracker,
+}
+
+impl WebVttExtractor {
+    /// Create a new [WebVttExtractor] for the service number `service_no`
+    pub fn new(service_no: u8) -> Self {
+        Self {
+            tracker: CueTracker::new(service_no),
+        }
+    }
+
+    /// Apply the codes for the service in `packet` that is presented at `pts`
+    pub fn process_packet(&mut self, packet: &DTVCCPacket, pts: Duration) {
+        self.tracker.process_packet(packet, pts)
+    }
+
+    /// End all currently visible cues at `pts`.  Any cues ended will be returned from the next
+    /// call to [take_cues](Self::take_cues).
+    pub fn finish(&mut self, pts: Duration) {
+        self.tracker.finish(pts)
+    }
+
+    /// Take all the cues that have been completed since the last call, ordered by start time
+    pub fn take_cues(&mut self) -> Vec<WebVttCue> {
+        self.tracker
+            .take_cues()
+            .into_iter()
+            .map(|cue| WebVttCue {
+                start: cue.caption.start,
+                end: cue.end,
+                position: WebVttPosition::from_caption(&cue.caption),
+                text: cue.caption.text,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tables::*;
+    use crate::tests::*;
+    use crate::Service;
+
+    fn packet(seq_no: u8, service_no: u8, codes: &[Code]) -> DTVCCPacket {
+        let service = Service::from_codes(service_no, codes.iter().cloned()).unwrap();
+        let mut packet = DTVCCPacket::new(seq_no);
+        packet.push_service(service).unwrap();
+        packet
+    }
+
+    fn text(s: &str) -> Vec<Code> {
+        s.chars().map(|c| Code::from_char(c).unwrap()).collect()
+    }
+
+    fn define_window(id: u8, visible: bool, rows: u8) -> Code {
+        Code::DefineWindow(DefineWindowArgs::new(
+            id,
+            0,
+            Anchor::BottomMiddle,
+            true,
+            90,
+            50,
+            rows - 1,
+            31,
+            true,
+            true,
+            visible,
+            1,
+            1,
+        ))
+    }
+
+    #[test]
+    fn pop_on() {
+        test_init_log();
+        let mut extractor = WebVttExtractor::new(1);
+        // fill a hidden window then swap
+        let mut codes = vec![define_window(1, false, 2)];
+        codes.extend(text("Hello"));
+        codes.push(Code::CR);
+        codes.extend(text("World"));
+        extractor.process_packet(&packet(0, 1, &codes), Duration::from_secs(1));
+        // other services are ignored
+        extractor.process_packet(
+            &packet(1, 2, &[Code::DisplayWindows(WindowBits::ONE)]),
+            Duration::from_secs(2),
+        );
+        assert!(extractor.take_cues().is_empty());
+        extractor.process_packet(
+            &packet(2, 1, &[Code::DisplayWindows(WindowBits::ONE)]),
+            Duration::from_secs(3),
+        );
+        assert!(extractor.take_cues().is_empty());
+        extractor.process_packet(
+            &packet(3, 1, &[Code::ClearWindows(WindowBits::ONE)]),
+            Duration::from_secs(5),
+        );
+        let cues = extractor.take_cues();
+        assert_eq!(
+            cues,
+            [WebVttCue {
+                start: Duration::from_secs(3),
+                end: Duration::from_secs(5),
+                text: "Hello\nWorld".to_string(),
+                position: Some(WebVttPosition {
+                    anchor: Anchor::BottomMiddle,
+                    line: 90.,
+                    position: 50.
+                }),
+            }]
+        );
+        assert_eq!(
+            cues[0].to_string(),
+            "00:00:03.000 --> 00:00:05.000 line:90%,end position:50%,center\nHello\nWorld\n"
+        );
+    }
+
+    #[test]
+    fn roll_up() {
+        test_init_log();
+        let mut extractor = WebVttExtractor::new(1);
+        let mut codes = vec![define_window(0, true, 2)];
+        codes.extend(text("One"));
+        extractor.process_packet(&packet(0, 1, &codes), Duration::from_secs(1));
+        let mut codes = vec![Code::CR];
+        codes.extend(text("Two"));
+        extractor.process_packet(&packet(1, 1, &codes), Duration::from_secs(2));
+        let mut codes = vec![Code::CR];
+        codes.extend(text("Three"));
+        extractor.process_packet(&packet(2, 1, &codes), Duration::from_secs(3));
+        extractor.finish(Duration::from_secs(4));
+        let cues = extractor
+            .take_cues()
+            .into_iter()
+            .map(|cue| (cue.start.as_secs(), cue.end.as_secs(), cue.text))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cues,
+            [
+                (1, 2, "One".to_string()),
+                (2, 3, "One\nTwo".to_string()),
+                (3, 4, "Two\nThree".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn delete_and_reset() {
+        test_init_log();
+        let mut extractor = WebVttExtractor::new(1);
+        let mut codes = vec![define_window(0, true, 1)];
+        codes.extend(text("A"));
+        codes.push(define_window(3, true, 1));
+        codes.extend(text("B"));
+        extractor.process_packet(&packet(0, 1, &codes), Duration::from_secs(1));
+        extractor.process_packet(
+            &packet(1, 1, &[Code::DeleteWindows(WindowBits::THREE)]),
+            Duration::from_secs(2),
+        );
+        extractor.process_packet(&packet(2, 1, &[Code::Reset]), Duration::from_secs(3));
+        let cues = extractor
+            .take_cues()
+            .into_iter()
+            .map(|cue| (cue.start.as_secs(), cue.end.as_secs(), cue.text))
+            .collect::<Vec<_>>();
+        assert_eq!(cues, [(1, 3, "A".to_string()), (1, 2, "B".to_string())]);
+    }
+}
@@ -0,0 +1,1255 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for tracking the on screen state of CEA-708 captions
+//!
+//! A [CaptionState] applies the commands and text of a single [Service](crate::Service) to a
+//! model of the 8 caption windows that a decoder would present.
+
+use log::trace;
+
+use crate::tables::{
+    self, Code, DefineWindowArgs, Direction, SetPenAttributesArgs, SetPenColorArgs,
+    SetPenLocationArgs, SetWindowAttributesArgs, WindowBits,
+};
+use crate::DTVCCPacket;
+
+/// A character with the pen style it was written with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StyledChar {
+    /// The character
+    pub c: char,
+    /// The pen attributes when the character was written
+    pub attributes: SetPenAttributesArgs,
+    /// The pen color when the character was written
+    pub color: SetPenColorArgs,
+}
+
+/// The position and style of the pen of a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PenState {
+    /// The row the next character will be placed in
+    pub row: u8,
+    /// The column the next character will be placed in
+    pub column: u8,
+    /// The current pen attributes
+    pub attributes: SetPenAttributesArgs,
+    /// The current pen color
+    pub color: SetPenColorArgs,
+}
+
+/// A [PenState] at the top left using the predefined pen style 1
+impl Default for PenState {
+    fn default() -> Self {
+        Self {
+            row: 0,
+            column: 0,
+            attributes: tables::PREDEFINED_PEN_STYLES_ATTRIBUTES[0],
+            color: tables::PREDEFINED_PEN_STYLES_COLOR[0],
+        }
+    }
+}
+
+impl PenState {
+    /// Move the pen to the location in `args`
+    pub fn apply_set_pen_location(&mut self, args: SetPenLocationArgs) {
+        self.row = args.row;
+        self.column = args.column;
+    }
+
+    /// Change the pen attributes used for following characters
+    pub fn apply_set_pen_attributes(&mut self, args: SetPenAttributesArgs) {
+        self.attributes = args;
+    }
+
+    /// Change the pen color used for following characters
+    pub fn apply_set_pen_color(&mut self, args: SetPenColorArgs) {
+        self.color = args;
+    }
+
+    /// Move the pen one position in `direction`.  The position will not move below 0.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{tables::*, renderer::PenState};
+    /// let mut pen = PenState::default();
+    /// pen.advance(Direction::LeftToRight);
+    /// pen.advance(Direction::TopToBottom);
+    /// assert_eq!((pen.row, pen.column), (1, 1));
+    /// pen.advance(Direction::RightToLeft);
+    /// pen.advance(Direction::RightToLeft);
+    /// assert_eq!((pen.row, pen.column), (1, 0));
+    /// ```
+    pub fn advance(&mut self, direction: Direction) {
+        match direction {
+            Direction::LeftToRight => self.column = self.column.saturating_add(1),
+            Direction::RightToLeft => self.column = self.column.saturating_sub(1),
+            Direction::TopToBottom => self.row = self.row.saturating_add(1),
+            Direction::BottomToTop => self.row = self.row.saturating_sub(1),
+        }
+    }
+}
+
+/// The state of a single caption window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowState {
+    definition: DefineWindowArgs,
+    window_attrs: SetWindowAttributesArgs,
+    pen_state: PenState,
+    visible: bool,
+    content: Vec<Vec<Option<StyledChar>>>,
+}
+
+impl WindowState {
+    /// Create a new empty [WindowState] from a window definition using the window and pen styles
+    /// of the definition
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{tables::*, renderer::WindowState};
+    /// let mut window = WindowState::new(DefineWindowArgs::new(
+    ///     0, 0, Anchor::TopLeft, true, 0, 0, 1, 2, true, true, true, 1, 1,
+    /// ));
+    /// for c in "ABCD".chars() {
+    ///     window.apply_text(c);
+    /// }
+    /// // without wordwrap the last column is overwritten
+    /// assert_eq!(window.to_plain_text(), "ABD");
+    /// ```
+    pub fn new(definition: DefineWindowArgs) -> Self {
+        Self {
+            definition,
+            window_attrs: definition.window_attributes(),
+            pen_state: PenState {
+                attributes: definition.pen_attributes(),
+                color: definition.pen_color(),
+                ..Default::default()
+            },
+            visible: definition.visible,
+            content: vec![
+                vec![None; definition.column_count as usize + 1];
+                definition.row_count as usize + 1
+            ],
+        }
+    }
+
+    /// The definition of the window
+    pub fn definition(&self) -> DefineWindowArgs {
+        self.definition
+    }
+
+    /// The current window attributes
+    pub fn window_attributes(&self) -> SetWindowAttributesArgs {
+        self.window_attrs
+    }
+
+    /// The current pen of the window.  The pen is always inside the window.
+    pub fn pen_state(&self) -> PenState {
+        self.pen_state
+    }
+
+    /// Whether the window is currently displayed
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// The characters in the window in row-major order.  There are
+    /// [row_count](Self::row_count) rows each with [column_count](Self::column_count) columns.
+    pub fn content(&self) -> &[Vec<Option<StyledChar>>] {
+        &self.content
+    }
+
+    /// The number of rows in this window
+    pub fn row_count(&self) -> u8 {
+        self.definition.row_count + 1
+    }
+
+    /// The number of columns in this window
+    pub fn column_count(&self) -> u8 {
+        self.definition.column_count + 1
+    }
+
+    fn redefine(&mut self, definition: DefineWindowArgs) {
+        if definition.window_style_id != 0 {
+            self.window_attrs = definition.window_attributes();
+        }
+        if definition.pen_style_id != 0 {
+            self.pen_state.attributes = definition.pen_attributes();
+            self.pen_state.color = definition.pen_color();
+        }
+        self.definition = definition;
+        self.visible = definition.visible;
+        let columns = self.column_count() as usize;
+        self.content.resize(self.row_count() as usize, vec![]);
+        for row in self.content.iter_mut() {
+            row.resize(columns, None);
+        }
+        self.pen_state.row = self.pen_state.row.min(self.row_count() - 1);
+        self.pen_state.column = self.pen_state.column.min(self.column_count() - 1);
+    }
+
+    fn is_vertical(&self) -> bool {
+        matches!(
+            self.window_attrs.print_direction,
+            Direction::TopToBottom | Direction::BottomToTop
+        )
+    }
+
+    // the (row, column) of the start of a line for the print direction
+    fn line_start(&self) -> (u8, u8) {
+        match self.window_attrs.print_direction {
+            Direction::LeftToRight => (self.pen_state.row, 0),
+            Direction::RightToLeft => (self.pen_state.row, self.column_count() - 1),
+            Direction::TopToBottom => (0, self.pen_state.column),
+            Direction::BottomToTop => (self.row_count() - 1, self.pen_state.column),
+        }
+    }
+
+    /// Clear all the characters in the window and move the pen to the top left
+    pub fn clear(&mut self) {
+        for row in self.content.iter_mut() {
+            row.fill(None);
+        }
+        self.pen_state.row = 0;
+        self.pen_state.column = 0;
+    }
+
+    fn set_pen_location(&mut self, location: SetPenLocationArgs) {
+        self.pen_state.apply_set_pen_location(location);
+        self.pen_state.row = self.pen_state.row.min(self.row_count() - 1);
+        self.pen_state.column = self.pen_state.column.min(self.column_count() - 1);
+    }
+
+    fn pen_at_end(&self, direction: Direction) -> bool {
+        match direction {
+            Direction::LeftToRight => self.pen_state.column + 1 >= self.column_count(),
+            Direction::RightToLeft => self.pen_state.column == 0,
+            Direction::TopToBottom => self.pen_state.row + 1 >= self.row_count(),
+            Direction::BottomToTop => self.pen_state.row == 0,
+        }
+    }
+
+    /// Move the pen one position in the print direction.  Returns false if the pen is at the
+    /// end of the line.
+    fn advance_pen(&mut self) -> bool {
+        let direction = self.window_attrs.print_direction;
+        if self.pen_at_end(direction) {
+            return false;
+        }
+        self.pen_state.advance(direction);
+        true
+    }
+
+    fn retreat_pen(&mut self) -> bool {
+        let direction = match self.window_attrs.print_direction {
+            Direction::LeftToRight => Direction::RightToLeft,
+            Direction::RightToLeft => Direction::LeftToRight,
+            Direction::TopToBottom => Direction::BottomToTop,
+            Direction::BottomToTop => Direction::TopToBottom,
+        };
+        if self.pen_at_end(direction) {
+            return false;
+        }
+        self.pen_state.advance(direction);
+        true
+    }
+
+    /// Place `c` at the pen location with the current pen style and advance the pen in the print
+    /// direction.
+    ///
+    /// At the end of a line, the pen moves to the next line if wordwrap is enabled, otherwise the
+    /// pen stays at the end of the line.
+    pub fn apply_text(&mut self, c: char) {
+        let pen = self.pen_state;
+        self.content[pen.row as usize][pen.column as usize] = Some(StyledChar {
+            c,
+            attributes: pen.attributes,
+            color: pen.color,
+        });
+        if !self.advance_pen() && self.window_attrs.wordwrap {
+            trace!("wrapping after {c:?}");
+            self.carriage_return();
+        }
+    }
+
+    /// Remove the character before the pen and move the pen back one position
+    pub fn backspace(&mut self) {
+        if self.retreat_pen() {
+            let pen = self.pen_state;
+            self.content[pen.row as usize][pen.column as usize] = None;
+        }
+    }
+
+    /// Move the pen to the start of the next line, scrolling the content of the window if the pen
+    /// is on the last line.
+    pub fn carriage_return(&mut self) {
+        let vertical = self.is_vertical();
+        let pen = &mut self.pen_state;
+        if vertical {
+            if pen.column + 1 < self.definition.column_count + 1 {
+                pen.column += 1;
+            } else {
+                for row in self.content.iter_mut() {
+                    row.remove(0);
+                    row.push(None);
+                }
+            }
+        } else if pen.row + 1 < self.definition.row_count + 1 {
+            pen.row += 1;
+        } else {
+            let mut row = self.content.remove(0);
+            row.fill(None);
+            self.content.push(row);
+        }
+        (self.pen_state.row, self.pen_state.column) = self.line_start();
+    }
+
+    /// Clear the current line and move the pen to the start of the line
+    pub fn horizontal_carriage_return(&mut self) {
+        if self.is_vertical() {
+            let column = self.pen_state.column as usize;
+            for row in self.content.iter_mut() {
+                row[column] = None;
+            }
+        } else {
+            self.content[self.pen_state.row as usize].fill(None);
+        }
+        (self.pen_state.row, self.pen_state.column) = self.line_start();
+    }
+
+    /// The text of each row of the window from top to bottom.  Leading and trailing whitespace of
+    /// each row is removed.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{tables::*, renderer::WindowState};
+    /// let mut window = WindowState::new(DefineWindowArgs::new(
+    ///     0, 0, Anchor::TopLeft, true, 0, 0, 1, 9, true, true, true, 1, 1,
+    /// ));
+    /// window.apply_text('A');
+    /// window.carriage_return();
+    /// window.apply_text('B');
+    /// assert_eq!(window.plain_text_rows().collect::<Vec<_>>(), ["A", "B"]);
+    /// ```
+    pub fn plain_text_rows(&self) -> impl Iterator<Item = String> + '_ {
+        self.content.iter().map(|row| {
+            row.iter()
+                .map(|c| c.map(|c| c.c).unwrap_or(' '))
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+    }
+
+    /// The text of all the rows that contain characters separated by `\n`.  Leading and
+    /// trailing whitespace of each row is removed.
+    pub fn to_plain_text(&self) -> String {
+        self.plain_text_rows()
+            .filter(|row| !row.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// A change to a [CaptionState]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptionEvent {
+    /// A window was created or redefined
+    WindowDefined {
+        /// The window identifier
+        id: u8,
+        /// The new definition of the window
+        args: DefineWindowArgs,
+    },
+    /// A hidden window became visible
+    WindowShown {
+        /// The window identifier
+        id: u8,
+    },
+    /// A visible window became hidden
+    WindowHidden {
+        /// The window identifier
+        id: u8,
+    },
+    /// All the text in a window was removed
+    WindowCleared {
+        /// The window identifier
+        id: u8,
+    },
+    /// A window was deleted
+    WindowDeleted {
+        /// The window identifier
+        id: u8,
+    },
+    /// Text was added to a window
+    TextAppended {
+        /// The window identifier
+        window_id: u8,
+        /// The added text
+        text: String,
+        /// The pen attributes of the text
+        pen: SetPenAttributesArgs,
+        /// The pen color of the text
+        color: SetPenColorArgs,
+    },
+    /// The pen attributes of a window changed
+    PenAttributesSet {
+        /// The window identifier
+        window_id: u8,
+        /// The new pen attributes
+        attrs: SetPenAttributesArgs,
+    },
+    /// The pen color of a window changed
+    PenColorSet {
+        /// The window identifier
+        window_id: u8,
+        /// The new pen color
+        color: SetPenColorArgs,
+    },
+    /// The pen of a window was moved
+    PenMoved {
+        /// The window identifier
+        window_id: u8,
+        /// The new location of the pen
+        location: SetPenLocationArgs,
+    },
+    /// All windows were deleted by a [Code::Reset]
+    StreamReset,
+}
+
+/// A receiver of the changes made to a [CaptionState] by [CaptionState::render_to]
+pub trait CaptionRenderer {
+    /// The window `id` was created or redefined
+    fn on_window_defined(&mut self, id: u8, state: &WindowState);
+    /// The hidden window `id` became visible
+    fn on_window_shown(&mut self, id: u8, state: &WindowState);
+    /// The visible window `id` became hidden
+    fn on_window_hidden(&mut self, id: u8);
+    /// All the text in the window `id` was removed
+    fn on_window_cleared(&mut self, id: u8);
+    /// The window `id` was deleted
+    fn on_window_deleted(&mut self, id: u8);
+    /// The character `c` was written to the window `id`.  `pen` is the state of the pen after the
+    /// character has been written.  Carriage returns are provided as `\n`.
+    fn on_text_appended(&mut self, id: u8, c: StyledChar, pen: &PenState);
+}
+
+/// A [CaptionRenderer] that discards all changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NullRenderer;
+
+impl CaptionRenderer for NullRenderer {
+    fn on_window_defined(&mut self, _id: u8, _state: &WindowState) {}
+    fn on_window_shown(&mut self, _id: u8, _state: &WindowState) {}
+    fn on_window_hidden(&mut self, _id: u8) {}
+    fn on_window_cleared(&mut self, _id: u8) {}
+    fn on_window_deleted(&mut self, _id: u8) {}
+    fn on_text_appended(&mut self, _id: u8, _c: StyledChar, _pen: &PenState) {}
+}
+
+/// The state of all the caption windows of a single service
+///
+/// # Examples
+/// ```
+/// # use cea708_types::{*, tables::*, renderer::CaptionState};
+/// let mut service = Service::new(1);
+/// service.push_code(DefineWindowArgs::new(
+///     0, 0, Anchor::BottomMiddle, true, 90, 50, 1, 31, true, true, true, 1, 1,
+/// )).unwrap();
+/// service.push_code(Code::LatinCapitalA).unwrap();
+/// let mut packet = DTVCCPacket::new(0);
+/// packet.push_service(service).unwrap();
+///
+/// let mut state = CaptionState::new();
+/// state.apply_packet(&packet, 1);
+/// let (id, window) = state.visible_windows().next().unwrap();
+/// assert_eq!(id, 0);
+/// assert_eq!(window.to_plain_text(), "A");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaptionState {
+    windows: [Option<WindowState>; 8],
+    current_window: u8,
+}
+
+impl CaptionState {
+    /// Create a new [CaptionState] without any defined windows
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The identifier of the window that commands and text are applied to
+    pub fn current_window(&self) -> u8 {
+        self.current_window
+    }
+
+    /// The state of the window `id` if it has been defined
+    pub fn window(&self, id: u8) -> Option<&WindowState> {
+        self.windows.get(id as usize).and_then(|w| w.as_ref())
+    }
+
+    /// Iterate over the identifiers and states of all the currently visible windows
+    pub fn visible_windows(&self) -> impl Iterator<Item = (u8, &WindowState)> {
+        self.windows
+            .iter()
+            .enumerate()
+            .filter_map(|(id, window)| window.as_ref().map(|window| (id as u8, window)))
+            .filter(|(_id, window)| window.visible)
+    }
+
+    /// Apply the [Code]s of all the service blocks in `packet` with the service number
+    /// `service_no`
+    pub fn apply_packet(&mut self, packet: &DTVCCPacket, service_no: u8) {
+        for service in packet.services() {
+            if service.number() != service_no {
+                continue;
+            }
+            for code in service.codes() {
+                self.apply_code(code);
+            }
+        }
+    }
+
+    /// Apply the [Code]s of all the service blocks in `packet` with the service number
+    /// `service_no` and return the changes as a list of [CaptionEvent]s.
+    ///
+    /// Consecutive characters written to the same window with the same pen style are combined
+    /// into a single [CaptionEvent::TextAppended].  Carriage returns are included in the text as
+    /// `\n`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*, renderer::*};
+    /// let define = DefineWindowArgs::new(
+    ///     0, 0, Anchor::BottomMiddle, true, 90, 50, 1, 31, true, true, false, 1, 1,
+    /// );
+    /// let mut service = Service::new(1);
+    /// service.push_code(define).unwrap();
+    /// service.push_code(Code::LatinCapitalA).unwrap();
+    /// service.push_code(Code::LatinCapitalB).unwrap();
+    /// service.push_code(Code::DisplayWindows(WindowBits::ZERO)).unwrap();
+    /// let mut packet = DTVCCPacket::new(0);
+    /// packet.push_service(service).unwrap();
+    ///
+    /// let mut state = CaptionState::new();
+    /// let events = state.apply_packet_events(&packet, 1);
+    /// assert_eq!(
+    ///     events,
+    ///     [
+    ///         CaptionEvent::WindowDefined { id: 0, args: define },
+    ///         CaptionEvent::TextAppended {
+    ///             window_id: 0,
+    ///             text: "AB".to_string(),
+    ///             pen: define.pen_attributes(),
+    ///             color: define.pen_color(),
+    ///         },
+    ///         CaptionEvent::WindowShown { id: 0 },
+    ///     ]
+    /// );
+    /// ```
+    pub fn apply_packet_events(
+        &mut self,
+        packet: &DTVCCPacket,
+        service_no: u8,
+    ) -> Vec<CaptionEvent> {
+        let mut events: Vec<CaptionEvent> = vec![];
+        for service in packet.services() {
+            if service.number() != service_no {
+                continue;
+            }
+            for code in service.codes() {
+                self.apply_code_with(code, &mut |event| {
+                    if let (
+                        CaptionEvent::TextAppended {
+                            window_id,
+                            text,
+                            pen,
+                            color,
+                        },
+                        Some(CaptionEvent::TextAppended {
+                            window_id: last_window_id,
+                            text: last_text,
+                            pen: last_pen,
+                            color: last_color,
+                        }),
+                    ) = (&event, events.last_mut())
+                    {
+                        if window_id == last_window_id && pen == last_pen && color == last_color {
+                            last_text.push_str(text);
+                            return;
+                        }
+                    }
+                    events.push(event);
+                });
+            }
+        }
+        events
+    }
+
+    /// Apply the [Code]s of all the service blocks in `packet` with the service number
+    /// `service_no` and notify `renderer` of the resulting changes.
+    ///
+    /// A [Code::Reset] is reported as the deletion of every defined window.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*, renderer::*};
+    /// let mut service = Service::new(1);
+    /// service.push_code(DefineWindowArgs::new(
+    ///     0, 0, Anchor::BottomMiddle, true, 90, 50, 1, 31, true, true, true, 1, 1,
+    /// )).unwrap();
+    /// service.push_code(Code::LatinCapitalA).unwrap();
+    /// let mut packet = DTVCCPacket::new(0);
+    /// packet.push_service(service).unwrap();
+    ///
+    /// let mut state = CaptionState::new();
+    /// state.render_to(&packet, 1, &mut NullRenderer);
+    /// assert_eq!(state.window(0).unwrap().to_plain_text(), "A");
+    /// ```
+    pub fn render_to<R: CaptionRenderer>(
+        &mut self,
+        packet: &DTVCCPacket,
+        service_no: u8,
+        renderer: &mut R,
+    ) {
+        let mut events = vec![];
+        let mut defined = Vec::with_capacity(self.windows.len());
+        for service in packet.services() {
+            if service.number() != service_no {
+                continue;
+            }
+            for code in service.codes() {
+                defined.clear();
+                defined.extend(
+                    self.windows
+                        .iter()
+                        .enumerate()
+                        .filter(|(_id, window)| window.is_some())
+                        .map(|(id, _window)| id as u8),
+                );
+                self.apply_code_with(code, &mut |event| events.push(event));
+                for event in events.drain(..) {
+                    match event {
+                        CaptionEvent::WindowDefined { id, .. } => {
+                            if let Some(window) = self.window(id) {
+                                renderer.on_window_defined(id, window);
+                            }
+                        }
+                        CaptionEvent::WindowShown { id } => {
+                            if let Some(window) = self.window(id) {
+                                renderer.on_window_shown(id, window);
+                            }
+                        }
+                        CaptionEvent::WindowHidden { id } => renderer.on_window_hidden(id),
+                        CaptionEvent::WindowCleared { id } => renderer.on_window_cleared(id),
+                        CaptionEvent::WindowDeleted { id } => renderer.on_window_deleted(id),
+                        CaptionEvent::TextAppended {
+                            window_id,
+                            text,
+                            pen,
+                            color,
+                        } => {
+                            if let Some(window) = self.window(window_id) {
+                                for c in text.chars() {
+                                    let c = StyledChar {
+                                        c,
+                                        attributes: pen,
+                                        color,
+                                    };
+                                    renderer.on_text_appended(window_id, c, &window.pen_state);
+                                }
+                            }
+                        }
+                        CaptionEvent::StreamReset => {
+                            for &id in defined.iter() {
+                                renderer.on_window_deleted(id);
+                            }
+                        }
+                        CaptionEvent::PenAttributesSet { .. }
+                        | CaptionEvent::PenColorSet { .. }
+                        | CaptionEvent::PenMoved { .. } => (),
+                    }
+                }
+            }
+        }
+    }
+
+    fn for_each_window<F: FnMut(u8, &mut WindowState)>(&mut self, bits: WindowBits, mut f: F) {
+        for (id, window) in self.windows.iter_mut().enumerate() {
+            if (bits & WindowBits::from_window_id(id as u8)) != WindowBits::NONE {
+                if let Some(window) = window {
+                    f(id as u8, window);
+                }
+            }
+        }
+    }
+
+    fn current_window_mut(&mut self) -> Option<&mut WindowState> {
+        self.windows[self.current_window as usize].as_mut()
+    }
+
+    /// Apply a single [Code] to this [CaptionState]
+    pub fn apply_code(&mut self, code: &Code) {
+        self.apply_code_with(code, &mut |_event| ())
+    }
+
+    fn set_visible<F: FnMut(CaptionEvent)>(
+        id: u8,
+        window: &mut WindowState,
+        visible: bool,
+        emit: &mut F,
+    ) {
+        if window.visible != visible {
+            window.visible = visible;
+            if visible {
+                emit(CaptionEvent::WindowShown { id });
+            } else {
+                emit(CaptionEvent::WindowHidden { id });
+            }
+        }
+    }
+
+    fn apply_code_with<F: FnMut(CaptionEvent)>(&mut self, code: &Code, emit: &mut F) {
+        trace!("applying {code:?}");
+        let current = self.current_window;
+        match code {
+            Code::SetCurrentWindow0 => self.current_window = 0,
+            Code::SetCurrentWindow1 => self.current_window = 1,
+            Code::SetCurrentWindow2 => self.current_window = 2,
+            Code::SetCurrentWindow3 => self.current_window = 3,
+            Code::SetCurrentWindow4 => self.current_window = 4,
+            Code::SetCurrentWindow5 => self.current_window = 5,
+            Code::SetCurrentWindow6 => self.current_window = 6,
+            Code::SetCurrentWindow7 => self.current_window = 7,
+            Code::DefineWindow(args) => {
+                let id = args.window_id & 0x7;
+                if let Some(window) = self.windows[id as usize].as_mut() {
+                    window.redefine(*args);
+                } else {
+                    self.windows[id as usize] = Some(WindowState::new(*args));
+                }
+                self.current_window = id;
+                emit(CaptionEvent::WindowDefined { id, args: *args });
+            }
+            Code::ClearWindows(bits) => self.for_each_window(*bits, |id, w| {
+                w.clear();
+                emit(CaptionEvent::WindowCleared { id });
+            }),
+            Code::DisplayWindows(bits) => {
+                self.for_each_window(*bits, |id, w| Self::set_visible(id, w, true, emit))
+            }
+            Code::HideWindows(bits) => {
+                self.for_each_window(*bits, |id, w| Self::set_visible(id, w, false, emit))
+            }
+            Code::ToggleWindows(bits) => self.for_each_window(*bits, |id, w| {
+                let visible = !w.visible;
+                Self::set_visible(id, w, visible, emit)
+            }),
+            Code::DeleteWindows(bits) => {
+                for (id, window) in self.windows.iter_mut().enumerate() {
+                    if (*bits & WindowBits::from_window_id(id as u8)) != WindowBits::NONE
+                        && window.take().is_some()
+                    {
+                        emit(CaptionEvent::WindowDeleted { id: id as u8 });
+                    }
+                }
+            }
+            Code::Reset => {
+                *self = Self::default();
+                emit(CaptionEvent::StreamReset);
+            }
+            Code::SetPenAttributes(args) => {
+                if let Some(window) = self.current_window_mut() {
+                    window.pen_state.apply_set_pen_attributes(*args);
+                    emit(CaptionEvent::PenAttributesSet {
+                        window_id: current,
+                        attrs: *args,
+                    });
+                }
+            }
+            Code::SetPenColor(args) => {
+                if let Some(window) = self.current_window_mut() {
+                    window.pen_state.apply_set_pen_color(*args);
+                    emit(CaptionEvent::PenColorSet {
+                        window_id: current,
+                        color: *args,
+                    });
+                }
+            }
+            Code::SetWindowAttributes(args) => {
+                if let Some(window) = self.current_window_mut() {
+                    window.window_attrs = *args;
+                }
+            }
+            Code::SetPenLocation(args) => {
+                if let Some(window) = self.current_window_mut() {
+                    window.set_pen_location(*args);
+                    emit(CaptionEvent::PenMoved {
+                        window_id: current,
+                        location: *args,
+                    });
+                }
+            }
+            Code::FF => {
+                if let Some(window) = self.current_window_mut() {
+                    window.clear();
+                    emit(CaptionEvent::WindowCleared { id: current });
+                }
+            }
+            Code::CR => {
+                if let Some(window) = self.current_window_mut() {
+                    window.carriage_return();
+                    emit(CaptionEvent::TextAppended {
+                        window_id: current,
+                        text: "\n".to_string(),
+                        pen: window.pen_state.attributes,
+                        color: window.pen_state.color,
+                    });
+                }
+            }
+            Code::HCR => {
+                if let Some(window) = self.current_window_mut() {
+                    window.horizontal_carriage_return();
+                }
+            }
+            Code::BS => {
+                if let Some(window) = self.current_window_mut() {
+                    window.backspace();
+                }
+            }
+            _ => {
+                let c = match code {
+                    Code::P16(c) => char::from_u32(*c as u32),
+                    _ => code.char(),
+                };
+                if let Some(c) = c {
+                    if let Some(window) = self.current_window_mut() {
+                        window.apply_text(c);
+                        emit(CaptionEvent::TextAppended {
+                            window_id: current,
+                            text: c.to_string(),
+                            pen: window.pen_state.attributes,
+                            color: window.pen_state.color,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tables::*;
+    use crate::tests::*;
+    use crate::Service;
+
+    fn packet(service_no: u8, codes: &[Code]) -> DTVCCPacket {
+        let mut packet = DTVCCPacket::new(0);
+        for chunk in codes.chunks(8) {
+            let service = Service::from_codes(service_no, chunk.iter().cloned()).unwrap();
+            packet.push_service(service).unwrap();
+        }
+        packet
+    }
+
+    fn text(s: &str) -> Vec<Code> {
+        s.chars().map(|c| Code::from_char(c).unwrap()).collect()
+    }
+
+    fn define_window(id: u8, visible: bool, rows: u8, columns: u8) -> Code {
+        Code::DefineWindow(DefineWindowArgs::new(
+            id,
+            0,
+            Anchor::TopLeft,
+            true,
+            0,
+            0,
+            rows - 1,
+            columns - 1,
+            true,
+            true,
+            visible,
+            1,
+            1,
+        ))
+    }
+
+    fn rows(window: &WindowState) -> Vec<String> {
+        window
+            .content
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|c| c.map(|c| c.c).unwrap_or(' '))
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn text_and_carriage_return() {
+        test_init_log();
+        let mut state = CaptionState::new();
+        let mut codes = vec![define_window(2, true, 2, 4)];
+        codes.extend(text("ABCDEF"));
+        codes.push(Code::CR);
+        codes.extend(text("GH"));
+        codes.push(Code::BS);
+        state.apply_packet(&packet(1, &codes), 1);
+        // other services are ignored
+        state.apply_packet(&packet(2, &[Code::FF]), 1);
+        assert_eq!(state.current_window(), 2);
+        let window = state.window(2).unwrap();
+        // without wordwrap, the last column is overwritten
+        assert_eq!(rows(window), ["ABCF", "G   "]);
+        assert_eq!((window.pen_state.row, window.pen_state.column), (1, 1));
+
+        let mut codes = vec![Code::CR];
+        codes.extend(text("IJ"));
+        state.apply_packet(&packet(1, &codes), 1);
+        assert_eq!(rows(state.window(2).unwrap()), ["G   ", "IJ  "]);
+
+        state.apply_packet(&packet(1, &[Code::HCR, Code::LatinCapitalK]), 1);
+        assert_eq!(rows(state.window(2).unwrap()), ["G   ", "K   "]);
+
+        state.apply_packet(&packet(1, &[Code::FF]), 1);
+        assert_eq!(rows(state.window(2).unwrap()), ["    ", "    "]);
+    }
+
+    fn window(rows: u8, columns: u8) -> WindowState {
+        let Code::DefineWindow(args) = define_window(0, true, rows, columns) else {
+            unreachable!();
+        };
+        WindowState::new(args)
+    }
+
+    #[test]
+    fn window_wordwrap() {
+        test_init_log();
+        let mut window = window(2, 3);
+        window.window_attrs.wordwrap = true;
+        "ABCDEFGH".chars().for_each(|c| window.apply_text(c));
+        assert_eq!(rows(&window), ["DEF", "GH "]);
+        assert_eq!(window.to_plain_text(), "DEF\nGH");
+        window.backspace();
+        window.apply_text('I');
+        assert_eq!(window.to_plain_text(), "DEF\nGI");
+        window.horizontal_carriage_return();
+        assert_eq!(window.to_plain_text(), "DEF");
+        assert_eq!((window.pen_state.row, window.pen_state.column), (1, 0));
+    }
+
+    #[test]
+    fn window_print_direction() {
+        test_init_log();
+        let mut window = window(2, 3);
+        window.window_attrs.print_direction = Direction::RightToLeft;
+        window.pen_state.column = 2;
+        "AB".chars().for_each(|c| window.apply_text(c));
+        window.carriage_return();
+        window.apply_text('C');
+        assert_eq!(rows(&window), [" BA", "  C"]);
+
+        let mut window = self::window(3, 2);
+        window.window_attrs.print_direction = Direction::TopToBottom;
+        "ABC".chars().for_each(|c| window.apply_text(c));
+        window.carriage_return();
+        window.apply_text('D');
+        assert_eq!(rows(&window), ["AD", "B ", "C "]);
+        // scrolls the columns
+        window.carriage_return();
+        assert_eq!(rows(&window), ["D ", "  ", "  "]);
+        assert_eq!((window.pen_state.row, window.pen_state.column), (0, 1));
+    }
+
+    #[test]
+    fn pen_state_default() {
+        test_init_log();
+        let pen = PenState::default();
+        assert_eq!((pen.row, pen.column), (0, 0));
+        let Code::DefineWindow(args) = define_window(0, true, 1, 1) else {
+            unreachable!();
+        };
+        assert_eq!(pen.attributes, args.pen_attributes());
+        assert_eq!(pen.color, args.pen_color());
+        let mut pen = pen;
+        pen.apply_set_pen_location(SetPenLocationArgs::new(3, 4));
+        pen.advance(Direction::BottomToTop);
+        assert_eq!((pen.row, pen.column), (2, 4));
+    }
+
+    #[test]
+    fn caption_events() {
+        test_init_log();
+        let mut state = CaptionState::new();
+        let mut codes = vec![define_window(1, false, 2, 8), Code::CR];
+        codes.extend(text("AB"));
+        codes.push(Code::SetCurrentWindow2);
+        codes.extend(text("C"));
+        codes.push(Code::SetCurrentWindow1);
+        codes.push(Code::SetPenLocation(SetPenLocationArgs::new(0, 1)));
+        codes.extend(text("D"));
+        codes.push(Code::ToggleWindows(WindowBits::ONE | WindowBits::TWO));
+        codes.push(Code::DisplayWindows(WindowBits::ONE));
+        let events = state.apply_packet_events(&packet(1, &codes), 1);
+        let window = state.window(1).unwrap();
+        let pen = window.pen_state.attributes;
+        let color = window.pen_state.color;
+        assert_eq!(
+            events,
+            [
+                CaptionEvent::WindowDefined {
+                    id: 1,
+                    args: window.definition
+                },
+                CaptionEvent::TextAppended {
+                    window_id: 1,
+                    text: "\nAB".to_string(),
+                    pen,
+                    color
+                },
+                CaptionEvent::PenMoved {
+                    window_id: 1,
+                    location: SetPenLocationArgs::new(0, 1)
+                },
+                CaptionEvent::TextAppended {
+                    window_id: 1,
+                    text: "D".to_string(),
+                    pen,
+                    color
+                },
+                CaptionEvent::WindowShown { id: 1 },
+            ]
+        );
+
+        let codes = [
+            Code::ClearWindows(WindowBits::ONE | WindowBits::TWO),
+            Code::HideWindows(WindowBits::ONE),
+            Code::DeleteWindows(WindowBits::ONE | WindowBits::TWO),
+            Code::Reset,
+        ];
+        let events = state.apply_packet_events(&packet(1, &codes), 1);
+        assert_eq!(
+            events,
+            [
+                CaptionEvent::WindowCleared { id: 1 },
+                CaptionEvent::WindowHidden { id: 1 },
+                CaptionEvent::WindowDeleted { id: 1 },
+                CaptionEvent::StreamReset,
+            ]
+        );
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordingRenderer {
+        calls: Vec<String>,
+    }
+
+    impl CaptionRenderer for RecordingRenderer {
+        fn on_window_defined(&mut self, id: u8, state: &WindowState) {
+            self.calls
+                .push(format!("defined {id} {}", state.row_count()));
+        }
+        fn on_window_shown(&mut self, id: u8, state: &WindowState) {
+            self.calls
+                .push(format!("shown {id} {}", state.to_plain_text()));
+        }
+        fn on_window_hidden(&mut self, id: u8) {
+            self.calls.push(format!("hidden {id}"));
+        }
+        fn on_window_cleared(&mut self, id: u8) {
+            self.calls.push(format!("cleared {id}"));
+        }
+        fn on_window_deleted(&mut self, id: u8) {
+            self.calls.push(format!("deleted {id}"));
+        }
+        fn on_text_appended(&mut self, id: u8, c: StyledChar, pen: &PenState) {
+            self.calls
+                .push(format!("text {id} {:?} {}", c.c, pen.column));
+        }
+    }
+
+    #[test]
+    fn render_to() {
+        test_init_log();
+        let mut state = CaptionState::new();
+        let mut renderer = RecordingRenderer::default();
+        let mut codes = vec![define_window(1, false, 2, 4), define_window(3, false, 1, 4)];
+        codes.extend(text("AB"));
+        codes.push(Code::SetCurrentWindow1);
+        codes.push(Code::CR);
+        codes.push(Code::DisplayWindows(WindowBits::ONE | WindowBits::THREE));
+        codes.push(Code::HideWindows(WindowBits::THREE));
+        codes.push(Code::ClearWindows(WindowBits::ONE));
+        codes.push(Code::DeleteWindows(WindowBits::ONE));
+        codes.push(Code::Reset);
+        state.render_to(&packet(1, &codes), 1, &mut renderer);
+        assert_eq!(
+            renderer.calls,
+            [
+                "defined 1 2",
+                "defined 3 1",
+                "text 3 'A' 1",
+                "text 3 'B' 2",
+                "text 1 '\\n' 0",
+                "shown 1 ",
+                "shown 3 AB",
+                "hidden 3",
+                "cleared 1",
+                "deleted 1",
+                "deleted 3",
+            ]
+        );
+        assert_eq!(state, CaptionState::new());
+
+        let mut state = CaptionState::new();
+        state.render_to(&packet(1, &codes), 1, &mut NullRenderer);
+        assert_eq!(state, CaptionState::new());
+    }
+
+    #[test]
+    fn window_visibility() {
+        test_init_log();
+        let mut state = CaptionState::new();
+        state.apply_packet(
+            &packet(
+                1,
+                &[
+                    define_window(0, true, 1, 4),
+                    define_window(1, false, 1, 4),
+                    define_window(5, false, 1, 4),
+                ],
+            ),
+            1,
+        );
+        assert_eq!(
+            state
+                .visible_windows()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            [0]
+        );
+        state.apply_packet(
+            &packet(
+                1,
+                &[
+                    Code::DisplayWindows(WindowBits::ONE | WindowBits::FIVE),
+                    Code::HideWindows(WindowBits::ZERO),
+                ],
+            ),
+            1,
+        );
+        assert_eq!(
+            state
+                .visible_windows()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            [1, 5]
+        );
+        state.apply_packet(
+            &packet(
+                1,
+                &[Code::ToggleWindows(
+                    WindowBits::ZERO | WindowBits::ONE | WindowBits::SEVEN,
+                )],
+            ),
+            1,
+        );
+        assert_eq!(
+            state
+                .visible_windows()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            [0, 5]
+        );
+        state.apply_packet(&packet(1, &[Code::DeleteWindows(WindowBits::FIVE)]), 1);
+        assert!(state.window(5).is_none());
+        assert_eq!(
+            state
+                .visible_windows()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            [0]
+        );
+        state.apply_packet(&packet(1, &[Code::Reset]), 1);
+        assert_eq!(state, CaptionState::new());
+    }
+
+    #[test]
+    fn pen_commands() {
+        test_init_log();
+        let mut state = CaptionState::new();
+        let attrs = SetPenAttributesArgs::new(
+            PenSize::Large,
+            FontStyle::CasualFontType,
+            TextTag::Dialog,
+            TextOffset::Normal,
+            true,
+            false,
+            EdgeType::None,
+        );
+        let color = SetPenColorArgs::new(
+            Color::WHITE,
+            Opacity::Solid,
+            Color::BLACK,
+            Opacity::Translucent,
+            Color::BLACK,
+        );
+        let mut codes = vec![
+            define_window(3, true, 3, 8),
+            define_window(4, true, 3, 8),
+            Code::SetCurrentWindow3,
+            Code::SetPenAttributes(attrs),
+            Code::SetPenColor(color),
+            Code::SetPenLocation(SetPenLocationArgs::new(2, 5)),
+        ];
+        codes.extend(text("AB"));
+        state.apply_packet(&packet(1, &codes), 1);
+        let window = state.window(3).unwrap();
+        assert_eq!(window.pen_state.attributes, attrs);
+        assert_eq!(window.pen_state.color, color);
+        assert_eq!(rows(window)[2], "     AB ");
+        assert_eq!((window.pen_state.row, window.pen_state.column), (2, 7));
+        let window = state.window(4).unwrap();
+        assert_eq!(
+            window.pen_state.attributes,
+            window.definition.pen_attributes()
+        );
+
+        // redefinition keeps the content
+        state.apply_packet(&packet(1, &[define_window(3, false, 3, 8)]), 1);
+        let window = state.window(3).unwrap();
+        assert!(!window.visible);
+        assert_eq!(rows(window)[2], "     AB ");
+    }
+
+    #[test]
+    fn pen_outside_window() {
+        test_init_log();
+        let mut state = CaptionState::new();
+        let mut codes = vec![
+            define_window(0, true, 2, 3),
+            Code::SetPenLocation(SetPenLocationArgs::new(14, 41)),
+        ];
+        codes.extend(text("AB"));
+        state.apply_packet(&packet(1, &codes), 1);
+        let window = state.window(0).unwrap();
+        assert_eq!(rows(window), ["   ", "  B"]);
+        let pen = window.pen_state();
+        assert_eq!((pen.row, pen.column), (1, 2));
+        assert_eq!(window.content().len(), window.row_count() as usize);
+
+        // shrinking the window moves the pen inside the window
+        state.apply_packet(&packet(1, &[define_window(0, true, 1, 2)]), 1);
+        let window = state.window(0).unwrap();
+        let pen = window.pen_state();
+        assert_eq!((pen.row, pen.column), (0, 1));
+        state.apply_packet(&packet(1, &text("C")), 1);
+        assert_eq!(rows(state.window(0).unwrap()), [" C"]);
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Serializing raw bytes as base64 strings with serde
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+pub(crate) fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&STANDARD.encode(data))
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    STANDARD.decode(s).map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tables::*;
+    use crate::tests::*;
+    use crate::*;
+
+    #[test]
+    fn packet_json_roundtrip() {
+        test_init_log();
+        let mut service = Service::new(1);
+        service
+            .push_code(
+                DefineWindowArgs::builder()
+                    .window_id(1)
+                    .visible(true)
+                    .build(),
+            )
+            .unwrap();
+        service.push_code(Code::LatinCapitalA).unwrap();
+        service.push_code(Code::Ext1(Ext1::TradeMarkSign)).unwrap();
+        service.push_code(Code::Unknown(vec![0x93])).unwrap();
+        let mut packet = DTVCCPacket::new(2);
+        packet.push_service(service).unwrap();
+        packet.push_service(Service::new(12)).unwrap();
+
+        let json = serde_json::to_string(&packet).unwrap();
+        log::debug!("{json}");
+        assert!(json.contains("\"Unknown\":\"kw==\""));
+        assert!(json.contains("\"anchor_point\":\"TopLeft\""));
+        let parsed: DTVCCPacket = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, packet);
+    }
+
+    #[test]
+    fn cea608_framerate_json_roundtrip() {
+        test_init_log();
+        let cea608 = Cea608::Field2(0x94, 0x20);
+        let json = serde_json::to_string(&cea608).unwrap();
+        assert_eq!(serde_json::from_str::<Cea608>(&json).unwrap(), cea608);
+        let framerate = Framerate::new(30000, 1001);
+        let json = serde_json::to_string(&framerate).unwrap();
+        assert_eq!(serde_json::from_str::<Framerate>(&json).unwrap(), framerate);
+
+        assert!(serde_json::from_str::<Code>("{\"Unknown\":\"not base64!\"}").is_err());
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Deserializing types with invariants through their checked constructors
+
+use serde::Deserialize;
+
+use crate::{tables, DTVCCPacket, Service, WriterError};
+
+/// Errors produced when deserialized data does not uphold the invariants of a type
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum InvalidData {
+    #[error("DTVCCPacket sequence numbers must be between 0 and 3 inclusive, not {0}")]
+    SequenceNo(u8),
+    #[error(transparent)]
+    Writer(#[from] WriterError),
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ServiceRepr {
+    number: u8,
+    codes: Vec<tables::Code>,
+}
+
+impl TryFrom<ServiceRepr> for Service {
+    type Error = InvalidData;
+
+    fn try_from(repr: ServiceRepr) -> Result<Self, Self::Error> {
+        let mut service = Service::try_new(repr.number)?;
+        for code in repr.codes {
+            service.push_code(code)?;
+        }
+        Ok(service)
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DTVCCPacketRepr {
+    seq_no: u8,
+    services: Vec<Service>,
+    null_padding: usize,
+    truncated: bool,
+}
+
+impl TryFrom<DTVCCPacketRepr> for DTVCCPacket {
+    type Error = InvalidData;
+
+    fn try_from(repr: DTVCCPacketRepr) -> Result<Self, Self::Error> {
+        if repr.seq_no > 3 {
+            return Err(InvalidData::SequenceNo(repr.seq_no));
+        }
+        let mut packet = DTVCCPacket::new(repr.seq_no);
+        for service in repr.services {
+            packet.push_service(service)?;
+        }
+        packet.push_null_padding(repr.null_padding)?;
+        packet.truncated = repr.truncated;
+        Ok(packet)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tables::*;
+    use crate::tests::*;
+    use crate::*;
+
+    #[test]
+    fn service_json_invalid() {
+        test_init_log();
+        let service = Service::from_codes(1, [Code::LatinCapitalA]).unwrap();
+        let json = serde_json::to_string(&service).unwrap();
+        assert_eq!(json, "{\"number\":1,\"codes\":[\"LatinCapitalA\"]}");
+        assert_eq!(serde_json::from_str::<Service>(&json).unwrap(), service);
+
+        let err = serde_json::from_str::<Service>("{\"number\":200,\"codes\":[]}").unwrap_err();
+        assert!(err.to_string().contains("Invalid service number 200"));
+        assert!(serde_json::from_str::<Service>("{\"number\":64,\"codes\":[]}").is_err());
+
+        // the NULL Service cannot contain any codes
+        assert!(
+            serde_json::from_str::<Service>("{\"number\":0,\"codes\":[\"LatinCapitalA\"]}")
+                .is_err()
+        );
+
+        // more than 31 bytes of codes
+        let codes = ["\"LatinCapitalA\""; 32].join(",");
+        let err = serde_json::from_str::<Service>(&format!("{{\"number\":1,\"codes\":[{codes}]}}"))
+            .unwrap_err();
+        assert!(err.to_string().contains("overflow by 1 bytes in service 1"));
+    }
+
+    #[test]
+    fn packet_json_invalid() {
+        test_init_log();
+        let packet = DTVCCPacket::new(3);
+        let json = serde_json::to_string(&packet).unwrap();
+        assert_eq!(serde_json::from_str::<DTVCCPacket>(&json).unwrap(), packet);
+
+        let err = serde_json::from_str::<DTVCCPacket>(
+            "{\"seq_no\":4,\"services\":[],\"null_padding\":0,\"truncated\":false}",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not 4"));
+
+        // services larger than 128 bytes in total
+        let codes = ["\"LatinCapitalA\""; 31].join(",");
+        let service = format!("{{\"number\":1,\"codes\":[{codes}]}}");
+        let services = [service.as_str(); 5].join(",");
+        let err = serde_json::from_str::<DTVCCPacket>(&format!(
+            "{{\"seq_no\":0,\"services\":[{services}],\"null_padding\":0,\"truncated\":false}}"
+        ))
+        .unwrap_err();
+        assert!(err.to_string().contains("overflow by 1 bytes in service 1"));
+
+        // null padding larger than the packet
+        assert!(serde_json::from_str::<DTVCCPacket>(
+            "{\"seq_no\":0,\"services\":[],\"null_padding\":128,\"truncated\":false}"
+        )
+        .is_err());
+
+        // invalid services are rejected inside a packet
+        assert!(serde_json::from_str::<DTVCCPacket>(
+            "{\"seq_no\":0,\"services\":[{\"number\":200,\"codes\":[]}],\"null_padding\":0,\"truncated\":false}"
+        )
+        .is_err());
+    }
+}
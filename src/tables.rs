@@ -16,10 +16,23 @@ pub enum CodeError {
         /// The actual size
         actual: usize,
     },
+    /// The data ended in the middle of a [Code]
+    #[error(
+        "The code at offset {offset} requires {expected} bytes but only {actual} are available"
+    )]
+    UnexpectedEnd {
+        /// The offset of the start of the truncated [Code] in the data
+        offset: usize,
+        /// The expected size of the [Code]
+        expected: usize,
+        /// The number of bytes available from `offset`
+        actual: usize,
+    },
 }
 
 /// Enum representing characters or commands accessible through the [Ext1] byte
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 // must be ordered the same as the byte values
 pub enum Ext1 {
     TransparentSpace,
@@ -49,11 +62,12 @@ pub enum Ext1 {
     UpperLeftBorder,
     ClosedCaptionSign,
 
-    Unknown(Vec<u8>),
+    Unknown(#[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))] Vec<u8>),
 }
 
 /// Enum of all possible characters or commands available within [Service](super::Service) block
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 // must be ordered the same as the byte values for binary search to be successful
 pub enum Code {
     NUL,
@@ -283,11 +297,84 @@ pub enum Code {
     LatinLowerYWithAcute,
     LatinLowerThorn,
     LatinLowerYWithDiaeresis,
-    Unknown(Vec<u8>),
+    Unknown(#[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))] Vec<u8>),
+}
+
+/// The code set a [Code] belongs to as defined by CEA-708
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CodeCategory {
+    /// Miscellaneous control codes (0x00-0x1F)
+    C0,
+    /// ASCII printable characters (0x20-0x7F)
+    G0,
+    /// Caption control codes (0x80-0x9F)
+    C1,
+    /// ISO 8859-1 Latin-1 characters (0xA0-0xFF)
+    G1,
+    /// Extended miscellaneous control codes (0x10 0x00-0x1F)
+    C2,
+    /// Extended miscellaneous characters (0x10 0x20-0x7F)
+    G2,
+    /// Extended control code set 2 (0x10 0x80-0x9F)
+    C3,
+    /// Future characters and icons (0x10 0xA0-0xFF)
+    G3,
+}
+
+impl CodeCategory {
+    /// The number of [CodeCategory]s
+    pub const COUNT: usize = 8;
+
+    /// The index of this [CodeCategory] in the range `[0, COUNT)`
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::CodeCategory;
+    /// assert_eq!(CodeCategory::C0.index(), 0);
+    /// assert_eq!(CodeCategory::G3.index(), CodeCategory::COUNT - 1);
+    /// ```
+    pub const fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Whether this [CodeCategory] contains commands rather than characters
+    pub const fn is_control(&self) -> bool {
+        matches!(
+            self,
+            CodeCategory::C0 | CodeCategory::C1 | CodeCategory::C2 | CodeCategory::C3
+        )
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            0x00..=0x1F => CodeCategory::C0,
+            0x20..=0x7F => CodeCategory::G0,
+            0x80..=0x9F => CodeCategory::C1,
+            0xA0..=0xFF => CodeCategory::G1,
+        }
+    }
+
+    fn from_ext1_byte(byte: u8) -> Self {
+        match byte {
+            0x00..=0x1F => CodeCategory::C2,
+            0x20..=0x7F => CodeCategory::G2,
+            0x80..=0x9F => CodeCategory::C3,
+            0xA0..=0xFF => CodeCategory::G3,
+        }
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        match bytes {
+            [0x10, ext1, ..] => Self::from_ext1_byte(*ext1),
+            [byte, ..] => Self::from_byte(*byte),
+            [] => CodeCategory::C0,
+        }
+    }
 }
 
 /// A collection of 8 Windows (0-7) represented as a bitfield
-#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WindowBits(u8);
 
 impl From<u8> for WindowBits {
@@ -377,7 +464,8 @@ impl std::fmt::Debug for WindowBits {
 }
 
 /// Anchor points
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Anchor {
     TopLeft,
     TopMiddle,
@@ -444,8 +532,102 @@ impl From<Anchor> for u8 {
     }
 }
 
+/// The vertical component of an [Anchor]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnchorRow {
+    Top,
+    Center,
+    Bottom,
+}
+
+/// The horizontal component of an [Anchor]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnchorCol {
+    Left,
+    Middle,
+    Right,
+}
+
+impl Anchor {
+    /// Construct an [Anchor] from its row and column components
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::{Anchor, AnchorRow, AnchorCol};
+    /// assert_eq!(Anchor::from_position(AnchorRow::Bottom, AnchorCol::Middle), Anchor::BottomMiddle);
+    /// ```
+    pub fn from_position(row: AnchorRow, col: AnchorCol) -> Anchor {
+        let row = match row {
+            AnchorRow::Top => 0,
+            AnchorRow::Center => 1,
+            AnchorRow::Bottom => 2,
+        };
+        let col = match col {
+            AnchorCol::Left => 0,
+            AnchorCol::Middle => 1,
+            AnchorCol::Right => 2,
+        };
+        Anchor::from(row * 3 + col)
+    }
+
+    /// Whether this [Anchor] is one of the 9 anchor points defined by CEA-708
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Anchor;
+    /// assert!(Anchor::CenterRight.is_defined());
+    /// assert!(!Anchor::Undefined9.is_defined());
+    /// ```
+    pub fn is_defined(&self) -> bool {
+        u8::from(*self) <= 8
+    }
+
+    /// The normalized vertical position of this [Anchor]
+    ///
+    /// Returns 0.0 for the top, 0.5 for the center and 1.0 for the bottom.  Undefined anchor
+    /// points will return None.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Anchor;
+    /// assert_eq!(Anchor::TopRight.row_f32(), Some(0.0));
+    /// assert_eq!(Anchor::CenterLeft.row_f32(), Some(0.5));
+    /// assert_eq!(Anchor::BottomMiddle.row_f32(), Some(1.0));
+    /// assert_eq!(Anchor::Undefined12.row_f32(), None);
+    /// ```
+    pub fn row_f32(&self) -> Option<f32> {
+        if !self.is_defined() {
+            return None;
+        }
+        Some((u8::from(*self) / 3) as f32 * 0.5)
+    }
+
+    /// The normalized horizontal position of this [Anchor]
+    ///
+    /// Returns 0.0 for the left, 0.5 for the middle and 1.0 for the right.  Undefined anchor
+    /// points will return None.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Anchor;
+    /// assert_eq!(Anchor::BottomLeft.col_f32(), Some(0.0));
+    /// assert_eq!(Anchor::TopMiddle.col_f32(), Some(0.5));
+    /// assert_eq!(Anchor::CenterRight.col_f32(), Some(1.0));
+    /// assert_eq!(Anchor::Undefined15.col_f32(), None);
+    /// ```
+    pub fn col_f32(&self) -> Option<f32> {
+        if !self.is_defined() {
+            return None;
+        }
+        Some((u8::from(*self) % 3) as f32 * 0.5)
+    }
+}
+
 /// Arguments required for the [Code::DefineWindow] command
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DefineWindowArgs {
     pub window_id: u8, // [0, 7]
     pub priority: u8,  // [0, 7]
@@ -498,6 +680,27 @@ impl From<DefineWindowArgs> for [u8; 6] {
     }
 }
 
+/// A [DefineWindowArgs] for window 0 anchored at the top left with all other values zeroed
+impl Default for DefineWindowArgs {
+    fn default() -> Self {
+        Self::new(
+            0,
+            0,
+            Anchor::TopLeft,
+            false,
+            0,
+            0,
+            0,
+            0,
+            false,
+            false,
+            false,
+            0,
+            0,
+        )
+    }
+}
+
 impl DefineWindowArgs {
     #[allow(clippy::too_many_arguments)]
     pub const fn new(
@@ -546,6 +749,39 @@ impl DefineWindowArgs {
     pub fn pen_color(&self) -> SetPenColorArgs {
         PREDEFINED_PEN_STYLES_COLOR[self.pen_style_id.max(1) as usize - 1]
     }
+
+    /// The `(horizontal, vertical)` position of the anchor point of the window as a fraction of
+    /// the screen size in the range `0.0..=1.0`
+    ///
+    /// Relative positions are a percentage of the screen size and absolute positions are in the
+    /// 210x75 grid of 16:9 content.  [Anchor::col_f32] and [Anchor::row_f32] provide the point
+    /// of the window that is placed at this position.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::*;
+    /// let define = DefineWindowArgs::new(
+    ///     0, 0, Anchor::BottomMiddle, true, 90, 50, 1, 31, true, true, true, 1, 1,
+    /// );
+    /// assert_eq!(define.anchor_position_f32(), (0.5, 0.9));
+    /// let define = DefineWindowArgs::new(
+    ///     0, 0, Anchor::TopLeft, false, 74, 0, 1, 31, true, true, true, 1, 1,
+    /// );
+    /// assert_eq!(define.anchor_position_f32(), (0.0, 1.0));
+    /// ```
+    pub fn anchor_position_f32(&self) -> (f32, f32) {
+        if self.relative_positioning {
+            (
+                self.anchor_horizontal.min(99) as f32 / 100.,
+                self.anchor_vertical.min(99) as f32 / 100.,
+            )
+        } else {
+            (
+                self.anchor_horizontal.min(209) as f32 / 209.,
+                self.anchor_vertical.min(74) as f32 / 74.,
+            )
+        }
+    }
 }
 
 static PREDEFINED_WINDOW_STYLES: [SetWindowAttributesArgs; 7] = [
@@ -649,7 +885,7 @@ static PREDEFINED_WINDOW_STYLES: [SetWindowAttributesArgs; 7] = [
     },
 ];
 
-static PREDEFINED_PEN_STYLES_ATTRIBUTES: [SetPenAttributesArgs; 7] = [
+pub(crate) static PREDEFINED_PEN_STYLES_ATTRIBUTES: [SetPenAttributesArgs; 7] = [
     // style 1
     SetPenAttributesArgs {
         pen_size: PenSize::Standard,
@@ -722,7 +958,7 @@ static PREDEFINED_PEN_STYLES_ATTRIBUTES: [SetPenAttributesArgs; 7] = [
     },
 ];
 
-static PREDEFINED_PEN_STYLES_COLOR: [SetPenColorArgs; 7] = [
+pub(crate) static PREDEFINED_PEN_STYLES_COLOR: [SetPenColorArgs; 7] = [
     // style 1
     SetPenColorArgs {
         foreground_color: Color::WHITE,
@@ -782,7 +1018,8 @@ static PREDEFINED_PEN_STYLES_COLOR: [SetPenColorArgs; 7] = [
 ];
 
 /// Text tustification options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Justify {
     Left,
     Right,
@@ -813,8 +1050,24 @@ impl From<Justify> for u8 {
     }
 }
 
+impl Justify {
+    /// Whether the end of line positions can be computed without word wrapping.  Returns false
+    /// only for [Justify::Full].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Justify;
+    /// assert!(Justify::Center.is_aligned());
+    /// assert!(!Justify::Full.is_aligned());
+    /// ```
+    pub fn is_aligned(self) -> bool {
+        !matches!(self, Justify::Full)
+    }
+}
+
 /// Text/Scroll/etc direction options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     LeftToRight,
     RightToLeft,
@@ -845,8 +1098,52 @@ impl From<Direction> for u8 {
     }
 }
 
+impl Direction {
+    /// The [Direction] in the reverse orientation
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Direction;
+    /// assert_eq!(Direction::LeftToRight.opposite(), Direction::RightToLeft);
+    /// assert_eq!(Direction::BottomToTop.opposite(), Direction::TopToBottom);
+    /// ```
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::LeftToRight => Direction::RightToLeft,
+            Direction::RightToLeft => Direction::LeftToRight,
+            Direction::TopToBottom => Direction::BottomToTop,
+            Direction::BottomToTop => Direction::TopToBottom,
+        }
+    }
+
+    /// Whether this [Direction] is [Direction::LeftToRight] or [Direction::RightToLeft]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Direction;
+    /// assert!(Direction::RightToLeft.is_horizontal());
+    /// assert!(!Direction::TopToBottom.is_horizontal());
+    /// ```
+    pub fn is_horizontal(self) -> bool {
+        matches!(self, Direction::LeftToRight | Direction::RightToLeft)
+    }
+
+    /// Whether this [Direction] is [Direction::TopToBottom] or [Direction::BottomToTop]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Direction;
+    /// assert!(Direction::BottomToTop.is_vertical());
+    /// assert!(!Direction::LeftToRight.is_vertical());
+    /// ```
+    pub fn is_vertical(self) -> bool {
+        !self.is_horizontal()
+    }
+}
+
 /// Display effect options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DisplayEffect {
     Snap,
     Fade,
@@ -878,7 +1175,8 @@ impl From<DisplayEffect> for u8 {
 }
 
 /// Opacity options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Opacity {
     Solid,
     Flash,
@@ -910,7 +1208,8 @@ impl From<Opacity> for u8 {
 }
 
 /// Color value options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ColorValue {
     None,
     OneThird,
@@ -942,7 +1241,8 @@ impl From<ColorValue> for u8 {
 }
 
 /// A RGB color
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub r: ColorValue,
     pub g: ColorValue,
@@ -965,6 +1265,13 @@ impl From<u8> for Color {
     }
 }
 
+/// [Color::BLACK]
+impl Default for Color {
+    fn default() -> Self {
+        Color::BLACK
+    }
+}
+
 impl Color {
     pub const BLACK: Color = Color::new(ColorValue::None, ColorValue::None, ColorValue::None);
     pub const WHITE: Color = Color::new(ColorValue::Full, ColorValue::Full, ColorValue::Full);
@@ -992,7 +1299,8 @@ impl From<u8> for ColorOpacity {
 }
 
 /// Border options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BorderType {
     None,
     Raised,
@@ -1035,8 +1343,23 @@ impl From<u8> for BorderType {
     }
 }
 
+impl BorderType {
+    /// Whether this [BorderType] is [BorderType::ShadowLeft] or [BorderType::ShadowRight]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::BorderType;
+    /// assert!(BorderType::ShadowLeft.is_shadow());
+    /// assert!(!BorderType::Raised.is_shadow());
+    /// ```
+    pub fn is_shadow(self) -> bool {
+        matches!(self, BorderType::ShadowLeft | BorderType::ShadowRight)
+    }
+}
+
 /// Arguments required for the [Code::SetWindowAttributes] command
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetWindowAttributesArgs {
     pub justify: Justify,
     pub print_direction: Direction,
@@ -1089,6 +1412,13 @@ impl From<[u8; 4]> for SetWindowAttributesArgs {
     }
 }
 
+/// The attributes of the predefined window style 1
+impl Default for SetWindowAttributesArgs {
+    fn default() -> Self {
+        PREDEFINED_WINDOW_STYLES[0]
+    }
+}
+
 impl SetWindowAttributesArgs {
     #[allow(clippy::too_many_arguments)]
     pub const fn new(
@@ -1121,7 +1451,8 @@ impl SetWindowAttributesArgs {
 }
 
 /// Pen size options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PenSize {
     Small,
     Standard,
@@ -1153,7 +1484,8 @@ impl From<u8> for PenSize {
 }
 
 /// Font style options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontStyle {
     Default,
     MonospacedWithSerifs,
@@ -1197,7 +1529,8 @@ impl From<u8> for FontStyle {
 }
 
 /// Text tag options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextTag {
     Dialog,
     SourceOrSpeakerId,
@@ -1265,7 +1598,8 @@ impl From<u8> for TextTag {
 }
 
 /// Text offset options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextOffset {
     Subscript,
     Normal,
@@ -1297,7 +1631,8 @@ impl From<u8> for TextOffset {
 }
 
 /// Edge type options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EdgeType {
     None,
     Raised,
@@ -1340,8 +1675,23 @@ impl From<EdgeType> for u8 {
     }
 }
 
+impl EdgeType {
+    /// Whether this [EdgeType] is [EdgeType::LeftDropShadow] or [EdgeType::RightDropShadow]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::EdgeType;
+    /// assert!(EdgeType::RightDropShadow.is_drop_shadow());
+    /// assert!(!EdgeType::Uniform.is_drop_shadow());
+    /// ```
+    pub fn is_drop_shadow(self) -> bool {
+        matches!(self, EdgeType::LeftDropShadow | EdgeType::RightDropShadow)
+    }
+}
+
 /// Arguments required for the [Code::SetPenAttributes] command
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetPenAttributesArgs {
     pub pen_size: PenSize,
     pub font_style: FontStyle,
@@ -1378,6 +1728,13 @@ impl From<[u8; 2]> for SetPenAttributesArgs {
     }
 }
 
+/// The attributes of the predefined pen style 1
+impl Default for SetPenAttributesArgs {
+    fn default() -> Self {
+        PREDEFINED_PEN_STYLES_ATTRIBUTES[0]
+    }
+}
+
 impl SetPenAttributesArgs {
     pub const fn new(
         pen_size: PenSize,
@@ -1401,14 +1758,15 @@ impl SetPenAttributesArgs {
 }
 
 #[derive(Debug, Clone)]
-struct CodeMap<'a> {
+pub(crate) struct CodeMap<'a> {
     pub cea708_bytes: &'a [u8],
     pub code: Code,
     pub utf8: Option<char>,
 }
 
 /// Arguments required for the [Code::SetPenColor] command
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetPenColorArgs {
     pub foreground_color: Color,
     pub foreground_opacity: Opacity,
@@ -1417,6 +1775,13 @@ pub struct SetPenColorArgs {
     pub edge_color: Color,
 }
 
+/// The colors of the predefined pen style 1
+impl Default for SetPenColorArgs {
+    fn default() -> Self {
+        PREDEFINED_PEN_STYLES_COLOR[0]
+    }
+}
+
 impl SetPenColorArgs {
     pub const fn new(
         foreground_color: Color,
@@ -1461,7 +1826,8 @@ impl From<SetPenColorArgs> for [u8; 3] {
 }
 
 /// Arguments required for the [Code::SetPenLocation] command
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetPenLocationArgs {
     pub row: u8,    // [0, 14]
     pub column: u8, // [0, 31/41]
@@ -1488,6 +1854,130 @@ impl From<SetPenLocationArgs> for [u8; 2] {
     }
 }
 
+macro_rules! args_builder {
+    ($(#[$meta:meta])* $builder:ident, $args:ident, { $($field:ident: $ty:ty),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        #[must_use]
+        pub struct $builder {
+            args: $args,
+        }
+
+        impl $builder {
+            #[doc = concat!("Create a new [", stringify!($builder), "] starting from [", stringify!($args), "::default()]")]
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            $(
+                #[doc = concat!("Set `", stringify!($field), "`")]
+                pub fn $field(mut self, $field: $ty) -> Self {
+                    self.args.$field = $field;
+                    self
+                }
+            )*
+
+            #[doc = concat!("Build the [", stringify!($args), "]")]
+            pub fn build(self) -> $args {
+                self.args
+            }
+        }
+
+        impl $args {
+            #[doc = concat!("Create a [", stringify!($builder), "] for building a [", stringify!($args), "]")]
+            pub fn builder() -> $builder {
+                $builder::new()
+            }
+        }
+    };
+}
+
+args_builder!(
+    /// Builder for [SetWindowAttributesArgs]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::*;
+    /// let args = SetWindowAttributesArgs::builder()
+    ///     .justify(Justify::Center)
+    ///     .wordwrap(true)
+    ///     .build();
+    /// assert_eq!(args.justify, Justify::Center);
+    /// assert_eq!(args.fill_color, Color::BLACK);
+    /// ```
+    SetWindowAttributesArgsBuilder,
+    SetWindowAttributesArgs,
+    {
+        justify: Justify,
+        print_direction: Direction,
+        scroll_direction: Direction,
+        wordwrap: bool,
+        display_effect: DisplayEffect,
+        effect_direction: Direction,
+        effect_speed: u8,
+        fill_color: Color,
+        fill_opacity: Opacity,
+        border_type: BorderType,
+        border_color: Color,
+    }
+);
+
+args_builder!(
+    /// Builder for [DefineWindowArgs]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::*;
+    /// let args = DefineWindowArgs::builder()
+    ///     .window_id(2)
+    ///     .anchor_point(Anchor::BottomMiddle)
+    ///     .visible(true)
+    ///     .build();
+    /// assert_eq!(args.window_id, 2);
+    /// assert_eq!(args.priority, 0);
+    /// ```
+    DefineWindowArgsBuilder,
+    DefineWindowArgs,
+    {
+        window_id: u8,
+        priority: u8,
+        anchor_point: Anchor,
+        relative_positioning: bool,
+        anchor_vertical: u8,
+        anchor_horizontal: u8,
+        row_count: u8,
+        column_count: u8,
+        row_lock: bool,
+        column_lock: bool,
+        visible: bool,
+        window_style_id: u8,
+        pen_style_id: u8,
+    }
+);
+
+args_builder!(
+    /// Builder for [SetPenAttributesArgs]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::*;
+    /// let args = SetPenAttributesArgs::builder().italics(true).build();
+    /// assert!(args.italics);
+    /// assert_eq!(args.pen_size, PenSize::Standard);
+    /// ```
+    SetPenAttributesArgsBuilder,
+    SetPenAttributesArgs,
+    {
+        pen_size: PenSize,
+        font_style: FontStyle,
+        text_tag: TextTag,
+        offset: TextOffset,
+        italics: bool,
+        underline: bool,
+        edge_type: EdgeType,
+    }
+);
+
 macro_rules! code_map_bytes {
     ($bytes:expr, $code:expr, $utf8:expr) => {
         CodeMap {
@@ -1505,7 +1995,7 @@ macro_rules! code_map_single_byte {
 }
 
 // needs to be sorted by bytes and Code
-static CODE_MAP_TABLE: [CodeMap; 234] = [
+pub(crate) static CODE_MAP_TABLE: [CodeMap; 234] = [
     code_map_single_byte!(0x00, Code::NUL, None),
     code_map_single_byte!(0x03, Code::ETX, None),
     code_map_single_byte!(0x08, Code::BS, None),
@@ -1818,35 +2308,72 @@ impl Code {
     /// assert_eq!(Code::LatinCapitalA.byte_len(), 1);
     /// ```
     pub fn byte_len(&self) -> usize {
-        if let Ok(idx) = CODE_MAP_TABLE.binary_search_by_key(&self, |code_map| &code_map.code) {
-            return CODE_MAP_TABLE[idx].cea708_bytes.len();
+        if let Some(len) = self.static_byte_len() {
+            return len;
         }
         match self {
-            Code::Ext1(ext1) => ext1.byte_len(),
-            Code::P16(_) => 3,
-            Code::ClearWindows(_args) => 2,
-            Code::DisplayWindows(_args) => 2,
-            Code::HideWindows(_args) => 2,
-            Code::ToggleWindows(_args) => 2,
-            Code::DeleteWindows(_args) => 2,
-            Code::SetPenAttributes(_args) => 3,
-            Code::SetPenColor(_args) => 4,
-            Code::SetPenLocation(_args) => 3,
-            Code::SetWindowAttributes(_args) => 5,
-            Code::DefineWindow(_args) => 7,
+            Code::Ext1(ext1) => ext1.byte_len() + 1,
             Code::Unknown(data) => data.len(),
             _ => unreachable!(),
         }
     }
 
-    fn parse_element(data: &[u8]) -> Result<Code, CodeError> {
+    /// The length in bytes of a [Code::ClearWindows], [Code::DisplayWindows],
+    /// [Code::HideWindows], [Code::ToggleWindows] or [Code::DeleteWindows] command
+    pub const WINDOWS_COMMAND_LEN: usize = 2;
+    /// The length in bytes of a [Code::Delay] command
+    pub const DELAY_LEN: usize = 2;
+    /// The length in bytes of a [Code::SetPenAttributes] command
+    pub const SET_PEN_ATTRIBUTES_LEN: usize = 3;
+    /// The length in bytes of a [Code::SetPenColor] command
+    pub const SET_PEN_COLOR_LEN: usize = 4;
+    /// The length in bytes of a [Code::SetPenLocation] command
+    pub const SET_PEN_LOCATION_LEN: usize = 3;
+    /// The length in bytes of a [Code::SetWindowAttributes] command
+    pub const SET_WINDOW_ATTRIBUTES_LEN: usize = 5;
+    /// The length in bytes of a [Code::DefineWindow] command
+    pub const DEFINE_WINDOW_LEN: usize = 7;
+
+    /// The length in bytes of this [Code] if it can be determined without inspecting any
+    /// variable length data.  Returns None for [Code::Unknown] and [Ext1::Unknown].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::*;
+    /// const LEN: Option<usize> = Code::SetPenLocation(SetPenLocationArgs::new(0, 0)).static_byte_len();
+    /// assert_eq!(LEN, Some(Code::SET_PEN_LOCATION_LEN));
+    /// assert_eq!(Code::Unknown(vec![0x93]).static_byte_len(), None);
+    /// ```
+    pub const fn static_byte_len(&self) -> Option<usize> {
+        Some(match self {
+            Code::Ext1(Ext1::Unknown(_)) | Code::Unknown(_) => return None,
+            Code::Ext1(_) => 2,
+            Code::P16(_) => 3,
+            Code::ClearWindows(_)
+            | Code::DisplayWindows(_)
+            | Code::HideWindows(_)
+            | Code::ToggleWindows(_)
+            | Code::DeleteWindows(_) => Code::WINDOWS_COMMAND_LEN,
+            Code::Delay(_) => Code::DELAY_LEN,
+            Code::SetPenAttributes(_) => Code::SET_PEN_ATTRIBUTES_LEN,
+            Code::SetPenColor(_) => Code::SET_PEN_COLOR_LEN,
+            Code::SetPenLocation(_) => Code::SET_PEN_LOCATION_LEN,
+            Code::SetWindowAttributes(_) => Code::SET_WINDOW_ATTRIBUTES_LEN,
+            Code::DefineWindow(_) => Code::DEFINE_WINDOW_LEN,
+            _ => 1,
+        })
+    }
+
+    // Parse the first Code in data.  Any bytes after the Code are ignored.
+    pub(crate) fn parse_element(data: &[u8]) -> Result<Code, CodeError> {
         let size = Code::expected_size(data)?;
-        if data.len() > size {
+        if data.len() < size {
             return Err(CodeError::LengthMismatch {
                 expected: size,
                 actual: data.len(),
             });
         }
+        let data = &data[..size];
         if let Ok(idx) =
             CODE_MAP_TABLE.binary_search_by_key(&data, |code_map| code_map.cea708_bytes)
         {
@@ -1860,6 +2387,7 @@ impl Code {
             0x8A => parse_control_code!(data, 1, Code::HideWindows),
             0x8B => parse_control_code!(data, 1, Code::ToggleWindows),
             0x8C => parse_control_code!(data, 1, Code::DeleteWindows),
+            0x8D => Code::Delay(data[1]),
             0x90 => parse_control_code!(data, 2, Code::SetPenAttributes),
             0x91 => parse_control_code!(data, 3, Code::SetPenColor),
             0x92 => parse_control_code!(data, 2, Code::SetPenLocation),
@@ -1879,29 +2407,48 @@ impl Code {
 
     /// Parse a byte sequence into a list of [Code]s
     ///
+    /// Empty `data` contains no [Code]s and is not an error, unlike
+    /// [from_data_exact](Self::from_data_exact) which requires exactly one [Code].
+    ///
+    /// # Errors
+    ///
+    /// * [CodeError::UnexpectedEnd] if `data` ends in the middle of a [Code]
+    ///
     /// # Examples
     /// ```
     /// # use cea708_types::tables::Code;
     /// assert_eq!(Code::from_data(&[0x41]), Ok(vec![Code::LatinCapitalA]));
+    /// assert_eq!(Code::from_data(&[]), Ok(vec![]));
     /// ```
     pub fn from_data(data: &[u8]) -> Result<Vec<Code>, CodeError> {
-        let mut data_iter = data;
-        let mut ret = vec![];
-        while !data_iter.is_empty() {
-            let size = Code::expected_size(data_iter)?;
-            if data_iter.len() < size {
-                return Err(CodeError::LengthMismatch {
-                    expected: size,
-                    actual: data_iter.len(),
-                });
-            }
-            let element = &data_iter[..size];
-            let element = Code::parse_element(element)?;
-            ret.push(element);
+        CodeIterator::new(data).collect()
+    }
 
-            data_iter = &data_iter[size..];
+    /// Parse exactly one [Code] from a byte sequence
+    ///
+    /// # Errors
+    ///
+    /// * [CodeError::LengthMismatch] if `data` is not exactly the length of the [Code] it starts
+    ///   with
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::*;
+    /// assert_eq!(Code::from_data_exact(&[0x41]), Ok(Code::LatinCapitalA));
+    /// assert_eq!(
+    ///     Code::from_data_exact(&[0x41, 0x42]),
+    ///     Err(CodeError::LengthMismatch { expected: 1, actual: 2 })
+    /// );
+    /// ```
+    pub fn from_data_exact(data: &[u8]) -> Result<Code, CodeError> {
+        let size = Code::expected_size(data)?;
+        if data.len() != size {
+            return Err(CodeError::LengthMismatch {
+                expected: size,
+                actual: data.len(),
+            });
         }
-        Ok(ret)
+        Code::parse_element(data)
     }
 
     /// Write a [Code] to a byte stream
@@ -1928,6 +2475,7 @@ impl Code {
             Code::HideWindows(args) => write_control_code!(0x8A, w, *args, 1),
             Code::ToggleWindows(args) => write_control_code!(0x8B, w, *args, 1),
             Code::DeleteWindows(args) => write_control_code!(0x8C, w, *args, 1),
+            Code::Delay(tenths) => w.write_all(&[0x8D, *tenths]),
             Code::SetPenAttributes(args) => write_control_code!(0x90, w, *args, 2),
             Code::SetPenColor(args) => write_control_code!(0x91, w, *args, 3),
             Code::SetPenLocation(args) => write_control_code!(0x92, w, *args, 2),
@@ -1981,9 +2529,615 @@ impl Code {
             }
         })
     }
+
+    /// Retrieve a [Code] for an ISO 8859-1 (Latin-1) byte
+    ///
+    /// The printable ASCII range (0x20-0x7E) maps to the G0 table and the upper range
+    /// (0xA0-0xFF) maps to the G1 table.  Control bytes and 0x7F, which is a musical note in
+    /// CEA-708, return None.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Code;
+    /// assert_eq!(Code::from_latin1(b'A'), Some(Code::LatinCapitalA));
+    /// assert_eq!(Code::from_latin1(0xE9), Some(Code::LatinLowerEWithAcute));
+    /// assert_eq!(Code::from_latin1(0x0A), None);
+    /// ```
+    pub fn from_latin1(b: u8) -> Option<Code> {
+        match b {
+            0x20..=0x7E | 0xA0..=0xFF => Code::parse_element(&[b]).ok(),
+            _ => None,
+        }
+    }
+
+    /// The ISO 8859-1 (Latin-1) byte for this [Code]
+    ///
+    /// The inverse of [from_latin1](Self::from_latin1).  [Code]s without an equivalent Latin-1
+    /// byte return None.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Code;
+    /// assert_eq!(Code::LatinCapitalA.to_latin1(), Some(b'A'));
+    /// assert_eq!(Code::MusicalSymbolEighthNote.to_latin1(), None);
+    /// ```
+    pub fn to_latin1(&self) -> Option<u8> {
+        let idx = CODE_MAP_TABLE
+            .binary_search_by_key(&self, |code_map| &code_map.code)
+            .ok()?;
+        match CODE_MAP_TABLE[idx].cea708_bytes {
+            [b @ (0x20..=0x7E | 0xA0..=0xFF)] => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// The [CodeCategory] (code set) this [Code] belongs to
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::*;
+    /// assert_eq!(Code::LatinCapitalA.category(), CodeCategory::G0);
+    /// assert_eq!(Code::ETX.category(), CodeCategory::C0);
+    /// assert_eq!(Code::DelayCancel.category(), CodeCategory::C1);
+    /// assert_eq!(Code::Ext1(Ext1::TradeMarkSign).category(), CodeCategory::G2);
+    /// ```
+    pub fn category(&self) -> CodeCategory {
+        if let Ok(idx) = CODE_MAP_TABLE.binary_search_by_key(&self, |code_map| &code_map.code) {
+            return CodeCategory::from_bytes(CODE_MAP_TABLE[idx].cea708_bytes);
+        }
+        match self {
+            Code::Ext1(Ext1::Unknown(data)) => data
+                .first()
+                .map(|&byte| CodeCategory::from_ext1_byte(byte))
+                .unwrap_or(CodeCategory::C2),
+            Code::Unknown(data) => CodeCategory::from_bytes(data),
+            Code::P16(_) => CodeCategory::C0,
+            _ => CodeCategory::C1,
+        }
+    }
+
+    /// Whether this [Code] is a command rather than a character
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::*;
+    /// assert!(!Code::LatinCapitalA.is_control());
+    /// assert!(!Code::P16(0x1234).is_control());
+    /// assert!(Code::CR.is_control());
+    /// ```
+    pub fn is_control(&self) -> bool {
+        !matches!(self, Code::P16(_)) && self.category().is_control()
+    }
+
+    /// The windows this [Code] references.  Only the window commands (SetCurrentWindowN,
+    /// DefineWindow and the commands with a [WindowBits] argument) reference any windows.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::*;
+    /// assert_eq!(Code::SetCurrentWindow3.window_bits(), WindowBits::THREE);
+    /// assert_eq!(
+    ///     Code::ClearWindows(WindowBits::ZERO | WindowBits::ONE).window_bits(),
+    ///     WindowBits::ZERO | WindowBits::ONE,
+    /// );
+    /// assert_eq!(Code::LatinCapitalA.window_bits(), WindowBits::NONE);
+    /// ```
+    pub fn window_bits(&self) -> WindowBits {
+        match self {
+            Code::SetCurrentWindow0 => WindowBits::ZERO,
+            Code::SetCurrentWindow1 => WindowBits::ONE,
+            Code::SetCurrentWindow2 => WindowBits::TWO,
+            Code::SetCurrentWindow3 => WindowBits::THREE,
+            Code::SetCurrentWindow4 => WindowBits::FOUR,
+            Code::SetCurrentWindow5 => WindowBits::FIVE,
+            Code::SetCurrentWindow6 => WindowBits::SIX,
+            Code::SetCurrentWindow7 => WindowBits::SEVEN,
+            Code::DefineWindow(args) => WindowBits::from_window_id(args.window_id & 0x7),
+            Code::ClearWindows(bits)
+            | Code::DisplayWindows(bits)
+            | Code::HideWindows(bits)
+            | Code::ToggleWindows(bits)
+            | Code::DeleteWindows(bits) => *bits,
+            _ => WindowBits::NONE,
+        }
+    }
+
+    /// A short human readable name of this [Code].  Characters are named after their Unicode
+    /// character name and commands after their CEA-708 command name.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::*;
+    /// assert_eq!(Code::LatinCapitalA.display_name(), "Latin Capital Letter A");
+    /// assert_eq!(Code::Ext1(Ext1::HorizontalElipses).display_name(), "Ellipsis");
+    /// assert_eq!(Code::SetPenLocation(SetPenLocationArgs::new(1, 2)).display_name(), "SetPenLocation");
+    /// assert_eq!(Code::P16(0x1234).display_name(), "P16 Character");
+    /// ```
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Code::NUL => "Null",
+            Code::ETX => "End of Text",
+            Code::BS => "Backspace",
+            Code::FF => "Form Feed",
+            Code::CR => "Carriage Return",
+            Code::HCR => "Horizontal Carriage Return",
+            Code::Ext1(Ext1::TransparentSpace) => "Transparent Space",
+            Code::Ext1(Ext1::NonBreakingTransparentSpace) => "Non-Breaking Transparent Space",
+            Code::Ext1(Ext1::HorizontalElipses) => "Ellipsis",
+            Code::Ext1(Ext1::LatinCapitalSWithCaron) => "Latin Capital Letter S With Caron",
+            Code::Ext1(Ext1::LatinCapitalLigatureOE) => "Latin Capital Ligature OE",
+            Code::Ext1(Ext1::FullBlock) => "Full Block",
+            Code::Ext1(Ext1::SingleOpenQuote) => "Left Single Quotation Mark",
+            Code::Ext1(Ext1::SingleCloseQuote) => "Right Single Quotation Mark",
+            Code::Ext1(Ext1::DoubleOpenQuote) => "Left Double Quotation Mark",
+            Code::Ext1(Ext1::DoubleCloseQuote) => "Right Double Quotation Mark",
+            Code::Ext1(Ext1::SolidDot) => "Solid Dot",
+            Code::Ext1(Ext1::TradeMarkSign) => "Trade Mark Sign",
+            Code::Ext1(Ext1::LatinLowerSWithCaron) => "Latin Small Letter S With Caron",
+            Code::Ext1(Ext1::LatinLowerLigatureOE) => "Latin Small Ligature OE",
+            Code::Ext1(Ext1::LatinCapitalYWithDiaeresis) => "Latin Capital Letter Y With Diaeresis",
+            Code::Ext1(Ext1::Fraction18) => "Vulgar Fraction One Eighth",
+            Code::Ext1(Ext1::Fraction38) => "Vulgar Fraction Three Eighths",
+            Code::Ext1(Ext1::Fraction58) => "Vulgar Fraction Five Eighths",
+            Code::Ext1(Ext1::Fraction78) => "Vulgar Fraction Seven Eighths",
+            Code::Ext1(Ext1::VerticalBorder) => "Vertical Border",
+            Code::Ext1(Ext1::UpperRightBorder) => "Upper Right Border",
+            Code::Ext1(Ext1::LowerLeftBorder) => "Lower Left Border",
+            Code::Ext1(Ext1::HorizontalBorder) => "Horizontal Border",
+            Code::Ext1(Ext1::LowerRightBorder) => "Lower Right Border",
+            Code::Ext1(Ext1::UpperLeftBorder) => "Upper Left Border",
+            Code::Ext1(Ext1::ClosedCaptionSign) => "Closed Caption Sign",
+            Code::Space => "Space",
+            Code::ExclamationMark => "Exclamation Mark",
+            Code::QuotationMark => "Quotation Mark",
+            Code::NumberSign => "Number Sign",
+            Code::DollarSign => "Dollar Sign",
+            Code::PercentSign => "Percent Sign",
+            Code::Ampersand => "Ampersand",
+            Code::Apostrophe => "Apostrophe",
+            Code::LeftParenthesis => "Left Parenthesis",
+            Code::RightParenthesis => "Right Parenthesis",
+            Code::Asterisk => "Asterisk",
+            Code::PlusSign => "Plus Sign",
+            Code::Comma => "Comma",
+            Code::HyphenMinus => "Hyphen-Minus",
+            Code::FullStop => "Full Stop",
+            Code::Solidus => "Solidus",
+            Code::Zero => "Digit Zero",
+            Code::One => "Digit One",
+            Code::Two => "Digit Two",
+            Code::Three => "Digit Three",
+            Code::Four => "Digit Four",
+            Code::Five => "Digit Five",
+            Code::Six => "Digit Six",
+            Code::Seven => "Digit Seven",
+            Code::Eight => "Digit Eight",
+            Code::Nine => "Digit Nine",
+            Code::Colon => "Colon",
+            Code::SemiColon => "Semicolon",
+            Code::LessThan => "Less-Than Sign",
+            Code::Equals => "Equals Sign",
+            Code::GreaterThan => "Greater-Than Sign",
+            Code::QuestionMark => "Question Mark",
+            Code::CommercialAt => "Commercial At",
+            Code::LatinCapitalA => "Latin Capital Letter A",
+            Code::LatinCapitalB => "Latin Capital Letter B",
+            Code::LatinCapitalC => "Latin Capital Letter C",
+            Code::LatinCapitalD => "Latin Capital Letter D",
+            Code::LatinCapitalE => "Latin Capital Letter E",
+            Code::LatinCapitalF => "Latin Capital Letter F",
+            Code::LatinCapitalG => "Latin Capital Letter G",
+            Code::LatinCapitalH => "Latin Capital Letter H",
+            Code::LatinCapitalI => "Latin Capital Letter I",
+            Code::LatinCapitalJ => "Latin Capital Letter J",
+            Code::LatinCapitalK => "Latin Capital Letter K",
+            Code::LatinCapitalL => "Latin Capital Letter L",
+            Code::LatinCapitalM => "Latin Capital Letter M",
+            Code::LatinCapitalN => "Latin Capital Letter N",
+            Code::LatinCapitalO => "Latin Capital Letter O",
+            Code::LatinCapitalP => "Latin Capital Letter P",
+            Code::LatinCapitalQ => "Latin Capital Letter Q",
+            Code::LatinCapitalR => "Latin Capital Letter R",
+            Code::LatinCapitalS => "Latin Capital Letter S",
+            Code::LatinCapitalT => "Latin Capital Letter T",
+            Code::LatinCapitalU => "Latin Capital Letter U",
+            Code::LatinCapitalV => "Latin Capital Letter V",
+            Code::LatinCapitalW => "Latin Capital Letter W",
+            Code::LatinCapitalX => "Latin Capital Letter X",
+            Code::LatinCapitalY => "Latin Capital Letter Y",
+            Code::LatinCapitalZ => "Latin Capital Letter Z",
+            Code::LeftSquareBracket => "Left Square Bracket",
+            Code::ReverseSolidus => "Reverse Solidus",
+            Code::RightSquareBracket => "Right Square Bracket",
+            Code::CircumflexAccent => "Circumflex Accent",
+            Code::LowLine => "Low Line",
+            Code::GraveAccent => "Grave Accent",
+            Code::LatinLowerA => "Latin Small Letter A",
+            Code::LatinLowerB => "Latin Small Letter B",
+            Code::LatinLowerC => "Latin Small Letter C",
+            Code::LatinLowerD => "Latin Small Letter D",
+            Code::LatinLowerE => "Latin Small Letter E",
+            Code::LatinLowerF => "Latin Small Letter F",
+            Code::LatinLowerG => "Latin Small Letter G",
+            Code::LatinLowerH => "Latin Small Letter H",
+            Code::LatinLowerI => "Latin Small Letter I",
+            Code::LatinLowerJ => "Latin Small Letter J",
+            Code::LatinLowerK => "Latin Small Letter K",
+            Code::LatinLowerL => "Latin Small Letter L",
+            Code::LatinLowerM => "Latin Small Letter M",
+            Code::LatinLowerN => "Latin Small Letter N",
+            Code::LatinLowerO => "Latin Small Letter O",
+            Code::LatinLowerP => "Latin Small Letter P",
+            Code::LatinLowerQ => "Latin Small Letter Q",
+            Code::LatinLowerR => "Latin Small Letter R",
+            Code::LatinLowerS => "Latin Small Letter S",
+            Code::LatinLowerT => "Latin Small Letter T",
+            Code::LatinLowerU => "Latin Small Letter U",
+            Code::LatinLowerV => "Latin Small Letter V",
+            Code::LatinLowerW => "Latin Small Letter W",
+            Code::LatinLowerX => "Latin Small Letter X",
+            Code::LatinLowerY => "Latin Small Letter Y",
+            Code::LatinLowerZ => "Latin Small Letter Z",
+            Code::LeftCurlyBracket => "Left Curly Bracket",
+            Code::VerticalLine => "Vertical Line",
+            Code::RightCurlyBracket => "Right Curly Bracket",
+            Code::Tilde => "Tilde",
+            Code::MusicalSymbolEighthNote => "Eighth Note",
+            Code::SetCurrentWindow0 => "SetCurrentWindow0",
+            Code::SetCurrentWindow1 => "SetCurrentWindow1",
+            Code::SetCurrentWindow2 => "SetCurrentWindow2",
+            Code::SetCurrentWindow3 => "SetCurrentWindow3",
+            Code::SetCurrentWindow4 => "SetCurrentWindow4",
+            Code::SetCurrentWindow5 => "SetCurrentWindow5",
+            Code::SetCurrentWindow6 => "SetCurrentWindow6",
+            Code::SetCurrentWindow7 => "SetCurrentWindow7",
+            Code::DelayCancel => "DelayCancel",
+            Code::Reset => "Reset",
+            Code::NonBreakingSpace => "No-Break Space",
+            Code::InvertedExclamationMark => "Inverted Exclamation Mark",
+            Code::CentSign => "Cent Sign",
+            Code::PoundSign => "Pound Sign",
+            Code::GeneralCurrencySign => "Currency Sign",
+            Code::YenSign => "Yen Sign",
+            Code::BrokenVerticalBar => "Broken Bar",
+            Code::SectionSign => "Section Sign",
+            Code::Umlaut => "Diaeresis",
+            Code::CopyrightSign => "Copyright Sign",
+            Code::FeminineOrdinalSign => "Feminine Ordinal Indicator",
+            Code::LeftDoubleAngleQuote => "Left-Pointing Double Angle Quotation Mark",
+            Code::LogicalNotSign => "Not Sign",
+            Code::SoftHyphen => "Soft Hyphen",
+            Code::RegisteredTrademarkSign => "Circled Latin Capital Letter R",
+            Code::SpacingMacronLongAccent => "Macron",
+            Code::DegreeSign => "Degree Sign",
+            Code::PlusOrMinusSign => "Plus-Minus Sign",
+            Code::Superscript2 => "Superscript Two",
+            Code::Superscript3 => "Superscript Three",
+            Code::SpacingAccuteAccent => "Acute Accent",
+            Code::MicroSign => "Micro Sign",
+            Code::ParagraphSign => "Pilcrow Sign",
+            Code::MiddleDot => "Middle Dot",
+            Code::SpacingCedilla => "Cedilla",
+            Code::Superscript1 => "Superscript One",
+            Code::MasculineOrdinalSign => "Masculine Ordinal Indicator",
+            Code::RightDoubleAngleQuote => "Right-Pointing Double Angle Quotation Mark",
+            Code::Fraction14 => "Vulgar Fraction One Quarter",
+            Code::Fraction12 => "Vulgar Fraction One Half",
+            Code::Fraction34 => "Vulgar Fraction Three Quarters",
+            Code::InvertedQuestionMark => "Inverted Question Mark",
+            Code::LatinCapitalAWithGrave => "Latin Capital Letter A With Grave",
+            Code::LatinCapitalAWithAcute => "Latin Capital Letter A With Acute",
+            Code::LatinCapitalAWithCircumflex => "Latin Capital Letter A With Circumflex",
+            Code::LatinCapitalAWithTilde => "Latin Capital Letter A With Tilde",
+            Code::LatinCapitalAWithDiaeresis => "Latin Capital Letter A With Diaeresis",
+            Code::LatinCapitalAWithRingAbove => "Latin Capital Letter A With Ring Above",
+            Code::LatinCapitalAe => "Latin Capital Letter AE",
+            Code::LatinCapitalCWithCedilla => "Latin Capital Letter C With Cedilla",
+            Code::LatinCapitalEWithGrave => "Latin Capital Letter E With Grave",
+            Code::LatinCapitalEWithAcute => "Latin Capital Letter E With Acute",
+            Code::LatinCapitalEWithCircumflex => "Latin Capital Letter E With Circumflex",
+            Code::LatinCapitalEWithDiaeseris => "Latin Capital Letter E With Diaeresis",
+            Code::LatinCapitalIWithGrave => "Latin Capital Letter I With Grave",
+            Code::LatinCapitalIWithAcute => "Latin Capital Letter I With Acute",
+            Code::LatinCapitalIWithCircumflex => "Latin Capital Letter I With Circumflex",
+            Code::LatinCapitalIWithDiaeseris => "Latin Capital Letter I With Diaeresis",
+            Code::LatinCapitalEth => "Latin Capital Letter D With Stroke",
+            Code::LatinCapitalNWithTilde => "Latin Capital Letter N With Tilde",
+            Code::LatinCapitalOWithGrave => "Latin Capital Letter O With Grave",
+            Code::LatinCapitalOWithAcute => "Latin Capital Letter O With Acute",
+            Code::LatinCapitalOWithCircumflex => "Latin Capital Letter O With Circumflex",
+            Code::LatinCapitalOWithTilde => "Latin Capital Letter O With Tilde",
+            Code::LatinCapitalOWithDiaeresis => "Latin Capital Letter O With Diaeresis",
+            Code::MultiplicationSign => "Multiplication Sign",
+            Code::LatinCapitalOWithStroke => "Latin Capital Letter O With Stroke",
+            Code::LatinCapitalUWithGrave => "Latin Capital Letter U With Grave",
+            Code::LatinCapitalUWithAcute => "Latin Capital Letter U With Acute",
+            Code::LatinCapitalUWithCircumflex => "Latin Capital Letter U With Circumflex",
+            Code::LatinCapitalUWithDiaeresis => "Latin Capital Letter U With Diaeresis",
+            Code::LatinCapitalYWithAcute => "Latin Capital Letter Y With Acute",
+            Code::LatinCapitalThorn => "Latin Capital Letter Thorn",
+            Code::LatinLowerSharpS => "Latin Small Letter Sharp S",
+            Code::LatinLowerAWithGrave => "Latin Small Letter A With Grave",
+            Code::LatinLowerAWithAcute => "Latin Small Letter A With Acute",
+            Code::LatinLowerAWithCircumflex => "Latin Small Letter A With Circumflex",
+            Code::LatinLowerAWithTilde => "Latin Small Letter A With Tilde",
+            Code::LatinLowerAWithDiaeresis => "Latin Small Letter A With Diaeresis",
+            Code::LatinLowerAWithRingAbove => "Latin Small Letter A With Ring Above",
+            Code::LatinLowerAe => "Latin Small Letter AE",
+            Code::LatinLowerCWithCedilla => "Latin Small Letter C With Cedilla",
+            Code::LatinLowerEWithGrave => "Latin Small Letter E With Grave",
+            Code::LatinLowerEWithAcute => "Latin Small Letter E With Acute",
+            Code::LatinLowerEWithCircumflex => "Latin Small Letter E With Circumflex",
+            Code::LatinLowerEWithDiaeseris => "Latin Small Letter E With Diaeresis",
+            Code::LatinLowerIWithGrave => "Latin Small Letter I With Grave",
+            Code::LatinLowerIWithAcute => "Latin Small Letter I With Acute",
+            Code::LatinLowerIWithCircumflex => "Latin Small Letter I With Circumflex",
+            Code::LatinLowerIWithDiaeseris => "Latin Small Letter I With Diaeresis",
+            Code::LatinLowerEth => "Latin Small Letter Eth",
+            Code::LatinLowerNWithTilde => "Latin Small Letter N With Tilde",
+            Code::LatinLowerOWithGrave => "Latin Small Letter O With Grave",
+            Code::LatinLowerOWithAcute => "Latin Small Letter O With Acute",
+            Code::LatinLowerOWithCircumflex => "Latin Small Letter O With Circumflex",
+            Code::LatinLowerOWithTilde => "Latin Small Letter O With Tilde",
+            Code::LatinLowerOWithDiaeresis => "Latin Small Letter O With Diaeresis",
+            Code::DivisionSign => "Division Sign",
+            Code::LatinLowerOWithStroke => "Latin Small Letter O With Stroke",
+            Code::LatinLowerUWithGrave => "Latin Small Letter U With Grave",
+            Code::LatinLowerUWithAcute => "Latin Small Letter U With Acute",
+            Code::LatinLowerUWithCircumflex => "Latin Small Letter U With Circumflex",
+            Code::LatinLowerUWithDiaeresis => "Latin Small Letter U With Diaeresis",
+            Code::LatinLowerYWithAcute => "Latin Small Letter Y With Acute",
+            Code::LatinLowerThorn => "Latin Small Letter Thorn",
+            Code::LatinLowerYWithDiaeresis => "Latin Small Letter Y With Diaeresis",
+            Code::Ext1(Ext1::Unknown(_)) => "Unknown",
+            Code::P16(_) => "P16 Character",
+            Code::ClearWindows(_) => "ClearWindows",
+            Code::DisplayWindows(_) => "DisplayWindows",
+            Code::HideWindows(_) => "HideWindows",
+            Code::ToggleWindows(_) => "ToggleWindows",
+            Code::DeleteWindows(_) => "DeleteWindows",
+            Code::Delay(_) => "Delay",
+            Code::SetPenAttributes(_) => "SetPenAttributes",
+            Code::SetPenColor(_) => "SetPenColor",
+            Code::SetPenLocation(_) => "SetPenLocation",
+            Code::SetWindowAttributes(_) => "SetWindowAttributes",
+            Code::DefineWindow(_) => "DefineWindow",
+            Code::Unknown(_) => "Unknown",
+        }
+    }
+
+    /// Collect the utf8 chars of a list of [Code]s into a [String]
+    ///
+    /// Only [Code]s with a [char](Self::char) are included.  All other [Code]s (commands,
+    /// including [Code::CR], and [Code::P16]) are skipped.  Use
+    /// [Service::text](crate::Service::text) to also convert [Code::CR] to a newline.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::*;
+    /// let codes = [
+    ///     Code::LatinCapitalA,
+    ///     Code::SetPenLocation(SetPenLocationArgs::new(1, 2)),
+    ///     Code::CR,
+    ///     Code::LatinLowerB,
+    ///     Code::Ext1(Ext1::TradeMarkSign),
+    /// ];
+    /// assert_eq!(Code::text_from_codes(&codes), "Ab™");
+    /// ```
+    pub fn text_from_codes(codes: &[Code]) -> String {
+        codes.iter().filter_map(|code| code.char()).collect()
+    }
+
+    /// The number of utf8 chars that [text_from_codes](Self::text_from_codes) would produce
+    /// without allocating a [String]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Code;
+    /// let codes = [Code::LatinCapitalA, Code::CR, Code::ETX, Code::LatinLowerB];
+    /// assert_eq!(Code::text_char_count(&codes), 2);
+    /// ```
+    pub fn text_char_count(codes: &[Code]) -> usize {
+        codes.iter().filter(|code| code.char().is_some()).count()
+    }
+
+    /// Collect the utf8 chars of the [Code]s in a [Service](crate::Service) into a [String]
+    ///
+    /// See [text_from_codes](Self::text_from_codes).
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::{*, tables::*};
+    /// let service = Service::from_codes(1, [Code::LatinCapitalA, Code::ETX]).unwrap();
+    /// assert_eq!(Code::text_from_service(&service), "A");
+    /// ```
+    pub fn text_from_service(service: &crate::Service) -> String {
+        Code::text_from_codes(service.codes())
+    }
+}
+
+impl From<&Code> for Code {
+    fn from(code: &Code) -> Self {
+        code.clone()
+    }
+}
+
+impl From<Ext1> for Code {
+    fn from(ext1: Ext1) -> Self {
+        Code::Ext1(ext1)
+    }
+}
+
+impl From<SetPenAttributesArgs> for Code {
+    fn from(args: SetPenAttributesArgs) -> Self {
+        Code::SetPenAttributes(args)
+    }
+}
+
+impl From<SetPenColorArgs> for Code {
+    fn from(args: SetPenColorArgs) -> Self {
+        Code::SetPenColor(args)
+    }
 }
 
+impl From<SetPenLocationArgs> for Code {
+    fn from(args: SetPenLocationArgs) -> Self {
+        Code::SetPenLocation(args)
+    }
+}
+
+impl From<SetWindowAttributesArgs> for Code {
+    fn from(args: SetWindowAttributesArgs) -> Self {
+        Code::SetWindowAttributes(args)
+    }
+}
+
+impl From<DefineWindowArgs> for Code {
+    fn from(args: DefineWindowArgs) -> Self {
+        Code::DefineWindow(args)
+    }
+}
+
+/// Characters are written as their utf8 char and commands are written using their
+/// [Code::display_name].
+///
+/// # Examples
+/// ```
+/// # use cea708_types::tables::*;
+/// assert_eq!(Code::LatinCapitalA.to_string(), "A");
+/// assert_eq!(Code::ETX.to_string(), "End of Text");
+/// assert_eq!(Code::SetPenLocation(SetPenLocationArgs::new(1, 2)).to_string(), "SetPenLocation");
+/// ```
+impl std::fmt::Display for Code {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(c) = self.char() {
+            write!(f, "{c}")
+        } else {
+            f.write_str(self.display_name())
+        }
+    }
+}
+
+/// Characters are written as their utf8 char and anything else using the
+/// [Code::display_name] of the matching [Code::Ext1].
+///
+/// # Examples
+/// ```
+/// # use cea708_types::tables::*;
+/// assert_eq!(Ext1::HorizontalElipses.to_string(), "…");
+/// assert_eq!(Ext1::Unknown(vec![0x00]).to_string(), "Unknown");
+/// ```
+impl std::fmt::Display for Ext1 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(c) = self.char() {
+            write!(f, "{c}")
+        } else if let Ext1::Unknown(_) = self {
+            f.write_str("Unknown")
+        } else {
+            f.write_str(Code::Ext1(self.clone()).display_name())
+        }
+    }
+}
+
+/// An [Iterator] lazily parsing [Code]s from a byte sequence
+///
+/// Iteration stops after the first error is returned.
+///
+/// # Examples
+/// ```
+/// # use cea708_types::tables::{Code, CodeIterator};
+/// let mut iter = CodeIterator::new(&[0x41, 0x42]);
+/// assert_eq!(iter.next(), Some(Ok(Code::LatinCapitalA)));
+/// assert_eq!(iter.next(), Some(Ok(Code::LatinCapitalB)));
+/// assert_eq!(iter.next(), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CodeIterator<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> CodeIterator<'a> {
+    /// Create a new [CodeIterator] over `data`
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    /// The bytes that have not been parsed yet
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl<'a> Iterator for CodeIterator<'a> {
+    type Item = Result<Code, CodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let ret = Code::expected_size(self.data)
+            .and_then(|size| {
+                let code = Code::parse_element(self.data)?;
+                self.data = &self.data[size..];
+                self.offset += size;
+                Ok(code)
+            })
+            .map_err(|err| match err {
+                CodeError::LengthMismatch { expected, actual } => CodeError::UnexpectedEnd {
+                    offset: self.offset,
+                    expected,
+                    actual,
+                },
+                err => err,
+            });
+        if ret.is_err() {
+            self.data = &[];
+        }
+        Some(ret)
+    }
+}
+
+impl std::iter::FusedIterator for CodeIterator<'_> {}
+
 impl Ext1 {
+    /// The utf8 char for this [Ext1]
+    ///
+    /// [Ext1]s without a utf8 representation will return None.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Ext1;
+    /// assert_eq!(Ext1::TradeMarkSign.char(), Some('™'));
+    /// ```
+    pub fn char(&self) -> Option<char> {
+        CODE_MAP_TABLE
+            .iter()
+            .find_map(|code_map| match code_map.code {
+                Code::Ext1(ref ext1) if ext1 == self => code_map.utf8,
+                _ => None,
+            })
+    }
+
+    /// Retrieve an [Ext1] for a utf8 char
+    ///
+    /// If the char is not representable as an [Ext1], None will be returned.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea708_types::tables::Ext1;
+    /// assert_eq!(Ext1::from_char('™'), Some(Ext1::TradeMarkSign));
+    /// assert_eq!(Ext1::from_char('A'), None);
+    /// ```
+    pub fn from_char(c: char) -> Option<Ext1> {
+        CODE_MAP_TABLE
+            .iter()
+            .find_map(|code_map| match code_map.code {
+                Code::Ext1(ref ext1) if code_map.utf8 == Some(c) => Some(ext1.clone()),
+                _ => None,
+            })
+    }
+
     fn expected_size(bytes: &[u8]) -> Result<usize, CodeError> {
         if bytes.is_empty() {
             return Err(CodeError::LengthMismatch {
@@ -2000,13 +3154,14 @@ impl Ext1 {
             0x80..=0x87 => Ok(5),
             0x88..=0x8F => Ok(6),
             0x90..=0x9F => {
+                // variable length code, length byte, data
                 if bytes.len() < 2 {
                     return Err(CodeError::LengthMismatch {
                         expected: 2,
-                        actual: 0,
+                        actual: bytes.len(),
                     });
                 }
-                Ok(((bytes[1] & 0x3F) as usize) + 1)
+                Ok(((bytes[1] & 0x3F) as usize) + 2)
             }
             0xA0..=0xFF => Ok(1), // G3
         }
@@ -2040,6 +3195,107 @@ mod test {
     use crate::tests::*;
     use log::trace;
 
+    #[test]
+    fn code_from_data_empty() {
+        test_init_log();
+        assert_eq!(Code::from_data(&[]), Ok(vec![]));
+        assert!(CodeIterator::new(&[]).next().is_none());
+        assert_eq!(
+            Code::from_data_exact(&[]),
+            Err(CodeError::LengthMismatch {
+                expected: 1,
+                actual: 0
+            })
+        );
+    }
+
+    #[test]
+    fn code_from_data_exact() {
+        test_init_log();
+        for code_map in CODE_MAP_TABLE.iter().chain(VARIABLE_TEST_CODES.iter()) {
+            assert_eq!(
+                Code::from_data_exact(code_map.cea708_bytes).as_ref(),
+                Ok(&code_map.code)
+            );
+        }
+        // SetPenLocation
+        assert_eq!(
+            Code::from_data_exact(&[0x92, 0x01]),
+            Err(CodeError::LengthMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            Code::from_data_exact(&[0x92, 0x01, 0x02, 0x41]),
+            Err(CodeError::LengthMismatch {
+                expected: 3,
+                actual: 4
+            })
+        );
+        assert_eq!(
+            Code::from_data_exact(&[]),
+            Err(CodeError::LengthMismatch {
+                expected: 1,
+                actual: 0
+            })
+        );
+    }
+
+    #[test]
+    fn code_from_data_truncated_offset() {
+        test_init_log();
+        // 'A', 'B', then a SetPenColor missing its last argument byte
+        assert_eq!(
+            Code::from_data(&[0x41, 0x42, 0x91, 0x01, 0x02]),
+            Err(CodeError::UnexpectedEnd {
+                offset: 2,
+                expected: 4,
+                actual: 3
+            })
+        );
+        // a DefineWindow after a SetPenLocation
+        assert_eq!(
+            Code::from_data(&[0x92, 0x01, 0x02, 0x98, 0x00]),
+            Err(CodeError::UnexpectedEnd {
+                offset: 3,
+                expected: 7,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            crate::ParserError::from(CodeError::UnexpectedEnd {
+                offset: 3,
+                expected: 7,
+                actual: 2
+            }),
+            crate::ParserError::LengthMismatch {
+                expected: 10,
+                actual: 5
+            }
+        );
+    }
+
+    #[test]
+    fn code_display_names_unique() {
+        test_init_log();
+        let mut names = std::collections::HashSet::new();
+        for code_map in CODE_MAP_TABLE.iter() {
+            let name = code_map.code.display_name();
+            assert!(!name.is_empty());
+            assert!(
+                names.insert(name),
+                "duplicate name {name} for {:?}",
+                code_map.code
+            );
+        }
+        assert_eq!(Code::Unknown(vec![0x93]).display_name(), "Unknown");
+        assert_eq!(
+            Code::Ext1(Ext1::Unknown(vec![0x00])).display_name(),
+            "Unknown"
+        );
+    }
+
     #[test]
     fn codes_table_ordered() {
         test_init_log();
@@ -2053,7 +3309,7 @@ mod test {
         }
     }
 
-    static VARIABLE_TEST_CODES: [CodeMap; 10] = [
+    static VARIABLE_TEST_CODES: [CodeMap; 13] = [
         code_map_bytes!(
             [0x9A, 0x38, 0x4A, 0xD1, 0x8B, 0x0F, 0x11],
             Code::DefineWindow(DefineWindowArgs::new(
@@ -2148,6 +3404,9 @@ mod test {
             Code::SetPenLocation(SetPenLocationArgs::new(5, 8)),
             None
         ),
+        code_map_bytes!([0x8D, 0x00], Code::Delay(0), None),
+        code_map_bytes!([0x8D, 0x7F], Code::Delay(127), None),
+        code_map_bytes!([0x8D, 0xFF], Code::Delay(255), None),
     ];
 
     #[test]
@@ -2164,6 +3423,263 @@ mod test {
         }
     }
 
+    #[test]
+    fn code_delay() {
+        test_init_log();
+        assert_eq!(
+            Code::from_data(&[0x8D, 0x0A, 0x41, 0x8E]),
+            Ok(vec![
+                Code::Delay(10),
+                Code::LatinCapitalA,
+                Code::DelayCancel
+            ])
+        );
+        let mut written = vec![];
+        Code::DelayCancel.write(&mut written).unwrap();
+        assert_eq!(written, [0x8E]);
+        assert_eq!(Code::DelayCancel.byte_len(), 1);
+    }
+
+    #[test]
+    fn args_default() {
+        test_init_log();
+        assert_eq!(Color::default(), Color::BLACK);
+        assert_eq!(SetPenLocationArgs::default(), SetPenLocationArgs::new(0, 0));
+        let window = SetWindowAttributesArgs::default();
+        assert_eq!(window.justify, Justify::Left);
+        assert_eq!(window.fill_opacity, Opacity::Solid);
+        let define = DefineWindowArgs {
+            window_id: 3,
+            visible: true,
+            ..Default::default()
+        };
+        assert_eq!(define.anchor_point, Anchor::TopLeft);
+        assert_eq!(define.window_attributes(), window);
+        assert_eq!(define.pen_attributes(), SetPenAttributesArgs::default());
+        assert_eq!(define.pen_color(), SetPenColorArgs::default());
+        assert_eq!(SetPenColorArgs::default().foreground_color, Color::WHITE);
+    }
+
+    #[test]
+    fn codes_latin1() {
+        test_init_log();
+        for b in 0..=0xFFu8 {
+            let code = Code::from_latin1(b);
+            trace!("0x{b:02x} maps to {code:?}");
+            match b {
+                0x20..=0x7E | 0xA0..=0xFF => {
+                    let code = code.unwrap();
+                    let mut written = vec![];
+                    code.write(&mut written).unwrap();
+                    assert_eq!(written, [b]);
+                    assert!(code.char().is_some());
+                    assert_eq!(code.to_latin1(), Some(b));
+                }
+                _ => assert_eq!(code, None),
+            }
+        }
+        assert_eq!(Code::CR.to_latin1(), None);
+        assert_eq!(Code::Ext1(Ext1::TradeMarkSign).to_latin1(), None);
+        assert_eq!(Code::P16(0x41).to_latin1(), None);
+    }
+
+    #[test]
+    fn args_builder() {
+        test_init_log();
+        let built = DefineWindowArgs::builder()
+            .window_id(2)
+            .priority(3)
+            .anchor_point(Anchor::BottomRight)
+            .relative_positioning(true)
+            .anchor_vertical(74)
+            .anchor_horizontal(209)
+            .row_count(11)
+            .column_count(15)
+            .row_lock(true)
+            .column_lock(true)
+            .visible(true)
+            .window_style_id(2)
+            .pen_style_id(1)
+            .build();
+        assert_eq!(
+            built,
+            DefineWindowArgs::new(
+                2,
+                3,
+                Anchor::BottomRight,
+                true,
+                74,
+                209,
+                11,
+                15,
+                true,
+                true,
+                true,
+                2,
+                1
+            )
+        );
+        assert_eq!(
+            DefineWindowArgsBuilder::new().build(),
+            DefineWindowArgs::default()
+        );
+
+        let built = SetWindowAttributesArgs::builder()
+            .border_type(BorderType::Raised)
+            .border_color(Color::RED)
+            .build();
+        assert_eq!(
+            built,
+            SetWindowAttributesArgs {
+                border_type: BorderType::Raised,
+                border_color: Color::RED,
+                ..Default::default()
+            }
+        );
+
+        let built = SetPenAttributesArgs::builder()
+            .pen_size(PenSize::Large)
+            .edge_type(EdgeType::Raised)
+            .build();
+        assert_eq!(
+            built,
+            SetPenAttributesArgs {
+                pen_size: PenSize::Large,
+                edge_type: EdgeType::Raised,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn code_static_byte_len() {
+        test_init_log();
+        for code_map in CODE_MAP_TABLE.iter().chain(VARIABLE_TEST_CODES.iter()) {
+            assert_eq!(
+                code_map.code.static_byte_len(),
+                Some(code_map.cea708_bytes.len()),
+                "{:?}",
+                code_map.code
+            );
+        }
+        for (code, len) in [
+            (
+                Code::ClearWindows(WindowBits::ZERO),
+                Code::WINDOWS_COMMAND_LEN,
+            ),
+            (
+                Code::DisplayWindows(WindowBits::ZERO),
+                Code::WINDOWS_COMMAND_LEN,
+            ),
+            (
+                Code::HideWindows(WindowBits::ZERO),
+                Code::WINDOWS_COMMAND_LEN,
+            ),
+            (
+                Code::ToggleWindows(WindowBits::ZERO),
+                Code::WINDOWS_COMMAND_LEN,
+            ),
+            (
+                Code::DeleteWindows(WindowBits::ZERO),
+                Code::WINDOWS_COMMAND_LEN,
+            ),
+            (Code::Delay(0), Code::DELAY_LEN),
+            (
+                Code::SetPenAttributes(SetPenAttributesArgs::default()),
+                Code::SET_PEN_ATTRIBUTES_LEN,
+            ),
+            (
+                Code::SetPenColor(SetPenColorArgs::default()),
+                Code::SET_PEN_COLOR_LEN,
+            ),
+            (
+                Code::SetPenLocation(SetPenLocationArgs::default()),
+                Code::SET_PEN_LOCATION_LEN,
+            ),
+            (
+                Code::SetWindowAttributes(SetWindowAttributesArgs::default()),
+                Code::SET_WINDOW_ATTRIBUTES_LEN,
+            ),
+            (
+                Code::DefineWindow(DefineWindowArgs::default()),
+                Code::DEFINE_WINDOW_LEN,
+            ),
+        ] {
+            assert_eq!(code.byte_len(), len);
+            let mut written = vec![];
+            code.write(&mut written).unwrap();
+            assert_eq!(written.len(), len);
+        }
+        assert_eq!(
+            Code::Ext1(Ext1::Unknown(vec![0x90, 0x01])).static_byte_len(),
+            None
+        );
+    }
+
+    #[test]
+    fn ext1_unknown_byte_len() {
+        test_init_log();
+        // the EXT1 (0x10) prefix byte is part of the code
+        for data in [
+            vec![0x10, 0x00],
+            vec![0x10, 0x08, 0x01],
+            vec![0x10, 0x10, 0x01, 0x02],
+            vec![0x10, 0x18, 0x01, 0x02, 0x03],
+            vec![0x10, 0x80, 0x01, 0x02, 0x03, 0x04],
+            vec![0x10, 0x90, 0x01, 0x05],
+        ] {
+            let code = Code::from_data_exact(&data).unwrap();
+            assert_eq!(code, Code::Ext1(Ext1::Unknown(data[1..].to_vec())));
+            assert_eq!(code.byte_len(), data.len());
+            let mut written = vec![];
+            code.write(&mut written).unwrap();
+            assert_eq!(written, data);
+        }
+    }
+
+    #[test]
+    fn codes_text() {
+        test_init_log();
+        let codes = [
+            Code::LatinCapitalA,
+            Code::SetPenLocation(SetPenLocationArgs::new(1, 2)),
+            Code::Ext1(Ext1::TradeMarkSign),
+            Code::CR,
+            Code::LatinLowerB,
+            Code::ETX,
+            Code::P16(0x263A),
+        ];
+        assert_eq!(Code::text_from_codes(&codes), "A™b");
+        assert_eq!(Code::text_char_count(&codes), 3);
+        assert_eq!(Code::text_from_codes(&[]), "");
+        assert_eq!(Code::text_char_count(&[]), 0);
+        let service = crate::Service::from_codes(1, codes.iter().cloned()).unwrap();
+        assert_eq!(Code::text_from_service(&service), "A™b");
+        assert_eq!(
+            Code::text_char_count(service.codes()),
+            service
+                .codes()
+                .iter()
+                .filter(|code| code.char().is_some())
+                .count()
+        );
+        assert_eq!(service.first_printable(), Some(&Code::LatinCapitalA));
+        assert_eq!(service.text(), "A™\nb☺");
+    }
+
+    #[test]
+    fn codes_hash() {
+        test_init_log();
+        let mut set = std::collections::HashSet::new();
+        for code_map in CODE_MAP_TABLE.iter().chain(VARIABLE_TEST_CODES.iter()) {
+            assert!(set.insert(code_map.code.clone()));
+        }
+        assert!(!set.insert(Code::LatinCapitalA));
+        assert!(!set.insert(Code::SetPenLocation(SetPenLocationArgs::new(5, 8))));
+        assert!(set.insert(Code::SetPenLocation(SetPenLocationArgs::new(5, 9))));
+        assert!(set.contains(&Code::Ext1(Ext1::TradeMarkSign)));
+    }
+
     #[test]
     fn codes_to_from_char() {
         test_init_log();
@@ -2180,6 +3696,177 @@ mod test {
         }
     }
 
+    #[test]
+    fn ext1_variable_length_expected_size() {
+        test_init_log();
+        for c3 in 0x90..=0x9F {
+            for len in [0u8, 1, 5, 31, 63] {
+                // the upper bits of the length byte are not part of the length
+                for len_byte in [len, len | 0xC0] {
+                    let mut data = vec![0x10, c3, len_byte];
+                    data.extend((0..len).map(|i| i.wrapping_mul(7)));
+                    assert_eq!(Code::expected_size(&data), Ok(3 + len as usize));
+                    let code = Code::from_data_exact(&data).unwrap();
+                    assert_eq!(code, Code::Ext1(Ext1::Unknown(data[1..].to_vec())));
+                    assert_eq!(code.byte_len(), data.len());
+                    let mut written = vec![];
+                    code.write(&mut written).unwrap();
+                    assert_eq!(written, data);
+
+                    // followed by another code
+                    data.push(0x41);
+                    assert_eq!(Code::from_data(&data), Ok(vec![code, Code::LatinCapitalA]));
+                }
+            }
+            assert_eq!(
+                Code::from_data(&[0x10, c3]),
+                Err(CodeError::UnexpectedEnd {
+                    offset: 0,
+                    expected: 2,
+                    actual: 1
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn parse_element_trailing_data() {
+        test_init_log();
+        assert_eq!(Code::parse_element(&[0x41, 0x42]), Ok(Code::LatinCapitalA));
+        assert_eq!(Code::parse_element(&[0x8D, 0x05, 0x41]), Ok(Code::Delay(5)));
+        assert_eq!(
+            Code::parse_element(&[0x10, 0x20, 0x41]),
+            Ok(Code::Ext1(Ext1::TransparentSpace))
+        );
+        assert_eq!(
+            Code::parse_element(&[0x92, 0x01]),
+            Err(CodeError::LengthMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn ext1_to_from_char() {
+        test_init_log();
+        assert_eq!(Ext1::HorizontalElipses.char(), Some('…'));
+        assert_eq!(Ext1::from_char('…'), Some(Ext1::HorizontalElipses));
+        assert_eq!(Ext1::HorizontalElipses.to_string(), "…");
+        assert_eq!(Ext1::TradeMarkSign.char(), Some('™'));
+        assert_eq!(Ext1::from_char('™'), Some(Ext1::TradeMarkSign));
+        assert_eq!(Ext1::TradeMarkSign.to_string(), "™");
+        for code_map in CODE_MAP_TABLE.iter() {
+            if let Code::Ext1(ref ext1) = code_map.code {
+                assert_eq!(ext1.char(), code_map.code.char());
+                if let Some(c) = code_map.utf8 {
+                    assert_eq!(Ext1::from_char(c).as_ref(), Some(ext1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn anchor_position() {
+        test_init_log();
+        let rows = [
+            (AnchorRow::Top, 0.0),
+            (AnchorRow::Center, 0.5),
+            (AnchorRow::Bottom, 1.0),
+        ];
+        let cols = [
+            (AnchorCol::Left, 0.0),
+            (AnchorCol::Middle, 0.5),
+            (AnchorCol::Right, 1.0),
+        ];
+        for (row, row_f32) in rows {
+            for (col, col_f32) in cols {
+                let anchor = Anchor::from_position(row, col);
+                assert!(anchor.is_defined());
+                assert_eq!(anchor.row_f32(), Some(row_f32));
+                assert_eq!(anchor.col_f32(), Some(col_f32));
+            }
+        }
+        for a in 9..16u8 {
+            let anchor = Anchor::from(a);
+            assert!(!anchor.is_defined());
+            assert_eq!(anchor.row_f32(), None);
+            assert_eq!(anchor.col_f32(), None);
+        }
+    }
+
+    #[test]
+    fn c0_reserved_commands() {
+        test_init_log();
+        // reserved 2 and 3 byte commands are kept as Unknown with their arguments
+        for byte in (0x11..=0x17).chain(0x19..=0x1F) {
+            let data = if byte < 0x18 {
+                vec![byte, 0x42]
+            } else {
+                vec![byte, 0x12, 0x34]
+            };
+            let code = Code::from_data_exact(&data).unwrap();
+            assert_eq!(code, Code::Unknown(data.clone()));
+            assert_eq!(code.byte_len(), data.len());
+            assert_eq!(code.category(), CodeCategory::C0);
+            let mut written = vec![];
+            code.write(&mut written).unwrap();
+            assert_eq!(written, data);
+        }
+    }
+
+    #[test]
+    fn direction_predicates() {
+        test_init_log();
+        for d in 0..4u8 {
+            let direction = Direction::from(d);
+            assert_ne!(direction.opposite(), direction);
+            assert_eq!(direction.opposite().opposite(), direction);
+            assert_ne!(direction.is_horizontal(), direction.is_vertical());
+            assert_eq!(
+                direction.opposite().is_horizontal(),
+                direction.is_horizontal()
+            );
+        }
+    }
+
+    #[test]
+    fn style_predicates() {
+        test_init_log();
+        for v in 0..8u8 {
+            let border_type = BorderType::from(v);
+            assert_eq!(border_type.is_shadow(), matches!(v, 4 | 5));
+            let edge_type = EdgeType::from(v);
+            assert_eq!(edge_type.is_drop_shadow(), matches!(v, 4 | 5));
+        }
+        for j in 0..4u8 {
+            assert_eq!(Justify::from(j).is_aligned(), j != 3);
+        }
+    }
+
+    #[test]
+    fn define_window_anchor_roundtrip() {
+        test_init_log();
+        for a in 0..16u8 {
+            let anchor = Anchor::from(a);
+            assert_eq!(u8::from(anchor), a);
+            let args =
+                DefineWindowArgs::new(3, 7, anchor, true, 127, 255, 15, 63, true, true, true, 7, 7);
+            let bytes: [u8; 6] = args.into();
+            assert_eq!(bytes[3] >> 4, a);
+            assert_eq!(bytes[3] & 0x0F, 15);
+            let parsed = DefineWindowArgs {
+                window_id: 3,
+                ..DefineWindowArgs::from(bytes)
+            };
+            assert_eq!(parsed, args);
+            let code = Code::DefineWindow(args);
+            let mut written = vec![];
+            code.write(&mut written).unwrap();
+            assert_eq!(Code::from_data_exact(&written), Ok(code));
+        }
+    }
+
     #[test]
     fn define_zero_style_id() {
         test_init_log();
@@ -0,0 +1,274 @@
+// Copyright (C) 2023 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for tracking when captions are displayed
+//!
+//! A [TimedCaptionState] applies [DTVCCPacket]s with their presentation timestamps to a
+//! [CaptionState] and produces a [TimedCaption] for each period that a window displays the same
+//! text.
+
+use std::time::Duration;
+
+use log::trace;
+
+use crate::renderer::CaptionState;
+use crate::tables::Anchor;
+use crate::DTVCCPacket;
+
+/// A caption that was displayed in a single window
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedCaption {
+    /// When the caption became visible
+    pub start: Duration,
+    /// When the caption stopped being visible.  `None` if the caption was still visible when
+    /// [TimedCaptionState::flush] was called.
+    pub end: Option<Duration>,
+    /// The window that displayed the caption
+    pub window_id: u8,
+    /// The text of the caption with rows separated by `\n`
+    pub text: String,
+    /// The point of the window that is placed at `position`
+    pub anchor: Anchor,
+    /// The horizontal and vertical position of the window anchor point as a fraction of the
+    /// screen size in the range `0.0..=1.0`.  See [DefineWindowArgs::anchor_position_f32].
+    ///
+    /// [DefineWindowArgs::anchor_position_f32]: crate::tables::DefineWindowArgs::anchor_position_f32
+    pub position: (f32, f32),
+}
+
+/// A [CaptionState] that records when the text of each window is visible
+///
+/// A caption ends when its window is hidden, deleted, or the visible text of the window changes.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use cea708_types::{*, tables::*, timing::*};
+/// let mut service = Service::new(1);
+/// service.push_code(DefineWindowArgs::new(
+///     0, 0, Anchor::BottomMiddle, true, 99, 50, 1, 31, true, true, true, 1, 1,
+/// )).unwrap();
+/// service.push_code(Code::LatinCapitalA).unwrap();
+/// let mut packet = DTVCCPacket::new(0);
+/// packet.push_service(service).unwrap();
+///
+/// let mut state = TimedCaptionState::new();
+/// state.apply_packet_with_pts(&packet, 1, Duration::from_secs(1));
+///
+/// let mut packet = DTVCCPacket::new(1);
+/// packet.push_service(Service::from_codes(1, [Code::HideWindows(WindowBits::ZERO)]).unwrap()).unwrap();
+/// state.apply_packet_with_pts(&packet, 1, Duration::from_secs(3));
+///
+/// let captions = state.drain_completed_captions(Duration::from_secs(3));
+/// assert_eq!(
+///     captions,
+///     [TimedCaption {
+///         start: Duration::from_secs(1),
+///         end: Some(Duration::from_secs(3)),
+///         window_id: 0,
+///         text: "A".to_string(),
+///         anchor: Anchor::BottomMiddle,
+///         position: (0.5, 0.99),
+///     }]
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TimedCaptionState {
+    state: CaptionState,
+    active: [Option<TimedCaption>; 8],
+    completed: Vec<TimedCaption>,
+}
+
+impl TimedCaptionState {
+    /// Create a new [TimedCaptionState] without any defined windows
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current [CaptionState]
+    pub fn state(&self) -> &CaptionState {
+        &self.state
+    }
+
+    /// Apply the [Code](crate::tables::Code)s of all the service blocks in `packet` with the
+    /// service number `service_no` that are presented at `pts`.
+    pub fn apply_packet_with_pts(&mut self, packet: &DTVCCPacket, service_no: u8, pts: Duration) {
+        self.state.apply_packet(packet, service_no);
+
+        for id in 0..self.active.len() {
+            let visible = self
+                .state
+                .window(id as u8)
+                .filter(|window| window.is_visible())
+                .map(|window| (window.definition(), window.to_plain_text()))
+                .filter(|(_define, text)| !text.is_empty());
+            let changed = match (&self.active[id], &visible) {
+                (Some(active), Some((_define, text))) => &active.text != text,
+                (None, None) => false,
+                _ => true,
+            };
+            if changed {
+                if let Some(mut caption) = self.active[id].take() {
+                    trace!("window {id} stops showing {:?} at {pts:?}", caption.text);
+                    caption.end = Some(pts);
+                    self.completed.push(caption);
+                }
+                if let Some((define, text)) = visible {
+                    trace!("window {id} starts showing {text:?} at {pts:?}");
+                    self.active[id] = Some(TimedCaption {
+                        start: pts,
+                        end: None,
+                        window_id: id as u8,
+                        text,
+                        anchor: define.anchor_point,
+                        position: define.anchor_position_f32(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Remove and return all the captions that stopped being visible at or before `current_pts`
+    /// ordered by their start time.
+    pub fn drain_completed_captions(&mut self, current_pts: Duration) -> Vec<TimedCaption> {
+        let (mut ret, pending) = std::mem::take(&mut self.completed)
+            .into_iter()
+            .partition::<Vec<_>, _>(|caption| caption.end.map_or(true, |end| end <= current_pts));
+        self.completed = pending;
+        ret.sort_by_key(|caption| (caption.start, caption.window_id));
+        ret
+    }
+
+    /// Remove and return all the captions including those that are still visible.  Captions that
+    /// are still visible do not have an end time.
+    pub fn flush(&mut self) -> Vec<TimedCaption> {
+        let mut ret = std::mem::take(&mut self.completed);
+        ret.extend(self.active.iter_mut().filter_map(|caption| caption.take()));
+        ret.sort_by_key(|caption| (caption.start, caption.window_id));
+        ret
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tables::*;
+    use crate::tests::*;
+    use crate::Service;
+
+    fn packet(codes: &[Code]) -> DTVCCPacket {
+        let mut packet = DTVCCPacket::new(0);
+        for chunk in codes.chunks(8) {
+            let service = Service::from_codes(1, chunk.iter().cloned()).unwrap();
+            packet.push_service(service).unwrap();
+        }
+        packet
+    }
+
+    fn define_window(id: u8, visible: bool) -> Code {
+        Code::DefineWindow(DefineWindowArgs::new(
+            id,
+            0,
+            Anchor::TopLeft,
+            false,
+            74,
+            0,
+            1,
+            9,
+            true,
+            true,
+            visible,
+            1,
+            1,
+        ))
+    }
+
+    #[test]
+    fn timed_captions() {
+        test_init_log();
+        let mut state = TimedCaptionState::new();
+        let secs = Duration::from_secs;
+        state.apply_packet_with_pts(
+            &packet(&[
+                define_window(0, true),
+                Code::LatinCapitalA,
+                define_window(1, false),
+                Code::LatinCapitalB,
+            ]),
+            1,
+            secs(1),
+        );
+        // hidden windows do not produce captions
+        assert_eq!(state.flush().len(), 1);
+
+        let mut state = TimedCaptionState::new();
+        state.apply_packet_with_pts(
+            &packet(&[
+                define_window(0, true),
+                Code::LatinCapitalA,
+                define_window(1, false),
+                Code::LatinCapitalB,
+            ]),
+            1,
+            secs(1),
+        );
+        state.apply_packet_with_pts(
+            &packet(&[Code::DisplayWindows(WindowBits::ONE)]),
+            1,
+            secs(2),
+        );
+        // the text of window 1 changes
+        state.apply_packet_with_pts(&packet(&[Code::LatinCapitalC]), 1, secs(3));
+        state.apply_packet_with_pts(&packet(&[Code::HideWindows(WindowBits::ZERO)]), 1, secs(4));
+        assert!(state.drain_completed_captions(secs(2)).is_empty());
+        assert_eq!(
+            state.drain_completed_captions(secs(3)),
+            [TimedCaption {
+                start: secs(2),
+                end: Some(secs(3)),
+                window_id: 1,
+                text: "B".to_string(),
+                anchor: Anchor::TopLeft,
+                position: (0., 1.),
+            }]
+        );
+        state.apply_packet_with_pts(&packet(&[Code::Reset]), 1, secs(5));
+        state.apply_packet_with_pts(
+            &packet(&[define_window(2, true), Code::LatinCapitalD]),
+            1,
+            secs(6),
+        );
+        assert_eq!(
+            state.flush(),
+            [
+                TimedCaption {
+                    start: secs(1),
+                    end: Some(secs(4)),
+                    window_id: 0,
+                    text: "A".to_string(),
+                    anchor: Anchor::TopLeft,
+                    position: (0., 1.),
+                },
+                TimedCaption {
+                    start: secs(3),
+                    end: Some(secs(5)),
+                    window_id: 1,
+                    text: "BC".to_string(),
+                    anchor: Anchor::TopLeft,
+                    position: (0., 1.),
+                },
+                TimedCaption {
+                    start: secs(6),
+                    end: None,
+                    window_id: 2,
+                    text: "D".to_string(),
+                    anchor: Anchor::TopLeft,
+                    position: (0., 1.),
+                },
+            ]
+        );
+    }
+}